@@ -23,23 +23,101 @@
 //! Async wrapper for `I2cdev` - runs I2cDevice in a separate thread and forwards
 //! requests from async tasks.
 
+#[cfg(test)]
+pub mod test_utils;
+
 use ii_logging::macros::*;
 
+use async_trait::async_trait;
+
 use futures::channel::mpsc;
 use futures::channel::oneshot;
 use futures::executor::block_on;
 use futures::stream::StreamExt;
 use ii_async_compat::{futures, tokio};
-use tokio::task;
+use tokio::{task, time};
 
-use embedded_hal::blocking::i2c::{Read, Write};
+use embedded_hal::blocking::i2c::{Read, Write, WriteRead};
+use linux_embedded_hal::i2cdev::linux::LinuxI2CError;
 use linux_embedded_hal::I2cdev;
 
 use crate::error::{self, ErrorKind};
 use failure::ResultExt;
 
 use std::convert::AsRef;
-use std::path::Path;
+use std::io;
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex as StdMutex, MutexGuard as StdMutexGuard};
+use std::thread;
+use std::time::Duration;
+
+/// Linux errno values of I2C errors that are likely transient (see
+/// `Documentation/i2c/fault-codes`)
+mod errno {
+    /// Generic I/O error (some bus drivers report NACK this way)
+    pub const EIO: i32 = 5;
+    /// Address was not acknowledged
+    pub const ENXIO: i32 = 6;
+    /// Bus arbitration lost
+    pub const EAGAIN: i32 = 11;
+    /// Transfer timed out (e.g. slave held the clock low)
+    pub const ETIMEDOUT: i32 = 110;
+    /// Data byte was not acknowledged
+    pub const EREMOTEIO: i32 = 121;
+}
+
+/// How to retry `Read` and `Write` requests that failed with transient error
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RetryPolicy {
+    /// Number of retries after the first failed try (0 disables retrying)
+    pub retry_count: usize,
+    /// Delay between successive tries
+    pub delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            retry_count: 2,
+            delay: Duration::from_millis(2),
+        }
+    }
+}
+
+/// Busy bus or device that is not ready yet can fail single transaction, other errors (e.g.
+/// invalid file descriptor or unsupported operation) won't go away by retrying
+fn is_retryable(error: &io::Error) -> bool {
+    match error.raw_os_error() {
+        Some(errno::EIO)
+        | Some(errno::ENXIO)
+        | Some(errno::EAGAIN)
+        | Some(errno::ETIMEDOUT)
+        | Some(errno::EREMOTEIO) => true,
+        _ => false,
+    }
+}
+
+/// Run I2C `transaction` and retry it according to `retry_policy` as long as it fails with
+/// transient error
+fn with_retry<T, F>(retry_policy: RetryPolicy, mut transaction: F) -> error::Result<T>
+where
+    F: FnMut() -> Result<T, LinuxI2CError>,
+{
+    let mut tries_left = retry_policy.retry_count;
+    loop {
+        let e = match transaction() {
+            Ok(value) => return Ok(value),
+            Err(e) => io::Error::from(e),
+        };
+        if tries_left == 0 || !is_retryable(&e) {
+            Err(e).with_context(|e| ErrorKind::I2c(e.to_string()))?
+        }
+        tries_left -= 1;
+        trace!("AsyncI2c: retrying request after transient error: {}", e);
+        thread::sleep(retry_policy.delay);
+    }
+}
 
 enum Request {
     Read {
@@ -54,6 +132,25 @@ enum Request {
         /// Channel used to send back result
         reply: oneshot::Sender<error::Result<()>>,
     },
+    /// Write followed by read without releasing the bus in between
+    WriteRead {
+        address: u8,
+        write_bytes: Vec<u8>,
+        read_len: usize,
+        /// Channel used to send back result
+        reply: oneshot::Sender<error::Result<Vec<u8>>>,
+    },
+    /// Probe all addresses in `range` for devices
+    Scan {
+        range: RangeInclusive<u8>,
+        /// Channel used to send back result
+        reply: oneshot::Sender<error::Result<Vec<u8>>>,
+    },
+    /// Close the device and stop serving requests
+    Shutdown {
+        /// Channel used to confirm the device has been closed
+        reply: oneshot::Sender<()>,
+    },
 }
 
 /// Server for I2C read/write requests
@@ -62,6 +159,7 @@ enum Request {
 fn serve_requests(
     mut i2c_device: I2cdev,
     mut request_rx: mpsc::UnboundedReceiver<Request>,
+    retry_policy: RetryPolicy,
 ) -> error::Result<()> {
     while let Some(request) = block_on(request_rx.next()) {
         match request {
@@ -71,11 +169,8 @@ fn serve_requests(
                 reply,
             } => {
                 let mut bytes = vec![0; num_bytes];
-                let result = i2c_device
-                    .read(address, &mut bytes)
-                    .with_context(|e| ErrorKind::I2c(e.to_string()))
-                    .map(|_| bytes)
-                    .map_err(|e| e.into());
+                let result = with_retry(retry_policy, || i2c_device.read(address, &mut bytes))
+                    .map(|_| bytes);
                 if reply.send(result).is_err() {
                     warn!("AsyncI2c reply send failed - remote side may have ended");
                 }
@@ -85,72 +180,698 @@ fn serve_requests(
                 bytes,
                 reply,
             } => {
+                let result = with_retry(retry_policy, || i2c_device.write(address, &bytes));
+                if reply.send(result).is_err() {
+                    warn!("AsyncI2c reply send failed - remote side may have ended");
+                }
+            }
+            Request::WriteRead {
+                address,
+                write_bytes,
+                read_len,
+                reply,
+            } => {
+                let mut bytes = vec![0; read_len];
                 let result = i2c_device
-                    .write(address, &bytes)
+                    .write_read(address, &write_bytes, &mut bytes)
                     .with_context(|e| ErrorKind::I2c(e.to_string()))
+                    .map(|_| bytes)
                     .map_err(|e| e.into());
                 if reply.send(result).is_err() {
                     warn!("AsyncI2c reply send failed - remote side may have ended");
                 }
             }
+            Request::Scan { range, reply } => {
+                let mut present = Vec::new();
+                for address in range {
+                    let mut byte = [0u8; 1];
+                    // Missing device doesn't ACK its address and the read fails. This is
+                    // expected during scan, so it's not reported as an error.
+                    match i2c_device.read(address, &mut byte) {
+                        Ok(_) => present.push(address),
+                        Err(e) => trace!("AsyncI2c scan: no device on {:#x}: {}", address, e),
+                    }
+                }
+                if reply.send(Ok(present)).is_err() {
+                    warn!("AsyncI2c reply send failed - remote side may have ended");
+                }
+            }
+            Request::Shutdown { reply } => {
+                // close the device before confirming shutdown
+                drop(i2c_device);
+                if reply.send(()).is_err() {
+                    warn!("AsyncI2c reply send failed - remote side may have ended");
+                }
+                return Ok(());
+            }
         }
     }
     Ok(())
 }
 
-/// Clonable async I2C device. I2cDevice is closed when last sender channel is dropped.
-pub struct AsyncI2cDev {
+/// Name of I2C backend that is used to serve requests
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ActiveBackend {
+    Primary,
+    Fallback,
+}
+
+/// `AsyncI2c` represents raw (register-less) transactions on an async I2C controller
+#[async_trait]
+pub trait AsyncI2c: Send + Sync {
+    /// Read `num_bytes` from device on `address` in a single transaction
+    async fn read(&self, address: u8, num_bytes: usize) -> error::Result<Vec<u8>>;
+
+    /// Write `bytes` to device on `address` in a single transaction
+    async fn write(&self, address: u8, bytes: Vec<u8>) -> error::Result<()>;
+
+    /// Write `write_bytes` to device on `address` and read `read_len` bytes back in one
+    /// combined transaction (no other request can get in between)
+    async fn write_read(
+        &self,
+        address: u8,
+        write_bytes: Vec<u8>,
+        read_len: usize,
+    ) -> error::Result<Vec<u8>>;
+
+    /// Return addresses in `range` that have a device present (that ACK one-byte read)
+    async fn scan(&self, range: RangeInclusive<u8>) -> error::Result<Vec<u8>>;
+
+    /// Close the controller, further requests fail until it's reset
+    async fn close(&self) -> error::Result<()>;
+
+    /// Reset I2C controller and start serving requests on the primary backend again
+    async fn reset_i2c_controller(&self) -> error::Result<()>;
+
+    /// Return backend that is currently used to serve requests
+    fn active_backend(&self) -> ActiveBackend {
+        ActiveBackend::Primary
+    }
+}
+
+/// Function that opens I2C device and starts serving its requests
+type Spawner = fn(&Path, RetryPolicy) -> error::Result<mpsc::UnboundedSender<Request>>;
+
+/// Open I2C device and serve its requests in a separate thread
+fn spawn_device(
+    path: &Path,
+    retry_policy: RetryPolicy,
+) -> error::Result<mpsc::UnboundedSender<Request>> {
+    let i2c_device = I2cdev::new(path).with_context(|e| ErrorKind::I2c(e.to_string()))?;
+    let (request_tx, request_rx) = mpsc::unbounded();
+
+    // Spawn the future in a separate blocking pool (for blocking operations)
+    // so that this doesn't block the regular threadpool.
+    task::spawn_blocking(move || {
+        if let Err(e) = serve_requests(i2c_device, request_rx, retry_policy) {
+            error!("{}", e);
+        }
+    });
+    Ok(request_tx)
+}
+
+/// One opened I2C device served in a separate thread. The thread terminates when the
+/// backend is dropped.
+struct Backend {
+    path: PathBuf,
     request_tx: mpsc::UnboundedSender<Request>,
+    retry_policy: RetryPolicy,
+    /// Used to open the device again
+    spawn: Spawner,
+}
+
+impl Backend {
+    /// Although this function is not async, it has to be called from within Tokio context
+    /// because it spawns task in a separate thread that serves the (blocking) I2C requests.
+    fn open(path: PathBuf, retry_policy: RetryPolicy) -> error::Result<Self> {
+        Self::open_with(path, retry_policy, spawn_device)
+    }
+
+    /// Open device with custom `spawn` function (e.g. to serve requests with fake device)
+    fn open_with(path: PathBuf, retry_policy: RetryPolicy, spawn: Spawner) -> error::Result<Self> {
+        let request_tx = spawn(&path, retry_policy)?;
+        Ok(Self {
+            path,
+            request_tx,
+            retry_policy,
+            spawn,
+        })
+    }
+
+    /// Open the same device again (with the same configuration)
+    fn reopen(&self) -> error::Result<Self> {
+        Self::open_with(self.path.clone(), self.retry_policy, self.spawn)
+    }
+}
+
+struct State {
+    primary: Backend,
+    fallback: Option<Backend>,
+    active: ActiveBackend,
+    /// Number of consecutive failed requests on primary backend
+    primary_failures: usize,
+    /// Primary backend has been reopened and no request succeeded since then
+    primary_reset: bool,
+    /// Number of consecutive requests that timed out on active backend
+    timeouts: usize,
+    /// Device has been closed and doesn't accept requests until it's reset
+    closed: bool,
+}
+
+impl State {
+    fn request_tx(&self) -> mpsc::UnboundedSender<Request> {
+        match self.active {
+            ActiveBackend::Primary => self.primary.request_tx.clone(),
+            ActiveBackend::Fallback => self
+                .fallback
+                .as_ref()
+                .expect("BUG: missing fallback I2C backend")
+                .request_tx
+                .clone(),
+        }
+    }
+
+    /// Reopen the active backend. The old serving thread may be stuck in a wedged I2C
+    /// transaction, it's left behind and terminates once its pending request finishes.
+    fn reopen_active(&mut self) -> error::Result<()> {
+        let backend = match self.active {
+            ActiveBackend::Primary => &mut self.primary,
+            ActiveBackend::Fallback => self
+                .fallback
+                .as_mut()
+                .expect("BUG: missing fallback I2C backend"),
+        };
+        *backend = backend.reopen()?;
+        self.timeouts = 0;
+        Ok(())
+    }
+
+    /// Account request that timed out and reset active backend if timeouts pile up
+    fn account_timeout(&mut self) {
+        self.timeouts += 1;
+        if self.timeouts < AsyncI2cDev::MAX_TIMEOUTS {
+            return;
+        }
+        warn!(
+            "AsyncI2c: {} consecutive requests timed out, resetting {:?} backend",
+            self.timeouts, self.active
+        );
+        if let Err(e) = self.reopen_active() {
+            error!("AsyncI2c: failed to reset backend: {}", e);
+        }
+    }
+
+    /// Account result of one request and decide whether primary backend should be reset or
+    /// replaced by the fallback one
+    fn account(&mut self, success: bool) {
+        self.timeouts = 0;
+        if self.active != ActiveBackend::Primary {
+            return;
+        }
+        if success {
+            self.primary_failures = 0;
+            self.primary_reset = false;
+            return;
+        }
+        self.primary_failures += 1;
+        if self.primary_failures < AsyncI2cDev::PRIMARY_MAX_FAILURES {
+            return;
+        }
+        self.primary_failures = 0;
+
+        if !self.primary_reset {
+            // try to reopen the device first
+            warn!(
+                "AsyncI2c: primary backend {} keeps failing, reopening it",
+                self.primary.path.display()
+            );
+            self.primary_reset = true;
+            match self.primary.reopen() {
+                Ok(backend) => {
+                    self.primary = backend;
+                    return;
+                }
+                Err(e) => error!("AsyncI2c: failed to reopen primary backend: {}", e),
+            }
+        }
+        match self.fallback.as_ref() {
+            Some(fallback) => {
+                warn!(
+                    "AsyncI2c: switching from primary backend {} to fallback backend {}",
+                    self.primary.path.display(),
+                    fallback.path.display()
+                );
+                self.active = ActiveBackend::Fallback;
+            }
+            None => warn!(
+                "AsyncI2c: primary backend {} keeps failing and there's no fallback",
+                self.primary.path.display()
+            ),
+        }
+    }
+}
+
+/// Async I2C device. I2cDevice is closed when it's dropped.
+///
+/// Device can be configured with fallback backend (another path to the same devices). It is
+/// used when the primary backend fails repeatedly and reopening it doesn't help.
+pub struct AsyncI2cDev {
+    state: StdMutex<State>,
+    /// How long to wait for reply to one request
+    request_timeout: Duration,
 }
 
-/// TODO: Make this into a trait, then implement different backends.
-/// TODO: Write tests for this and for power controller (fake async I2C with power controller,
-/// check power initialization goes as expected etc., maybe reuse I2C bus from sensors?).
 /// TODO: Reuse traits from `i2c/i2c.rs`
 impl AsyncI2cDev {
-    /// Open I2C device
+    /// Number of consecutive failed requests after which primary backend is considered
+    /// broken. It's large enough to not be triggered by retries to a single missing device.
+    const PRIMARY_MAX_FAILURES: usize = 50;
+    /// Number of consecutive timed out requests after which active backend is reset
+    const MAX_TIMEOUTS: usize = 3;
+    /// Default time to wait for reply to one request
+    pub const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(1);
+
+    /// Open I2C device, `Read` and `Write` requests that fail with transient error are retried
+    /// according to `retry_policy`
     /// Although this function is not async, it has to be called from within Tokio context
     /// because it spawns task in a separate thread that serves the (blocking) I2C requests.
-    pub fn open<P: AsRef<Path>>(path: P) -> error::Result<Self> {
-        let i2c_device = I2cdev::new(path).with_context(|e| ErrorKind::I2c(e.to_string()))?;
-        let (request_tx, request_rx) = mpsc::unbounded();
+    pub fn open<P: AsRef<Path>>(path: P, retry_policy: RetryPolicy) -> error::Result<Self> {
+        Ok(Self::from_backends(
+            Backend::open(path.as_ref().to_path_buf(), retry_policy)?,
+            None,
+        ))
+    }
 
-        // Spawn the future in a separate blocking pool (for blocking operations)
-        // so that this doesn't block the regular threadpool.
-        task::spawn_blocking(move || {
-            if let Err(e) = serve_requests(i2c_device, request_rx) {
-                error!("{}", e);
-            }
-        });
+    /// Open I2C device with fallback device which is used when the primary one fails
+    /// Same as `open` - has to be called from within Tokio context.
+    pub fn open_with_fallback<P: AsRef<Path>, Q: AsRef<Path>>(
+        path: P,
+        fallback_path: Q,
+        retry_policy: RetryPolicy,
+    ) -> error::Result<Self> {
+        Ok(Self::from_backends(
+            Backend::open(path.as_ref().to_path_buf(), retry_policy)?,
+            Some(Backend::open(
+                fallback_path.as_ref().to_path_buf(),
+                retry_policy,
+            )?),
+        ))
+    }
+
+    fn from_backends(primary: Backend, fallback: Option<Backend>) -> Self {
+        Self {
+            state: StdMutex::new(State {
+                primary,
+                fallback,
+                active: ActiveBackend::Primary,
+                primary_failures: 0,
+                primary_reset: false,
+                timeouts: 0,
+                closed: false,
+            }),
+            request_timeout: Self::DEFAULT_REQUEST_TIMEOUT,
+        }
+    }
+
+    /// Set how long to wait for reply to one request before giving up
+    pub fn with_request_timeout(mut self, request_timeout: Duration) -> Self {
+        self.request_timeout = request_timeout;
+        self
+    }
 
-        Ok(Self { request_tx })
+    fn lock_state(&self) -> StdMutexGuard<State> {
+        self.state.lock().expect("BUG: failed to lock mutex")
     }
 
-    pub async fn read(&self, address: u8, num_bytes: usize) -> error::Result<Vec<u8>> {
+    /// Send request to active backend and wait for the reply
+    async fn request<T, F>(&self, make_request: F) -> error::Result<T>
+    where
+        T: Send,
+        F: FnOnce(oneshot::Sender<error::Result<T>>) -> Request + Send,
+    {
         let (reply_tx, reply_rx) = oneshot::channel();
-        let request = Request::Read {
+        let request_tx = {
+            let state = self.lock_state();
+            if state.closed {
+                Err(ErrorKind::I2c(format!(
+                    "I2C device {} is closed",
+                    state.primary.path.display()
+                )))?
+            }
+            state.request_tx()
+        };
+        request_tx
+            .unbounded_send(make_request(reply_tx))
+            .map_err(|_| ErrorKind::I2c("I2C request failed".into()))?;
+        let result = match time::timeout(self.request_timeout, reply_rx).await {
+            Ok(reply) => reply.map_err(|_| ErrorKind::I2c("failed to receive I2C reply".into()))?,
+            Err(_) => {
+                self.lock_state().account_timeout();
+                Err(ErrorKind::Timeout(format!(
+                    "I2C request not served within {:?}",
+                    self.request_timeout
+                )))?
+            }
+        };
+        self.lock_state().account(result.is_ok());
+        result
+    }
+}
+
+#[async_trait]
+impl AsyncI2c for AsyncI2cDev {
+    async fn read(&self, address: u8, num_bytes: usize) -> error::Result<Vec<u8>> {
+        self.request(|reply| Request::Read {
             address,
             num_bytes,
-            reply: reply_tx,
-        };
-        self.request_tx
-            .unbounded_send(request)
-            .expect("I2C request failed");
-        reply_rx.await.expect("failed to receive I2C reply")
+            reply,
+        })
+        .await
     }
 
-    pub async fn write(&self, address: u8, bytes: Vec<u8>) -> error::Result<()> {
-        let (reply_tx, reply_rx) = oneshot::channel();
-        let request = Request::Write {
+    async fn write(&self, address: u8, bytes: Vec<u8>) -> error::Result<()> {
+        self.request(|reply| Request::Write {
             address,
             bytes,
-            reply: reply_tx,
+            reply,
+        })
+        .await
+    }
+
+    async fn write_read(
+        &self,
+        address: u8,
+        write_bytes: Vec<u8>,
+        read_len: usize,
+    ) -> error::Result<Vec<u8>> {
+        self.request(|reply| Request::WriteRead {
+            address,
+            write_bytes,
+            read_len,
+            reply,
+        })
+        .await
+    }
+
+    /// Close the device: stop serving threads and wait until they release `/dev/i2c-*`
+    /// (e.g. so that the controller can be reset without "device busy" error)
+    ///
+    /// Requests issued after the device has been closed fail with an error. Device can be
+    /// opened again with `reset_i2c_controller`.
+    async fn close(&self) -> error::Result<()> {
+        let request_txs = {
+            let mut state = self.lock_state();
+            if state.closed {
+                return Ok(());
+            }
+            state.closed = true;
+            let mut request_txs = vec![state.primary.request_tx.clone()];
+            if let Some(fallback) = state.fallback.as_ref() {
+                request_txs.push(fallback.request_tx.clone());
+            }
+            request_txs
         };
-        self.request_tx
-            .unbounded_send(request)
-            .expect("I2C request failed");
-        reply_rx.await.expect("failed to receive I2C reply")
+        for request_tx in request_txs {
+            let (reply_tx, reply_rx) = oneshot::channel();
+            if request_tx
+                .unbounded_send(Request::Shutdown { reply: reply_tx })
+                .is_err()
+            {
+                // serving thread has already ended
+                continue;
+            }
+            match time::timeout(self.request_timeout, reply_rx).await {
+                Ok(_) => {}
+                Err(_) => Err(ErrorKind::Timeout(format!(
+                    "I2C device not closed within {:?}",
+                    self.request_timeout
+                )))?,
+            }
+        }
+        Ok(())
+    }
+
+    /// Scan is done in a single request so that it doesn't flood the request channel
+    async fn scan(&self, range: RangeInclusive<u8>) -> error::Result<Vec<u8>> {
+        self.request(|reply| Request::Scan { range, reply }).await
+    }
+
+    /// Reopen the primary backend and switch back to it (even from the fallback one)
+    async fn reset_i2c_controller(&self) -> error::Result<()> {
+        let mut state = self.lock_state();
+        info!(
+            "AsyncI2c: resetting primary backend {}",
+            state.primary.path.display()
+        );
+        if state.closed {
+            if let Some(fallback) = state.fallback.as_mut() {
+                *fallback = fallback.reopen()?;
+            }
+        }
+        state.active = ActiveBackend::Primary;
+        state.reopen_active()?;
+        state.primary_failures = 0;
+        state.primary_reset = true;
+        state.closed = false;
+        Ok(())
+    }
+
+    fn active_backend(&self) -> ActiveBackend {
+        self.lock_state().active
     }
 }
 
-// Please somebody write tests here
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ii_async_compat::tokio;
+
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[tokio::test]
+    async fn test_fake_i2c() {
+        let i2c = test_utils::FakeI2c::new(&[0x50, 0x51]);
+        i2c.push_read_bytes(0x50, &[0xaa, 0xbb]);
+
+        i2c.write(0x50, vec![0x55, 0xaa, 0x01]).await.unwrap();
+        i2c.write(0x51, vec![0x02]).await.unwrap();
+        assert_eq!(i2c.written_bytes(0x50), vec![0x55, 0xaa, 0x01]);
+        assert_eq!(i2c.written_bytes(0x51), vec![0x02]);
+
+        assert_eq!(i2c.read(0x50, 1).await.unwrap(), vec![0xaa]);
+        assert_eq!(i2c.read(0x50, 1).await.unwrap(), vec![0xbb]);
+        i2c.push_read_bytes(0x51, &[0x12, 0x34]);
+        assert_eq!(
+            i2c.write_read(0x51, vec![0x03], 2).await.unwrap(),
+            vec![0x12, 0x34]
+        );
+        assert_eq!(i2c.written_bytes(0x51), vec![0x02, 0x03]);
+        // nothing left to read
+        assert!(i2c.read(0x50, 1).await.is_err());
+
+        // nothing present on this address
+        assert!(i2c.read(0x52, 1).await.is_err());
+        assert!(i2c.write(0x52, vec![0]).await.is_err());
+
+        assert_eq!(i2c.scan(0..=0x7f).await.unwrap(), vec![0x50, 0x51]);
+        assert_eq!(i2c.scan(0x51..=0x60).await.unwrap(), vec![0x51]);
+        assert!(i2c.scan(0x10..=0x20).await.unwrap().is_empty());
+
+        // closed controller refuses requests until it's reset
+        i2c.close().await.unwrap();
+        assert!(i2c.read(0x50, 1).await.is_err());
+        assert!(i2c.write(0x50, vec![0]).await.is_err());
+        assert_eq!(i2c.reset_count(), 0);
+        i2c.reset_i2c_controller().await.unwrap();
+        assert_eq!(i2c.reset_count(), 1);
+        i2c.write(0x50, vec![0]).await.unwrap();
+        assert_eq!(i2c.active_backend(), ActiveBackend::Primary);
+    }
+
+    /// Number of backends opened by `spawn_wedged_once`
+    static WEDGED_ONCE_OPENED: AtomicUsize = AtomicUsize::new(0);
+
+    /// The first opened backend never replies (as if stuck in I2C transaction), backends
+    /// opened later reply to reads with zeroes
+    fn spawn_wedged_once(
+        _path: &Path,
+        _retry_policy: RetryPolicy,
+    ) -> error::Result<mpsc::UnboundedSender<Request>> {
+        let wedged = WEDGED_ONCE_OPENED.fetch_add(1, Ordering::SeqCst) == 0;
+        let (request_tx, mut request_rx) = mpsc::unbounded();
+        tokio::spawn(async move {
+            // keep reply channels of requests that are not served open
+            let mut pending = Vec::new();
+            while let Some(request) = request_rx.next().await {
+                match request {
+                    Request::Read {
+                        num_bytes, reply, ..
+                    } if !wedged => {
+                        let _ = reply.send(Ok(vec![0; num_bytes]));
+                    }
+                    request => pending.push(request),
+                }
+            }
+        });
+        Ok(request_tx)
+    }
+
+    #[tokio::test]
+    async fn test_request_timeout() {
+        let backend =
+            Backend::open_with("wedged".into(), RetryPolicy::default(), spawn_wedged_once)
+                .expect("BUG: failed to open backend");
+        let i2c = AsyncI2cDev::from_backends(backend, None)
+            .with_request_timeout(Duration::from_millis(10));
+
+        // requests time out instead of blocking forever
+        for _ in 0..AsyncI2cDev::MAX_TIMEOUTS {
+            match i2c.read(0x50, 2).await {
+                Err(e) => match e.kind() {
+                    ErrorKind::Timeout(_) => {}
+                    kind => panic!("BUG: unexpected error {}", kind),
+                },
+                Ok(_) => panic!("BUG: wedged backend replied"),
+            }
+        }
+        // too many timeouts in a row reopen the backend
+        assert_eq!(WEDGED_ONCE_OPENED.load(Ordering::SeqCst), 2);
+        assert_eq!(i2c.read(0x50, 2).await.unwrap(), vec![0, 0]);
+        assert_eq!(i2c.active_backend(), ActiveBackend::Primary);
+    }
+
+    /// Number of backends opened by `spawn_broken_primary`
+    static BROKEN_PRIMARY_OPENED: AtomicUsize = AtomicUsize::new(0);
+
+    /// Backend opened on path `broken` fails all reads, other backends reply with zeroes
+    fn spawn_broken_primary(
+        path: &Path,
+        _retry_policy: RetryPolicy,
+    ) -> error::Result<mpsc::UnboundedSender<Request>> {
+        let broken = path == Path::new("broken");
+        if broken {
+            BROKEN_PRIMARY_OPENED.fetch_add(1, Ordering::SeqCst);
+        }
+        let (request_tx, mut request_rx) = mpsc::unbounded();
+        tokio::spawn(async move {
+            while let Some(request) = request_rx.next().await {
+                if let Request::Read {
+                    num_bytes, reply, ..
+                } = request
+                {
+                    let result = if broken {
+                        Err(ErrorKind::I2c("broken".into()).into())
+                    } else {
+                        Ok(vec![0; num_bytes])
+                    };
+                    let _ = reply.send(result);
+                }
+            }
+        });
+        Ok(request_tx)
+    }
+
+    #[tokio::test]
+    async fn test_fallback() {
+        let open = |path: &str| {
+            Backend::open_with(path.into(), RetryPolicy::default(), spawn_broken_primary)
+                .expect("BUG: failed to open backend")
+        };
+        let i2c = AsyncI2cDev::from_backends(open("broken"), Some(open("fallback")));
+
+        // primary backend is reopened first
+        for _ in 0..AsyncI2cDev::PRIMARY_MAX_FAILURES {
+            assert!(i2c.read(0x50, 1).await.is_err());
+        }
+        assert_eq!(BROKEN_PRIMARY_OPENED.load(Ordering::SeqCst), 2);
+        assert_eq!(i2c.active_backend(), ActiveBackend::Primary);
+
+        // reopened primary backend keeps failing, switch to the fallback one
+        for _ in 0..AsyncI2cDev::PRIMARY_MAX_FAILURES {
+            assert!(i2c.read(0x50, 1).await.is_err());
+        }
+        assert_eq!(BROKEN_PRIMARY_OPENED.load(Ordering::SeqCst), 2);
+        assert_eq!(i2c.active_backend(), ActiveBackend::Fallback);
+        assert_eq!(i2c.read(0x50, 2).await.unwrap(), vec![0, 0]);
+
+        // reset switches back to the primary backend
+        i2c.reset_i2c_controller().await.unwrap();
+        assert_eq!(BROKEN_PRIMARY_OPENED.load(Ordering::SeqCst), 3);
+        assert_eq!(i2c.active_backend(), ActiveBackend::Primary);
+        assert!(i2c.read(0x50, 1).await.is_err());
+    }
+
+    #[test]
+    fn test_is_retryable() {
+        let error = |errno| io::Error::from_raw_os_error(errno);
+        assert!(is_retryable(&error(errno::ENXIO)));
+        assert!(is_retryable(&error(errno::EREMOTEIO)));
+        assert!(is_retryable(&error(errno::EAGAIN)));
+        // bad file descriptor
+        assert!(!is_retryable(&error(9)));
+        assert!(!is_retryable(&io::Error::new(
+            io::ErrorKind::Other,
+            "other"
+        )));
+    }
+
+    #[test]
+    fn test_with_retry() {
+        let retry_policy = RetryPolicy {
+            retry_count: 2,
+            delay: Duration::from_millis(0),
+        };
+        let error = |errno| LinuxI2CError::from(io::Error::from_raw_os_error(errno));
+
+        // transient error goes away after retry
+        let mut tries = 0;
+        let result = with_retry(retry_policy, || {
+            tries += 1;
+            if tries < 2 {
+                Err(error(errno::ENXIO))
+            } else {
+                Ok(tries)
+            }
+        });
+        assert_eq!(result.expect("BUG: retried request failed"), 2);
+
+        // transient error is retried `retry_count` times and then propagated
+        let mut tries = 0;
+        let result: error::Result<()> = with_retry(retry_policy, || {
+            tries += 1;
+            Err(error(errno::EREMOTEIO))
+        });
+        assert_eq!(tries, retry_policy.retry_count + 1);
+        match result {
+            Err(e) => match e.kind() {
+                ErrorKind::I2c(_) => {}
+                kind => panic!("BUG: unexpected error {}", kind),
+            },
+            Ok(_) => panic!("BUG: failing request succeeded"),
+        }
+
+        // other errors are not retried at all
+        let mut tries = 0;
+        let result: error::Result<()> = with_retry(retry_policy, || {
+            tries += 1;
+            Err(error(9))
+        });
+        assert_eq!(tries, 1);
+        assert!(result.is_err());
+
+        // retrying can be disabled
+        let mut tries = 0;
+        let result: error::Result<()> = with_retry(
+            RetryPolicy {
+                retry_count: 0,
+                ..retry_policy
+            },
+            || {
+                tries += 1;
+                Err(error(errno::EAGAIN))
+            },
+        );
+        assert_eq!(tries, 1);
+        assert!(result.is_err());
+    }
+}
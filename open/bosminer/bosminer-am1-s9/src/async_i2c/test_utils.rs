@@ -0,0 +1,179 @@
+// Copyright (C) 2019  Braiins Systems s.r.o.
+//
+// This file is part of Braiins Open-Source Initiative (BOSI).
+//
+// BOSI is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+//
+// Please, keep in mind that we may also license BOSI or any part thereof
+// under a proprietary license. For more information on the terms and conditions
+// of such proprietary license or if you have any other questions, please
+// contact us at opensource@braiins.com.
+
+use crate::async_i2c::AsyncI2c;
+use crate::error::{self, ErrorKind};
+
+use async_trait::async_trait;
+
+use std::collections::{HashMap, VecDeque};
+use std::ops::RangeInclusive;
+use std::sync::{Arc, Mutex as StdMutex};
+
+/// Traffic of one fake device on I2C bus
+#[derive(Default)]
+struct FakeDevice {
+    /// Bytes that are returned by subsequent reads
+    read_bytes: VecDeque<u8>,
+    /// All bytes that have been written to the device
+    written_bytes: Vec<u8>,
+}
+
+#[derive(Default)]
+struct State {
+    devices: HashMap<u8, FakeDevice>,
+    reset_count: usize,
+    /// Controller has been closed and it fails all requests until it's reset
+    closed: bool,
+}
+
+impl State {
+    /// Return device on `address` if it's accessible
+    fn device_mut(&mut self, address: u8) -> error::Result<&mut FakeDevice> {
+        if self.closed {
+            Err(ErrorKind::I2c("I2C controller is closed!".into()))?
+        }
+        Ok(self.devices.get_mut(&address).ok_or_else(|| {
+            ErrorKind::I2c(format!("Nothing present on I2C address {:#x}!", address))
+        })?)
+    }
+}
+
+/// FakeI2c is in-memory I2C controller with devices present on given addresses
+/// Clones share the same devices, so one clone can be handed over to the code under test
+/// while the other one is used to inspect the traffic.
+#[derive(Clone)]
+pub struct FakeI2c {
+    state: Arc<StdMutex<State>>,
+}
+
+impl FakeI2c {
+    /// Constructs fake I2C controller
+    ///
+    /// * `addresses` - I2C addresses with a device present, the rest of the addresses fail
+    pub fn new(addresses: &[u8]) -> Self {
+        let mut state = State::default();
+        for address in addresses.iter() {
+            state.devices.insert(*address, FakeDevice::default());
+        }
+        Self {
+            state: Arc::new(StdMutex::new(state)),
+        }
+    }
+
+    /// Queue bytes to be returned by reads from device on `address`
+    pub fn push_read_bytes(&self, address: u8, bytes: &[u8]) {
+        let mut state = self.state.lock().expect("BUG: failed to lock mutex");
+        state
+            .devices
+            .get_mut(&address)
+            .expect("BUG: no fake device on I2C address")
+            .read_bytes
+            .extend(bytes.iter());
+    }
+
+    /// Return all bytes written to device on `address`
+    pub fn written_bytes(&self, address: u8) -> Vec<u8> {
+        let state = self.state.lock().expect("BUG: failed to lock mutex");
+        state
+            .devices
+            .get(&address)
+            .expect("BUG: no fake device on I2C address")
+            .written_bytes
+            .clone()
+    }
+
+    /// Return how many times has been the controller reset
+    pub fn reset_count(&self) -> usize {
+        self.state
+            .lock()
+            .expect("BUG: failed to lock mutex")
+            .reset_count
+    }
+}
+
+#[async_trait]
+impl AsyncI2c for FakeI2c {
+    /// Read bytes queued for device on `address`
+    /// Fail if there's no device on `address` or there's not enough bytes queued.
+    async fn read(&self, address: u8, num_bytes: usize) -> error::Result<Vec<u8>> {
+        let mut state = self.state.lock().expect("BUG: failed to lock mutex");
+        let device = state.device_mut(address)?;
+        if device.read_bytes.len() < num_bytes {
+            Err(ErrorKind::I2c(format!(
+                "Not enough data to read from I2C address {:#x}!",
+                address
+            )))?
+        }
+        Ok(device.read_bytes.drain(..num_bytes).collect())
+    }
+
+    /// Record bytes written to device on `address`
+    async fn write(&self, address: u8, bytes: Vec<u8>) -> error::Result<()> {
+        let mut state = self.state.lock().expect("BUG: failed to lock mutex");
+        let device = state.device_mut(address)?;
+        device.written_bytes.extend(bytes);
+        Ok(())
+    }
+
+    /// Write and read while holding the lock so that nothing gets in between
+    async fn write_read(
+        &self,
+        address: u8,
+        write_bytes: Vec<u8>,
+        read_len: usize,
+    ) -> error::Result<Vec<u8>> {
+        let mut state = self.state.lock().expect("BUG: failed to lock mutex");
+        let device = state.device_mut(address)?;
+        if device.read_bytes.len() < read_len {
+            Err(ErrorKind::I2c(format!(
+                "Not enough data to read from I2C address {:#x}!",
+                address
+            )))?
+        }
+        device.written_bytes.extend(write_bytes);
+        Ok(device.read_bytes.drain(..read_len).collect())
+    }
+
+    /// Return addresses with fake device in `range`
+    async fn scan(&self, range: RangeInclusive<u8>) -> error::Result<Vec<u8>> {
+        let state = self.state.lock().expect("BUG: failed to lock mutex");
+        if state.closed {
+            Err(ErrorKind::I2c("I2C controller is closed!".into()))?
+        }
+        Ok(range
+            .filter(|address| state.devices.contains_key(address))
+            .collect())
+    }
+
+    async fn close(&self) -> error::Result<()> {
+        self.state.lock().expect("BUG: failed to lock mutex").closed = true;
+        Ok(())
+    }
+
+    async fn reset_i2c_controller(&self) -> error::Result<()> {
+        let mut state = self.state.lock().expect("BUG: failed to lock mutex");
+        state.reset_count += 1;
+        state.closed = false;
+        Ok(())
+    }
+}
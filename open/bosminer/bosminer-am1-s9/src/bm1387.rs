@@ -30,6 +30,8 @@ use packed_struct_codegen::{PrimitiveEnum_u16, PrimitiveEnum_u8};
 
 use once_cell::sync::Lazy;
 
+use serde::{Deserialize, Serialize};
+
 use ii_fpga_io_am1_s9::common::ctrl_reg::MIDSTATE_CNT_A;
 
 use std::convert::TryInto;
@@ -66,16 +68,28 @@ pub struct MidstateCount {
 }
 
 impl MidstateCount {
-    /// Construct Self, panic if number of midstates is not valid for this hw
-    pub fn new(count: usize) -> Self {
+    /// Supported numbers of midstates
+    pub const SUPPORTED_COUNTS: [usize; 3] = [1, 2, 4];
+
+    /// Construct Self, fail if number of midstates is not valid for this hw
+    pub fn try_new(count: usize) -> error::Result<Self> {
         match count {
-            1 => Self { log2: 0 },
-            2 => Self { log2: 1 },
-            4 => Self { log2: 2 },
-            _ => panic!("Unsupported S9 midstate count {}", count),
+            1 => Ok(Self { log2: 0 }),
+            2 => Ok(Self { log2: 1 }),
+            4 => Ok(Self { log2: 2 }),
+            _ => Err(ErrorKind::General(format!(
+                "Unsupported S9 midstate count {} (supported: {:?})",
+                count,
+                Self::SUPPORTED_COUNTS
+            )))?,
         }
     }
 
+    /// Construct Self, panic if number of midstates is not valid for this hw
+    pub fn new(count: usize) -> Self {
+        Self::try_new(count).unwrap_or_else(|e| panic!("{}", e))
+    }
+
     /// Return midstate count encoded for FPGA
     pub fn to_reg(&self) -> MIDSTATE_CNT_A {
         match self.log2 {
@@ -149,14 +163,79 @@ pub struct CoreAddress {
 
 impl CoreAddress {
     pub fn new(nonce: u32) -> Self {
+        Self::with_layout(nonce, &CORE_ADDRESS_LAYOUT)
+    }
+
+    /// Decode core address from nonce using custom address layout
+    pub fn with_layout(nonce: u32, layout: &CoreAddressLayout) -> Self {
         let nonce = nonce as usize;
         Self {
-            chip: (nonce >> 2) & 0x3f,
-            core: (nonce >> 24) & 0x7f,
+            chip: (nonce >> layout.chip_shift) & layout.chip_mask,
+            core: (nonce >> layout.core_shift) & layout.core_mask,
         }
     }
 }
 
+/// Describes where chip and core address are located in nonce
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct CoreAddressLayout {
+    pub chip_shift: usize,
+    pub chip_mask: usize,
+    pub core_shift: usize,
+    pub core_mask: usize,
+    /// Size of core address space. It should be `NUM_CORES_ON_CHIP`, but the addresses are
+    /// non-consecutive.
+    pub core_space_size: usize,
+}
+
+impl CoreAddressLayout {
+    /// Check that address decoded from nonce belongs to the expected core address space
+    pub fn contains(&self, addr: CoreAddress) -> bool {
+        addr.core < self.core_space_size
+    }
+
+    /// Check that chip and core address can be decoded from 32-bit nonce
+    pub fn validate(&self) -> Result<(), String> {
+        for (name, shift, mask) in [
+            ("chip", self.chip_shift, self.chip_mask),
+            ("core", self.core_shift, self.core_mask),
+        ]
+        .iter()
+        {
+            if *shift >= 32 {
+                return Err(format!("'{}_shift' ({}) must be less than 32", name, shift));
+            }
+            if *mask == 0 {
+                return Err(format!("'{}_mask' must not be zero", name));
+            }
+        }
+        if !(1..=self.core_mask + 1).contains(&self.core_space_size) {
+            return Err(format!(
+                "'core_space_size' ({}) is out of range '1..{}'",
+                self.core_space_size,
+                self.core_mask + 1
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl Default for CoreAddressLayout {
+    fn default() -> Self {
+        CORE_ADDRESS_LAYOUT
+    }
+}
+
+/// Core address layout of BM1387
+pub const CORE_ADDRESS_LAYOUT: CoreAddressLayout = CoreAddressLayout {
+    chip_shift: 2,
+    chip_mask: 0x3f,
+    core_shift: 24,
+    core_mask: 0x7f,
+    core_space_size: 128,
+};
+
 /// Control or work command layout
 #[derive(PackedStruct, Debug)]
 #[packed_struct(size_bytes = "1", bit_numbering = "lsb0")]
@@ -395,9 +474,12 @@ impl Default for ChipRev {
     }
 }
 
+/// Chip revision as reported in `GetAddressReg` (including unrecognized revisions)
+pub type ChipRevision = EnumCatchAll<ChipRev>;
+
 /// Chip revision with `EnumCatchAll` wrapper so we would have to import `packed_struct`
 /// everywhere.
-pub const CHIP_REV_BM1387: EnumCatchAll<ChipRev> = EnumCatchAll::Enum(ChipRev::Bm1387);
+pub const CHIP_REV_BM1387: ChipRevision = EnumCatchAll::Enum(ChipRev::Bm1387);
 
 /// This register represents ASIC difficulty
 ///
@@ -432,6 +514,11 @@ impl TicketMaskReg {
         let ticket_mask = (difficulty - 1).reverse_bits().swap_bytes();
         Ok(Self { ticket_mask })
     }
+
+    /// Decode difficulty from ticket mask (e.g. from register read back from chip)
+    pub fn difficulty(&self) -> u64 {
+        self.ticket_mask.swap_bytes().reverse_bits() as u64 + 1
+    }
 }
 
 impl Register for TicketMaskReg {
@@ -617,8 +704,21 @@ impl PllReg {
 
 impl Register for PllReg {
     const REG_NUM: u8 = 0x0c;
+
+    /// Take register and unpack it, ignoring `PLL_READBACK_FLAG` chips set on readback
+    fn from_reg(reg: u32) -> Self {
+        Self::unpack(&(reg & !Self::PLL_READBACK_FLAG).to_be_bytes()).expect("unpacking error")
+    }
 }
 
+impl PllReg {
+    /// Bit that is or-ed to PLL register when it's read back from chip
+    pub const PLL_READBACK_FLAG: u32 = 0x8000_0000;
+}
+
+/// Table of PLL dividers sorted by frequency (see `PllFrequency::precompute_pll_table`)
+pub type PllTable = [PllFrequency];
+
 // TODO: how to initialize with custom XTAL frequency?
 pub static PRECOMPUTED_PLL: Lazy<Vec<PllFrequency>> =
     Lazy::new(|| PllFrequency::precompute_pll_table(crate::CHIP_OSC_CLK_HZ));
@@ -645,6 +745,8 @@ impl PllFrequency {
     const MIN_FREQ_HZ: usize = 100_000_000;
     const MAX_FREQ_HZ: usize = 1_200_000_000;
     const BIN_SIZE_HZ: usize = 1_000_000;
+    /// Maximum acceptable difference between requested frequency and the one PLL is set to
+    pub const MAX_FREQ_ERROR_HZ: usize = Self::BIN_SIZE_HZ;
 
     /// Precompute divider table (which sorted list of frequencies and corresponding dividers)
     fn precompute_pll_table(xtal_freq: usize) -> Vec<Self> {
@@ -703,10 +805,19 @@ impl PllFrequency {
         pll_table
     }
 
+    /// Difference between `target_freq` and frequency this divider actually sets (in Hz)
+    pub fn freq_error(&self, target_freq: usize) -> usize {
+        distance(self.frequency, target_freq)
+    }
+
     /// Lookup best divider for a given frequency from a table of dividers
     /// This table is built on-demand (via `once_cell::Lazy`)
     pub fn lookup_freq(target_freq: usize) -> error::Result<PllFrequency> {
-        let plls = &PRECOMPUTED_PLL;
+        Self::lookup_freq_in(&PRECOMPUTED_PLL, target_freq)
+    }
+
+    /// Lookup best divider for a given frequency from a table `plls`
+    pub fn lookup_freq_in(plls: &PllTable, target_freq: usize) -> error::Result<PllFrequency> {
         // The table is sorted
         let result = plls.binary_search_by_key(&target_freq, |p| p.frequency);
         match result {
@@ -969,6 +1080,19 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_ticket_mask_reg_difficulty() {
+        for difficulty in [1, 64, 2048, 1 << 31].iter() {
+            assert_eq!(
+                TicketMaskReg::new(*difficulty)
+                    .expect("Cannot build difficulty register")
+                    .difficulty(),
+                *difficulty as u64
+            );
+        }
+        assert_eq!(TicketMaskReg { ticket_mask: 0 }.difficulty(), 1);
+    }
+
     #[test]
     fn test_hashrate_reg() {
         let reg = HashrateReg { hashrate24: 0x23 };
@@ -1034,11 +1158,34 @@ mod test {
         assert_eq!(lookup_one(217_700_000), Some(217_857_142));
         assert_eq!(lookup_one(1_081_250_000), Some(1_075_000_000));
         assert_eq!(lookup_one(1_081_250_001), Some(1_087_500_000));
+        // error of approximate lookup
+        assert_eq!(
+            PllFrequency::lookup_freq(1_081_250_000)
+                .unwrap()
+                .freq_error(1_081_250_000),
+            6_250_000
+        );
         // exact lookups
         assert_eq!(lookup_one(650_000_000), Some(650_000_000));
         assert_eq!(lookup_one(1_033_333_333), Some(1_033_333_333));
     }
 
+    #[test]
+    fn test_pll_readback() {
+        // readback flag is ignored
+        let pll = PllReg::from_reg(0x8068_0221);
+        assert_eq!(pll, PllReg::from_reg(0x0068_0221));
+        assert_eq!(pll.calc(DEFAULT_XTAL_FREQ), 650_000_000);
+        // every precomputed frequency survives register roundtrip exactly
+        for entry in PRECOMPUTED_PLL.iter() {
+            let reg = entry.reg.to_reg() | PllReg::PLL_READBACK_FLAG;
+            assert_eq!(
+                PllReg::from_reg(reg).calc(DEFAULT_XTAL_FREQ),
+                entry.frequency
+            );
+        }
+    }
+
     #[test]
     fn test_core_address() {
         assert_eq!(
@@ -1074,6 +1221,29 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_core_address_layout() {
+        let layout = CoreAddressLayout {
+            chip_shift: 3,
+            chip_mask: 0x1f,
+            core_shift: 25,
+            core_mask: 0x3f,
+            core_space_size: 60,
+        };
+        let addr = CoreAddress::with_layout(0x7fffff00 | (0x15 << 3), &layout);
+        assert_eq!(
+            addr,
+            CoreAddress {
+                chip: 0x15,
+                core: 0x3f
+            }
+        );
+        assert!(!layout.contains(addr));
+        assert!(layout.contains(CoreAddress { chip: 0, core: 59 }));
+        // default layout covers the whole decoded core space
+        assert!(CORE_ADDRESS_LAYOUT.contains(CoreAddress::new(0xffffffff)));
+    }
+
     #[test]
     fn test_midstate_count_instance() {
         MidstateCount::new(1);
@@ -1087,6 +1257,21 @@ mod test {
         MidstateCount::new(3);
     }
 
+    #[test]
+    fn test_midstate_count_try_new() {
+        for count in MidstateCount::SUPPORTED_COUNTS.iter() {
+            assert_eq!(
+                MidstateCount::try_new(*count)
+                    .expect("BUG: supported count rejected")
+                    .to_count(),
+                *count
+            );
+        }
+        assert!(MidstateCount::try_new(0).is_err());
+        assert!(MidstateCount::try_new(3).is_err());
+        assert!(MidstateCount::try_new(8).is_err());
+    }
+
     #[test]
     fn test_midstate_count_conversion() {
         use ii_fpga_io_am1_s9::common::ctrl_reg::MIDSTATE_CNT_A;
@@ -21,19 +21,43 @@
 // contact us at opensource@braiins.com.
 
 use ii_cgminer_api::command::{DEVDETAILS, FANS, TEMPCTRL, TEMPS};
-use ii_cgminer_api::{command, commands, response};
+use ii_cgminer_api::{command, commands, json, response, PARAMETER_DELIMITER};
+
+use ii_logging::macros::*;
 
 use serde::Serialize;
 
+use ii_async_compat::tokio;
+
 use std::sync::Arc;
+use std::time::Duration;
 
+use crate::config;
 use crate::monitor;
+use crate::power;
 use crate::sensor;
 
 #[derive(Eq, PartialEq, Copy, Clone, Debug)]
 #[repr(u32)]
 pub enum StatusCode {
     NotReady = 1,
+    InvalidPauseTimeout = 2,
+    InvalidSetFreq = 3,
+    HashboardNotPresent = 4,
+    HashboardNotRunning = 5,
+    HashboardBusy = 6,
+    SetFreqFailed = 7,
+    InvalidSimulateDecision = 8,
+    InvalidHashboard = 9,
+    InvalidPidParams = 10,
+
+    SolutionPause = 100,
+    FrequencySet = 101,
+    ChainTemps = 102,
+    Power = 103,
+    DecisionSimulated = 104,
+    HashboardEnabled = 105,
+    PidParams = 106,
 }
 
 impl From<StatusCode> for u32 {
@@ -44,18 +68,354 @@ impl From<StatusCode> for u32 {
 
 pub enum ErrorCode {
     NotReady,
+    InvalidPauseTimeout(String),
+    InvalidSetFreq(String),
+    HashboardNotPresent(usize),
+    HashboardNotRunning(usize),
+    HashboardBusy(usize, &'static str),
+    SetFreqFailed(usize, String),
+    InvalidSimulateDecision(String),
+    InvalidHashboard(String),
+    InvalidPidParams(String),
 }
 
 impl From<ErrorCode> for response::Error {
     fn from(code: ErrorCode) -> Self {
         let (code, msg) = match code {
             ErrorCode::NotReady => (StatusCode::NotReady, "Not ready".to_string()),
+            ErrorCode::InvalidPauseTimeout(value) => (
+                StatusCode::InvalidPauseTimeout,
+                format!(
+                    "Invalid pause timeout '{}' (expected 0..={} seconds)",
+                    value,
+                    crate::MAX_SOLUTION_PAUSE.as_secs()
+                ),
+            ),
+            ErrorCode::InvalidSetFreq(value) => (
+                StatusCode::InvalidSetFreq,
+                format!(
+                    "Invalid parameter '{}' (expected '<board>{}<frequency>' with frequency \
+                     {}..={} MHz)",
+                    value,
+                    PARAMETER_DELIMITER,
+                    config::FREQUENCY_MHZ_MIN,
+                    config::FREQUENCY_MHZ_MAX
+                ),
+            ),
+            ErrorCode::HashboardNotPresent(idx) => (
+                StatusCode::HashboardNotPresent,
+                format!("Hashboard {} not present", idx),
+            ),
+            ErrorCode::HashboardNotRunning(idx) => (
+                StatusCode::HashboardNotRunning,
+                format!("Hashboard {} not running", idx),
+            ),
+            ErrorCode::HashboardBusy(idx, owner) => (
+                StatusCode::HashboardBusy,
+                format!("Hashboard {} is owned by '{}'", idx, owner),
+            ),
+            ErrorCode::SetFreqFailed(idx, error) => (
+                StatusCode::SetFreqFailed,
+                format!("Setting frequency of hashboard {} failed: {}", idx, error),
+            ),
+            ErrorCode::InvalidSimulateDecision(value) => (
+                StatusCode::InvalidSimulateDecision,
+                format!(
+                    "Invalid parameter '{}' (expected '<fans>{}<temperature>')",
+                    value, PARAMETER_DELIMITER
+                ),
+            ),
+            ErrorCode::InvalidHashboard(value) => (
+                StatusCode::InvalidHashboard,
+                format!("Invalid parameter '{}' (expected '<board>')", value),
+            ),
+            ErrorCode::InvalidPidParams(value) => (
+                StatusCode::InvalidPidParams,
+                format!(
+                    "Invalid parameter '{}' (expected '<kp>{}<ki>{}<kd>' with non-positive \
+                     gains)",
+                    value, PARAMETER_DELIMITER, PARAMETER_DELIMITER
+                ),
+            ),
         };
 
         Self::from_custom_error(code, msg)
     }
 }
 
+/// Custom command for pausing solution submission
+///
+/// Parameter is number of seconds after which the submission is resumed automatically,
+/// 0 resumes it immediately.
+pub const PAUSE_SUBMIT: &str = "pausesubmit";
+
+#[derive(Serialize, PartialEq, Clone, Debug)]
+pub struct SolutionPauseInfo {
+    #[serde(rename = "Paused")]
+    pub paused: bool,
+    /// Seconds remaining until the submission is resumed
+    #[serde(rename = "Remaining")]
+    pub remaining: u64,
+    /// Number of solutions dropped while paused (sum over all running hashchains)
+    #[serde(rename = "Dropped")]
+    pub dropped: u64,
+}
+
+impl From<SolutionPauseInfo> for response::Dispatch {
+    fn from(pause: SolutionPauseInfo) -> Self {
+        let msg = if pause.paused {
+            format!(
+                "Solution submission paused for {} second(s)",
+                pause.remaining
+            )
+        } else {
+            "Solution submission running".to_string()
+        };
+        response::Dispatch::from_custom_success(
+            StatusCode::SolutionPause,
+            msg,
+            Some(response::Body {
+                name: "PAUSESUBMIT",
+                list: vec![pause],
+            }),
+        )
+    }
+}
+
+/// Custom command for setting frequency of all chips on hashboard
+///
+/// Parameter is hashboard index (as reported in `devdetails`) and frequency in MHz separated by
+/// comma, e.g. `setfreq|6,650`.
+pub const SET_FREQ: &str = "setfreq";
+
+#[derive(Serialize, PartialEq, Clone, Debug)]
+pub struct SetFreqInfo {
+    #[serde(rename = "ID")]
+    pub id: u32,
+    /// Average chip frequency before the change (MHz)
+    #[serde(rename = "OldFrequency")]
+    pub old_frequency: f64,
+    /// Frequency of all chips after the change (MHz)
+    #[serde(rename = "NewFrequency")]
+    pub new_frequency: f64,
+}
+
+impl From<SetFreqInfo> for response::Dispatch {
+    fn from(info: SetFreqInfo) -> Self {
+        response::Dispatch::from_custom_success(
+            StatusCode::FrequencySet,
+            format!(
+                "Hashboard {} frequency set to {} MHz",
+                info.id, info.new_frequency
+            ),
+            Some(response::Body {
+                name: "SETFREQ",
+                list: vec![info],
+            }),
+        )
+    }
+}
+
+/// Custom commands for disabling hashboard (e.g. for maintenance) and enabling it again
+///
+/// Parameter is hashboard index (as reported in `devdetails`), e.g. `disableboard|6`. Disabled
+/// hashboard is stopped and is not started again until it's enabled.
+pub const DISABLE_HASHBOARD: &str = "disableboard";
+pub const ENABLE_HASHBOARD: &str = "enableboard";
+
+#[derive(Serialize, PartialEq, Clone, Debug)]
+pub struct HashboardEnabledInfo {
+    #[serde(rename = "ID")]
+    pub id: u32,
+    #[serde(rename = "Enabled")]
+    pub enabled: bool,
+}
+
+impl From<HashboardEnabledInfo> for response::Dispatch {
+    fn from(info: HashboardEnabledInfo) -> Self {
+        response::Dispatch::from_custom_success(
+            StatusCode::HashboardEnabled,
+            format!(
+                "Hashboard {} {}",
+                info.id,
+                if info.enabled { "enabled" } else { "disabled" }
+            ),
+            Some(response::Body {
+                name: "HASHBOARD",
+                list: vec![info],
+            }),
+        )
+    }
+}
+
+/// Custom command reporting temperatures of all hashboards together with monitor input
+/// temperature and fan speed
+pub const CHAIN_TEMPS: &str = "chaintemps";
+
+/// Temperatures of single hashboard, `None` (null) means the temperature is unknown (hashboard
+/// not running, sensor not present or failing)
+#[derive(Serialize, PartialEq, Clone, Debug)]
+pub struct ChainTempInfo {
+    #[serde(rename = "ID")]
+    pub id: u32,
+    #[serde(rename = "Running")]
+    pub running: bool,
+    #[serde(rename = "Local")]
+    pub local: Option<f64>,
+    #[serde(rename = "Remote")]
+    pub remote: Option<f64>,
+}
+
+#[derive(Serialize, PartialEq, Clone, Debug)]
+pub struct ChainTempsInfo {
+    #[serde(rename = "Chains")]
+    pub chains: Vec<ChainTempInfo>,
+    /// Temperature the monitor uses for fan control (`None` when it's unknown)
+    #[serde(rename = "Input")]
+    pub input: Option<f64>,
+    /// Input temperature could not be determined due to failure
+    #[serde(rename = "InputFailed")]
+    pub input_failed: bool,
+    /// Fan speed in percent (`None` when the fan speed hasn't been set yet)
+    #[serde(rename = "FanSpeed")]
+    pub fan_speed: Option<u32>,
+    /// Reason of the last miner shutdown declared by monitor (empty when there was none)
+    #[serde(rename = "LastShutdownReason")]
+    pub last_shutdown_reason: String,
+    /// Unix time of the last miner shutdown declared by monitor (0 when there was none)
+    #[serde(rename = "LastShutdownTime")]
+    pub last_shutdown_time: u64,
+}
+
+impl From<ChainTempsInfo> for response::Dispatch {
+    fn from(info: ChainTempsInfo) -> Self {
+        response::Dispatch::from_custom_success(
+            StatusCode::ChainTemps,
+            format!("{} hashboard temperature(s)", info.chains.len()),
+            Some(response::Body {
+                name: "CHAINTEMPS",
+                list: vec![info],
+            }),
+        )
+    }
+}
+
+/// Custom command for validating temperature/fan configuration without real hardware
+///
+/// Parameter is number of running fans and input temperature separated by comma, e.g.
+/// `simulatedecision|2,85`. Monitor decides what it would do under current configuration, but
+/// nothing is actually changed.
+pub const SIMULATE_DECISION: &str = "simulatedecision";
+
+#[derive(Serialize, PartialEq, Clone, Debug)]
+pub struct SimulatedDecisionInfo {
+    #[serde(rename = "Fans")]
+    pub fans: u32,
+    #[serde(rename = "Temperature")]
+    pub temperature: f64,
+    /// One of `Shutdown`, `UsePid`, `UseFixedSpeed`, `WaitForFans` and `Nothing`
+    #[serde(rename = "Decision")]
+    pub decision: String,
+    /// Fan speed in percent (only for `UseFixedSpeed`)
+    #[serde(rename = "FanSpeed")]
+    pub fan_speed: Option<u32>,
+    /// Temperature PID would drive fans towards (only for `UsePid`)
+    #[serde(rename = "TargetTemp")]
+    pub target_temp: Option<f64>,
+    #[serde(rename = "Reason")]
+    pub reason: String,
+}
+
+impl From<SimulatedDecisionInfo> for response::Dispatch {
+    fn from(info: SimulatedDecisionInfo) -> Self {
+        response::Dispatch::from_custom_success(
+            StatusCode::DecisionSimulated,
+            format!("Monitor would decide: {} ({})", info.decision, info.reason),
+            Some(response::Body {
+                name: "SIMULATEDECISION",
+                list: vec![info],
+            }),
+        )
+    }
+}
+
+/// Custom commands for reading and retuning fan PID (see `monitor::Monitor::set_pid_params`)
+///
+/// Parameter of `setpidparams` is proportional, integral and derivative gain separated by
+/// comma, e.g. `setpidparams|-5,-0.03,-0.15`. Gains are negative, because the PID works in
+/// reverse direction (the lower the PWM, the higher the temperature).
+pub const PID_PARAMS: &str = "pidparams";
+pub const SET_PID_PARAMS: &str = "setpidparams";
+
+#[derive(Serialize, PartialEq, Clone, Debug)]
+pub struct PidParamsInfo {
+    #[serde(rename = "Kp")]
+    pub kp: f64,
+    #[serde(rename = "Ki")]
+    pub ki: f64,
+    #[serde(rename = "Kd")]
+    pub kd: f64,
+}
+
+impl From<PidParamsInfo> for response::Dispatch {
+    fn from(info: PidParamsInfo) -> Self {
+        response::Dispatch::from_custom_success(
+            StatusCode::PidParams,
+            format!(
+                "Fan PID params kp={} ki={} kd={}",
+                info.kp, info.ki, info.kd
+            ),
+            Some(response::Body {
+                name: "PIDPARAMS",
+                list: vec![info],
+            }),
+        )
+    }
+}
+
+/// Custom command reporting power draw of all hashboards as measured by power meter (see
+/// `power::PowerMeter`)
+pub const POWER: &str = "power";
+
+/// Power draw of single hashboard, `None` (null) means the value is unknown (hashboard not
+/// running, no power meter or power meter not able to measure it)
+#[derive(Serialize, PartialEq, Clone, Debug)]
+pub struct ChainPowerInfo {
+    #[serde(rename = "ID")]
+    pub id: u32,
+    #[serde(rename = "Running")]
+    pub running: bool,
+    /// Power has been measured (`false` without power meter or for board that hasn't been
+    /// measured yet)
+    #[serde(rename = "Supported")]
+    pub supported: bool,
+    #[serde(rename = "Watts")]
+    pub watts: Option<f64>,
+    #[serde(rename = "Voltage")]
+    pub voltage: Option<f64>,
+    #[serde(rename = "Current")]
+    pub current: Option<f64>,
+}
+
+#[derive(Serialize, PartialEq, Clone, Debug)]
+pub struct PowerInfo {
+    #[serde(rename = "Chains")]
+    pub chains: Vec<ChainPowerInfo>,
+}
+
+impl From<PowerInfo> for response::Dispatch {
+    fn from(info: PowerInfo) -> Self {
+        response::Dispatch::from_custom_success(
+            StatusCode::Power,
+            format!("{} hashboard power reading(s)", info.chains.len()),
+            Some(response::Body {
+                name: "POWER",
+                list: vec![info],
+            }),
+        )
+    }
+}
+
 #[derive(Serialize, PartialEq, Clone, Debug)]
 pub struct DevDetailInfo {
     #[serde(rename = "Voltage")]
@@ -66,6 +426,24 @@ pub struct DevDetailInfo {
     pub chips: u32,
     #[serde(rename = "Cores")]
     pub cores: u32,
+    /// Seconds since the hashchain was started (0 when it's not running)
+    #[serde(rename = "Uptime")]
+    pub uptime: u64,
+    /// Number of (attempted) hashchain starts
+    #[serde(rename = "Starts")]
+    pub starts: u32,
+    /// Baud rate actually set on the chips (0 when it's not running)
+    #[serde(rename = "ChipBaudRate")]
+    pub chip_baud_rate: u32,
+    /// Baud rate actually set in the FPGA (0 when it's not running)
+    #[serde(rename = "FpgaBaudRate")]
+    pub fpga_baud_rate: u32,
+    /// Achieved baud rate differs from the requested one
+    #[serde(rename = "BaudRateOffTarget")]
+    pub baud_rate_off_target: bool,
+    /// Reason of the last halt of the hashchain (empty when it hasn't been halted)
+    #[serde(rename = "LastHaltReason")]
+    pub last_halt_reason: String,
 }
 
 #[derive(Serialize, PartialEq, Clone, Debug)]
@@ -80,6 +458,7 @@ pub struct Handler {
     model: String,
     managers: Vec<Arc<crate::Manager>>,
     monitor: Arc<monitor::Monitor>,
+    solution_pause: Arc<crate::SolutionPause>,
 }
 
 impl Handler {
@@ -87,16 +466,153 @@ impl Handler {
         model: String,
         managers: Vec<Arc<crate::Manager>>,
         monitor: Arc<monitor::Monitor>,
+        solution_pause: Arc<crate::SolutionPause>,
     ) -> Self {
         Self {
             model,
             managers,
             monitor,
+            solution_pause,
+        }
+    }
+
+    /// Convert pause timeout parameter to number of seconds
+    fn parse_pause_timeout(parameter: &Option<&json::Value>) -> command::Result<u64> {
+        let timeout = match parameter {
+            Some(json::Value::Number(value)) => value.as_u64(),
+            // CGMiner recognizes strings and integers as the same type
+            Some(json::Value::String(value)) => value.parse().ok(),
+            _ => None,
+        };
+        match timeout.filter(|&timeout| timeout <= crate::MAX_SOLUTION_PAUSE.as_secs()) {
+            Some(timeout) => Ok(timeout),
+            None => Err(ErrorCode::InvalidPauseTimeout(
+                parameter.map(|value| value.to_string()).unwrap_or_default(),
+            )
+            .into()),
+        }
+    }
+
+    fn check_pause_timeout(
+        _command: &str,
+        parameter: &Option<&json::Value>,
+    ) -> command::Result<()> {
+        Self::parse_pause_timeout(parameter).map(|_| ())
+    }
+
+    /// Convert set frequency parameter to hashboard index and frequency in MHz
+    fn parse_set_freq(parameter: &Option<&json::Value>) -> Option<(usize, f64)> {
+        let value = match parameter {
+            Some(json::Value::String(value)) => value,
+            _ => return None,
+        };
+        let mut args = value.splitn(2, PARAMETER_DELIMITER);
+        let idx = args.next()?.trim().parse().ok()?;
+        let frequency: f64 = args.next()?.trim().parse().ok()?;
+        if (config::FREQUENCY_MHZ_MIN..=config::FREQUENCY_MHZ_MAX).contains(&frequency) {
+            Some((idx, frequency))
+        } else {
+            None
+        }
+    }
+
+    fn check_set_freq(_command: &str, parameter: &Option<&json::Value>) -> command::Result<()> {
+        match Self::parse_set_freq(parameter) {
+            Some(_) => Ok(()),
+            None => Err(ErrorCode::InvalidSetFreq(
+                parameter.map(|value| value.to_string()).unwrap_or_default(),
+            )
+            .into()),
+        }
+    }
+
+    fn parse_simulate_decision(parameter: &Option<&json::Value>) -> Option<(usize, f32)> {
+        let value = match parameter {
+            Some(json::Value::String(value)) => value,
+            _ => return None,
+        };
+        let mut args = value.splitn(2, PARAMETER_DELIMITER);
+        let fans = args.next()?.trim().parse().ok()?;
+        let temperature: f32 = args.next()?.trim().parse().ok()?;
+        if temperature.is_finite() {
+            Some((fans, temperature))
+        } else {
+            None
+        }
+    }
+
+    fn check_simulate_decision(
+        _command: &str,
+        parameter: &Option<&json::Value>,
+    ) -> command::Result<()> {
+        match Self::parse_simulate_decision(parameter) {
+            Some(_) => Ok(()),
+            None => Err(ErrorCode::InvalidSimulateDecision(
+                parameter.map(|value| value.to_string()).unwrap_or_default(),
+            )
+            .into()),
+        }
+    }
+
+    /// Convert hashboard parameter to hashboard index
+    fn parse_hashboard(parameter: &Option<&json::Value>) -> Option<usize> {
+        match parameter {
+            Some(json::Value::Number(value)) => value.as_u64().map(|idx| idx as usize),
+            // CGMiner recognizes strings and integers as the same type
+            Some(json::Value::String(value)) => value.trim().parse().ok(),
+            _ => None,
+        }
+    }
+
+    fn check_hashboard(_command: &str, parameter: &Option<&json::Value>) -> command::Result<()> {
+        match Self::parse_hashboard(parameter) {
+            Some(_) => Ok(()),
+            None => Err(ErrorCode::InvalidHashboard(
+                parameter.map(|value| value.to_string()).unwrap_or_default(),
+            )
+            .into()),
         }
     }
 
+    /// Convert PID params parameter to `(kp, ki, kd)`
+    fn parse_pid_params(parameter: &Option<&json::Value>) -> Option<(f64, f64, f64)> {
+        let value = match parameter {
+            Some(json::Value::String(value)) => value,
+            _ => return None,
+        };
+        let mut args = value.splitn(3, PARAMETER_DELIMITER);
+        let mut next_gain = || -> Option<f64> {
+            let gain: f64 = args.next()?.trim().parse().ok()?;
+            // positive gain would slow fans down with rising temperature
+            if gain.is_finite() && gain <= 0.0 {
+                Some(gain)
+            } else {
+                None
+            }
+        };
+        Some((next_gain()?, next_gain()?, next_gain()?))
+    }
+
+    fn check_pid_params(_command: &str, parameter: &Option<&json::Value>) -> command::Result<()> {
+        match Self::parse_pid_params(parameter) {
+            Some(_) => Ok(()),
+            None => Err(ErrorCode::InvalidPidParams(
+                parameter.map(|value| value.to_string()).unwrap_or_default(),
+            )
+            .into()),
+        }
+    }
+
+    fn find_manager(&self, idx: usize) -> command::Result<&Arc<crate::Manager>> {
+        Ok(self
+            .managers
+            .iter()
+            .find(|manager| manager.hashboard_idx == idx)
+            .ok_or(ErrorCode::HashboardNotPresent(idx))?)
+    }
+
     fn get_monitor_status(&self) -> command::Result<monitor::Status> {
-        match self.monitor.status_receiver.borrow().clone() {
+        match self.monitor.subscribe().current() {
             Some(status) => Ok(status),
             None => Err(ErrorCode::NotReady.into()),
         }
@@ -109,8 +625,12 @@ impl Handler {
             let mut chip_count = 0;
             let mut voltage = 0.0;
             let mut frequency = 0;
+            let mut baud_rates = (0, 0);
+            let mut baud_rate_off_target = false;
             if let Some(hash_chain) = inner.hash_chain.as_ref() {
-                chip_count = hash_chain.chip_count;
+                chip_count = hash_chain.get_chip_count();
+                baud_rates = hash_chain.get_baud_rates();
+                baud_rate_off_target = hash_chain.is_baud_rate_off_target();
                 voltage = hash_chain.get_voltage().await.as_volts() as f64;
                 frequency = hash_chain.get_frequency().await.avg() as u32;
             }
@@ -127,6 +647,12 @@ impl Handler {
                     frequency,
                     chips: chip_count as u32,
                     cores: (chip_count * crate::bm1387::NUM_CORES_ON_CHIP) as u32,
+                    uptime: inner.uptime().map(|uptime| uptime.as_secs()).unwrap_or(0),
+                    starts: inner.start_count as u32,
+                    chip_baud_rate: baud_rates.0 as u32,
+                    fpga_baud_rate: baud_rates.1 as u32,
+                    baud_rate_off_target,
+                    last_halt_reason: manager.last_halt_reason().await.unwrap_or_default(),
                 },
             });
         }
@@ -142,13 +668,14 @@ impl Handler {
         let mut hot = None;
         let mut dangerous = None;
 
-        if let Some(temp_config) = config.temp_config {
+        if let Some(temp_config) = config.temp_config() {
             mode = response::ext::TempCtrlMode::Manual;
             hot.replace(temp_config.hot_temp);
             dangerous.replace(temp_config.dangerous_temp);
         }
-        if let Some(fan_config) = config.fan_config {
-            if let monitor::FanControlMode::TargetTemperature(target_temp) = fan_config.mode {
+        if let Some(fan_config) = config.fan_config() {
+            if let monitor::FanControlMode::TargetTemperature { target_temp, .. } = fan_config.mode
+            {
                 mode = response::ext::TempCtrlMode::Automatic;
                 target.replace(target_temp);
             }
@@ -162,13 +689,20 @@ impl Handler {
         })
     }
 
+    /// Summarize readings of all hashboard sensors (the hottest one is reported)
+    fn temperature_summary(hash_chain: &crate::HashChain) -> Option<sensor::Temperature> {
+        hash_chain.current_temperature().map(|temps| {
+            sensor::Temperature::summary(&temps).unwrap_or(sensor::INVALID_TEMPERATURE_READING)
+        })
+    }
+
     async fn handle_temps(&self) -> command::Result<response::ext::Temps<TempInfo>> {
         let mut list = vec![];
         for manager in self.managers.iter() {
             let inner = manager.inner.lock().await;
             if let Some(hash_chain) = inner.hash_chain.as_ref() {
                 if let Some(sensor::Temperature { local, remote }) =
-                    hash_chain.current_temperature()
+                    Self::temperature_summary(hash_chain)
                 {
                     list.push(response::ext::Temp {
                         idx: list.len() as i32,
@@ -184,6 +718,252 @@ impl Handler {
         Ok(response::ext::Temps { list: list })
     }
 
+    async fn handle_chain_temps(&self) -> command::Result<ChainTempsInfo> {
+        let mut chains = vec![];
+        for manager in self.managers.iter() {
+            let inner = manager.inner.lock().await;
+            let hash_chain = inner.hash_chain.as_ref();
+            let temperature =
+                hash_chain.and_then(|hash_chain| Self::temperature_summary(hash_chain));
+            let (local, remote) = match temperature {
+                Some(sensor::Temperature { local, remote }) => (
+                    Option::<f32>::from(local).map(|t| t as f64),
+                    Option::<f32>::from(remote).map(|t| t as f64),
+                ),
+                None => (None, None),
+            };
+            chains.push(ChainTempInfo {
+                id: manager.hashboard_idx as u32,
+                running: hash_chain.is_some(),
+                local,
+                remote,
+            });
+        }
+
+        // Monitor may not have reported its status yet, treat it as unknown temperature
+        let status = self.monitor.subscribe().current();
+        let (input, input_failed) = match status.as_ref().map(|status| status.input_temperature) {
+            Some(monitor::ChainTemperature::Ok(t)) => (Some(t as f64), false),
+            Some(monitor::ChainTemperature::Failed) => (None, true),
+            Some(monitor::ChainTemperature::Unknown) | None => (None, false),
+        };
+        let fan_speed = status
+            .and_then(|status| status.fan_speed)
+            .map(|speed| speed.to_pwm() as u32);
+        let last_shutdown = self.monitor.last_shutdown().await;
+
+        Ok(ChainTempsInfo {
+            chains,
+            input,
+            input_failed,
+            fan_speed,
+            last_shutdown_reason: last_shutdown
+                .as_ref()
+                .map(|record| record.reason.to_string())
+                .unwrap_or_default(),
+            last_shutdown_time: last_shutdown.map(|record| record.unix_time()).unwrap_or(0),
+        })
+    }
+
+    async fn handle_power(&self) -> command::Result<PowerInfo> {
+        // Power is read from power meter by monitor
+        let status = self.monitor.subscribe().current();
+        let chain_power = status.map(|status| status.chain_power).unwrap_or_default();
+
+        let mut chains = vec![];
+        for manager in self.managers.iter() {
+            let running = manager.inner.lock().await.hash_chain.is_some();
+            let reading = chain_power
+                .iter()
+                .find(|power| power.hashboard_idx == manager.hashboard_idx)
+                .map(|power| power.reading);
+            let (voltage, current) = match reading {
+                Some(power::PowerReading::Measured { voltage, current }) => {
+                    (Some(voltage as f64), Some(current as f64))
+                }
+                Some(power::PowerReading::Unsupported) | None => (None, None),
+            };
+            chains.push(ChainPowerInfo {
+                id: manager.hashboard_idx as u32,
+                running,
+                supported: voltage.is_some(),
+                watts: reading
+                    .and_then(|reading| reading.watts())
+                    .map(|watts| watts as f64),
+                voltage,
+                current,
+            });
+        }
+
+        Ok(PowerInfo { chains })
+    }
+
+    async fn handle_pause_submit(
+        &self,
+        parameter: Option<&json::Value>,
+    ) -> command::Result<SolutionPauseInfo> {
+        let timeout = Self::parse_pause_timeout(&parameter)?;
+        if timeout == 0 {
+            self.solution_pause.resume();
+        } else {
+            self.solution_pause.pause(Duration::from_secs(timeout));
+        }
+
+        let mut dropped = 0;
+        for manager in self.managers.iter() {
+            let inner = manager.inner.lock().await;
+            if let Some(hash_chain) = inner.hash_chain.as_ref() {
+                dropped += hash_chain.snapshot_counter().await.dropped as u64;
+            }
+        }
+        let remaining = self.solution_pause.remaining();
+        Ok(SolutionPauseInfo {
+            paused: remaining.is_some(),
+            remaining: remaining.map(|remaining| remaining.as_secs()).unwrap_or(0),
+            dropped,
+        })
+    }
+
+    async fn handle_set_freq(
+        &self,
+        parameter: Option<&json::Value>,
+    ) -> command::Result<SetFreqInfo> {
+        let (idx, frequency) =
+            Self::parse_set_freq(&parameter).expect("BUG: invalid parameter not checked");
+        let manager = self
+            .managers
+            .iter()
+            .find(|manager| manager.hashboard_idx == idx)
+            .ok_or(ErrorCode::HashboardNotPresent(idx))?;
+
+        let chain = match manager.clone().acquire("cgminer").await {
+            Ok(crate::ChainStatus::Running(chain)) => chain,
+            Ok(crate::ChainStatus::Stopped(_)) => Err(ErrorCode::HashboardNotRunning(idx))?,
+            Err(owner) => Err(ErrorCode::HashboardBusy(idx, owner))?,
+        };
+        let old_frequency = chain.get_frequency().await;
+        let new_frequency = crate::FrequencySettings {
+            chip: vec![(frequency * 1_000_000.0) as usize; old_frequency.chip.len()],
+        };
+        chain
+            .set_frequency(&new_frequency)
+            .await
+            .map_err(|e| ErrorCode::SetFreqFailed(idx, e.to_string()))?;
+
+        Ok(SetFreqInfo {
+            id: idx as u32,
+            old_frequency: old_frequency.avg() as f64 / 1_000_000.0,
+            new_frequency: new_frequency.avg() as f64 / 1_000_000.0,
+        })
+    }
+
+    async fn handle_disable_hashboard(
+        &self,
+        parameter: Option<&json::Value>,
+    ) -> command::Result<HashboardEnabledInfo> {
+        let idx = Self::parse_hashboard(&parameter).expect("BUG: invalid parameter not checked");
+        let manager = self.find_manager(idx)?;
+
+        // hold the hashchain while it's being disabled so that nobody else is using it
+        let _chain = manager
+            .clone()
+            .acquire("cgminer")
+            .await
+            .map_err(|owner| ErrorCode::HashboardBusy(idx, owner))?;
+        manager.disable().await;
+
+        Ok(HashboardEnabledInfo {
+            id: idx as u32,
+            enabled: false,
+        })
+    }
+
+    async fn handle_enable_hashboard(
+        &self,
+        parameter: Option<&json::Value>,
+    ) -> command::Result<HashboardEnabledInfo> {
+        let idx = Self::parse_hashboard(&parameter).expect("BUG: invalid parameter not checked");
+        let manager = self.find_manager(idx)?;
+
+        let chain = match manager.clone().acquire("cgminer").await {
+            Ok(crate::ChainStatus::Stopped(chain)) => Some(chain),
+            // nothing to start
+            Ok(crate::ChainStatus::Running(_)) => None,
+            Err(owner) => Err(ErrorCode::HashboardBusy(idx, owner))?,
+        };
+        manager.enable().await;
+
+        if let Some(chain) = chain {
+            // starting hashchain takes a while, do not block the API
+            let initial_frequency = manager.chain_config.frequency.clone();
+            let initial_voltage = manager.chain_config.voltage;
+            tokio::spawn(async move {
+                if let Err((_, e)) = chain
+                    .start(
+                        &initial_frequency,
+                        initial_voltage,
+                        config::DEFAULT_ASIC_DIFFICULTY,
+                    )
+                    .await
+                {
+                    error!("Chain {}: failed to start enabled hashchain: {}", idx, e);
+                }
+            });
+        }
+
+        Ok(HashboardEnabledInfo {
+            id: idx as u32,
+            enabled: true,
+        })
+    }
+
+    async fn handle_simulate_decision(
+        &self,
+        parameter: Option<&json::Value>,
+    ) -> command::Result<SimulatedDecisionInfo> {
+        let (fans, temperature) =
+            Self::parse_simulate_decision(&parameter).expect("BUG: invalid parameter not checked");
+        let explained = self
+            .monitor
+            .simulate_decision(fans, monitor::ChainTemperature::Ok(temperature))
+            .await;
+
+        let (decision, fan_speed, target_temp) = match explained.decision {
+            monitor::ControlDecision::Shutdown => ("Shutdown", None, None),
+            monitor::ControlDecision::UsePid { target_temp, .. } => {
+                ("UsePid", None, Some(target_temp as f64))
+            }
+            monitor::ControlDecision::UseFixedSpeed(speed) => {
+                ("UseFixedSpeed", Some(speed.to_pwm() as u32), None)
+            }
+            monitor::ControlDecision::WaitForFans => ("WaitForFans", None, None),
+            monitor::ControlDecision::Nothing => ("Nothing", None, None),
+        };
+        Ok(SimulatedDecisionInfo {
+            fans: fans as u32,
+            temperature: temperature as f64,
+            decision: decision.to_string(),
+            fan_speed,
+            target_temp,
+            reason: explained.reason.to_string(),
+        })
+    }
+
+    async fn handle_pid_params(&self) -> command::Result<PidParamsInfo> {
+        let (kp, ki, kd) = self.monitor.get_pid_params().await;
+        Ok(PidParamsInfo { kp, ki, kd })
+    }
+
+    async fn handle_set_pid_params(
+        &self,
+        parameter: Option<&json::Value>,
+    ) -> command::Result<PidParamsInfo> {
+        let (kp, ki, kd) =
+            Self::parse_pid_params(&parameter).expect("BUG: invalid parameter not checked");
+        self.monitor.set_pid_params(kp, ki, kd).await;
+        self.handle_pid_params().await
+    }
+
     async fn handle_fans(&self) -> command::Result<response::ext::Fans> {
         let status = self.get_monitor_status()?;
         let speed = status.fan_speed.map(|speed| speed.to_pwm()).unwrap_or(0);
@@ -208,15 +988,94 @@ pub fn create_custom_commands(
     backend: Arc<crate::Backend>,
     managers: Vec<Arc<crate::Manager>>,
     monitor: Arc<monitor::Monitor>,
+    solution_pause: Arc<crate::SolutionPause>,
 ) -> Option<command::Map> {
-    let handler = Arc::new(Handler::new(backend.to_string(), managers, monitor));
+    let handler = Arc::new(Handler::new(
+        backend.to_string(),
+        managers,
+        monitor,
+        solution_pause,
+    ));
+
+    let check_pause_submit: command::ParameterCheckHandler =
+        Box::new(|command, parameter| Handler::check_pause_timeout(command, parameter));
+    let check_set_freq: command::ParameterCheckHandler =
+        Box::new(|command, parameter| Handler::check_set_freq(command, parameter));
+    let check_simulate_decision: command::ParameterCheckHandler =
+        Box::new(|command, parameter| Handler::check_simulate_decision(command, parameter));
+    let check_disable_hashboard: command::ParameterCheckHandler =
+        Box::new(|command, parameter| Handler::check_hashboard(command, parameter));
+    let check_enable_hashboard: command::ParameterCheckHandler =
+        Box::new(|command, parameter| Handler::check_hashboard(command, parameter));
+    let check_set_pid_params: command::ParameterCheckHandler =
+        Box::new(|command, parameter| Handler::check_pid_params(command, parameter));
 
     let custom_commands = commands![
         (DEVDETAILS: ParameterLess -> handler.handle_dev_details),
         (TEMPCTRL: ParameterLess -> handler.handle_temp_ctrl),
         (TEMPS: ParameterLess -> handler.handle_temps),
-        (FANS: ParameterLess -> handler.handle_fans)
+        (CHAIN_TEMPS: ParameterLess -> handler.handle_chain_temps),
+        (POWER: ParameterLess -> handler.handle_power),
+        (FANS: ParameterLess -> handler.handle_fans),
+        (PAUSE_SUBMIT: Parameter(check_pause_submit) -> handler.handle_pause_submit),
+        (SET_FREQ: Parameter(check_set_freq) -> handler.handle_set_freq),
+        (SIMULATE_DECISION: Parameter(check_simulate_decision) -> handler.handle_simulate_decision),
+        (DISABLE_HASHBOARD: Parameter(check_disable_hashboard) -> handler.handle_disable_hashboard),
+        (ENABLE_HASHBOARD: Parameter(check_enable_hashboard) -> handler.handle_enable_hashboard),
+        (PID_PARAMS: ParameterLess -> handler.handle_pid_params),
+        (SET_PID_PARAMS: Parameter(check_set_pid_params) -> handler.handle_set_pid_params)
     ];
 
     Some(custom_commands)
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_pause_timeout() {
+        let parse = |value: json::Value| Handler::parse_pause_timeout(&Some(&value)).ok();
+        assert_eq!(parse(json::Value::from(0)), Some(0));
+        assert_eq!(parse(json::Value::from(60)), Some(60));
+        assert_eq!(parse(json::Value::String("60".to_string())), Some(60));
+        assert_eq!(
+            parse(json::Value::from(crate::MAX_SOLUTION_PAUSE.as_secs())),
+            Some(crate::MAX_SOLUTION_PAUSE.as_secs())
+        );
+        assert_eq!(
+            parse(json::Value::from(crate::MAX_SOLUTION_PAUSE.as_secs() + 1)),
+            None
+        );
+        assert_eq!(parse(json::Value::from(-1)), None);
+        assert_eq!(parse(json::Value::String("one".to_string())), None);
+        assert!(Handler::parse_pause_timeout(&None).is_err());
+    }
+
+    #[test]
+    fn test_parse_simulate_decision() {
+        let parse = |value: &str| {
+            Handler::parse_simulate_decision(&Some(&json::Value::String(value.to_string())))
+        };
+        assert_eq!(parse("2,85"), Some((2, 85.0)));
+        assert_eq!(parse(" 0 , 72.5 "), Some((0, 72.5)));
+        assert_eq!(parse("-1,85"), None);
+        assert_eq!(parse("two,85"), None);
+        assert_eq!(parse("2,inf"), None);
+        assert_eq!(parse("2,NaN"), None);
+        assert_eq!(parse("2"), None);
+        assert_eq!(parse(""), None);
+        assert_eq!(Handler::parse_simulate_decision(&None), None);
+        assert_eq!(
+            Handler::parse_simulate_decision(&Some(&json::Value::from(2))),
+            None
+        );
+
+        assert!(Handler::check_simulate_decision(
+            SIMULATE_DECISION,
+            &Some(&json::Value::String("2,85".to_string()))
+        )
+        .is_ok());
+        assert!(Handler::check_simulate_decision(SIMULATE_DECISION, &None).is_err());
+    }
+}
@@ -236,6 +236,11 @@ impl InnerContext {
         self.chip_count = Some(chip_count);
     }
 
+    /// Forget number of chips on chain (disables check for number of replies)
+    fn reset_chip_count(&mut self) {
+        self.chip_count = None;
+    }
+
     pub fn new(command_io: io::CommandRxTx) -> Self {
         Self {
             command_io,
@@ -281,6 +286,11 @@ impl Context {
         inner.set_chip_count(chip_count);
     }
 
+    pub async fn reset_chip_count(&self) {
+        let mut inner = self.inner.lock().await;
+        inner.reset_chip_count();
+    }
+
     pub fn new(command_io: io::CommandRxTx) -> Self {
         Self {
             inner: Arc::new(Mutex::new(InnerContext::new(command_io))),
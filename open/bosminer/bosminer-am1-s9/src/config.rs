@@ -33,11 +33,14 @@ pub mod api;
 mod metadata;
 pub mod support;
 
-use crate::bm1387::MidstateCount;
+use crate::bm1387::{self, MidstateCount};
+use crate::error;
 use crate::fan;
 use crate::hooks;
+use crate::io;
 use crate::monitor;
 use crate::power;
+use crate::utils;
 use crate::FrequencySettings;
 
 use support::OptionDefault;
@@ -100,15 +103,63 @@ pub const DEFAULT_TARGET_TEMP_C: f64 = 89.0;
 pub const DEFAULT_HOT_TEMP_C: f64 = 100.0;
 pub const DEFAULT_DANGEROUS_TEMP_C: f64 = 110.0;
 
+/// Default hysteresis of HOT temperature (0 means fans leave full speed as soon as temperature
+/// drops below HOT)
+pub const DEFAULT_HOT_TEMP_HYSTERESIS_C: f64 = 0.0;
+
+/// Default temperature below which fans are not driven by PID but run at cold speed
+pub const DEFAULT_COLD_TEMP_C: f64 = TEMPERATURE_C_MIN;
+
+/// Default sensor to trust when chip and board temperatures disagree
+pub const DEFAULT_TEMP_SENSOR_PREFERENCE: monitor::TempSensorPreference =
+    monitor::TempSensorPreference::Remote;
+/// Default difference of chip and board temperature (incl. offset) considered as disagreement
+pub const DEFAULT_TEMP_SENSOR_DISAGREEMENT_C: f32 = 20.0;
+
+/// Default fan behavior on normal shutdown
+pub const DEFAULT_SHUTDOWN_FAN_MODE: monitor::ShutdownFanMode = monitor::ShutdownFanMode::Stop;
+
 /// Default fan speed for manual target speed
 pub const DEFAULT_FAN_SPEED: usize = 100;
 
+/// Default fan speed used while temperature is below cold temperature
+pub const DEFAULT_COLD_FAN_SPEED: usize = 30;
+
 /// Default minimal running fans for monitoring
 pub const DEFAULT_MIN_FANS: usize = 1;
 
+/// Default number of consecutive monitor ticks with less than minimal running fans before
+/// it's treated as real
+pub const DEFAULT_MIN_FANS_TICKS: usize = 2;
+
 /// Index of hashboard that is to be instantiated
 pub const S9_HASHBOARD_INDEX: usize = 8;
 
+/// Hashboard slots that are probed for present hashboards by default
+pub const DEFAULT_HASHBOARD_SLOTS: [usize; 8] = [1, 2, 3, 4, 5, 6, 7, 8];
+
+/// Range of hashboard slot index (given by control board GPIO pins)
+pub const HASHBOARD_SLOT_MIN: usize = 1;
+pub const HASHBOARD_SLOT_MAX: usize = 8;
+
+/// Default number of consecutive failed hashchain initializations after which the hashboard
+/// is powered off and not started again
+pub const DEFAULT_MAX_ENUMERATION_FAILURES: usize = 10;
+/// Upper bound of enumeration failures before quarantine (0 disables the quarantine)
+pub const MAX_ENUMERATION_FAILURES_MAX: usize = 1000;
+
+/// Upper bound of raw FPGA solutions kept per hash chain for debugging
+pub const RAW_SOLUTION_HISTORY_MAX: usize = 10_000;
+
+/// Default number of consecutive failed reads after which temperature sensor is disabled
+pub const DEFAULT_MAX_SENSOR_READ_ERRORS: usize = 10;
+
+/// Default number of chips to consider OK for initialization (S9 hashboard)
+pub const DEFAULT_EXPECTED_CHIPS_ON_CHAIN: usize = 63;
+
+/// Default address of chip with connected temperature sensor
+pub const DEFAULT_SENSOR_CHIP_ADDRESS: usize = 61;
+
 /// Range of hash chain index
 pub const HASH_CHAIN_INDEX_MIN: usize = 6;
 pub const HASH_CHAIN_INDEX_MAX: usize = 8;
@@ -117,10 +168,17 @@ pub const HASH_CHAIN_INDEX_MAX: usize = 8;
 pub const FREQUENCY_MHZ_MIN: f64 = 200.0;
 pub const FREQUENCY_MHZ_MAX: f64 = 900.0;
 
+/// Maximal difference between requested frequency and the nearest PLL setting in Hz
+pub const PLL_FREQ_ERROR_TOLERANCE_HZ_MAX: usize = 10_000_000;
+
 /// Range of hash chain voltage
 pub const VOLTAGE_V_MIN: f64 = 7.95;
 pub const VOLTAGE_V_MAX: f64 = 9.4;
 
+/// Range of FPGA IP core clock speed in Hz (for differently clocked bitstreams)
+pub const FPGA_CLOCK_FREQUENCY_HZ_MIN: usize = 10_000_000;
+pub const FPGA_CLOCK_FREQUENCY_HZ_MAX: usize = 250_000_000;
+
 /// Range of monitored temperature
 pub const TEMPERATURE_C_MIN: f64 = 0.0;
 pub const TEMPERATURE_C_MAX: f64 = 200.0;
@@ -139,6 +197,55 @@ pub const DEFAULT_ASIC_DIFFICULTY: usize = 64;
 /// Default hashrate interval used for statistics in seconds
 pub const DEFAULT_HASHRATE_INTERVAL: Duration = Duration::from_secs(60);
 
+/// Default delay between the first successive hashboard initialization attempts
+pub const DEFAULT_ENUM_RETRY_DELAY: Duration = Duration::from_secs(10);
+/// Default growth of delay between successive hashboard initialization attempts
+pub const DEFAULT_ENUM_RETRY_MULTIPLIER: u32 = 2;
+/// Default upper bound of delay between successive hashboard initialization attempts
+pub const DEFAULT_ENUM_RETRY_MAX_DELAY: Duration = Duration::from_secs(120);
+/// Default number of hashboard initialization retries
+pub const DEFAULT_ENUM_RETRY_COUNT: usize = 10;
+/// Default policy for chains with less chips than expected (accept them after half of the
+/// initialization retries have been exhausted)
+pub const DEFAULT_SHORT_CHAIN_POLICY: ShortChainPolicy =
+    ShortChainPolicy::AfterTries(DEFAULT_ENUM_RETRY_COUNT / 2);
+
+/// Default amount of open-core work relative to number of cores on chip
+pub const DEFAULT_INIT_WORK_MULTIPLIER: f32 = 1.0;
+
+/// Range of open-core work multiplier (more work only prolongs hashchain start)
+pub const INIT_WORK_MULTIPLIER_MIN: f32 = 0.0;
+pub const INIT_WORK_MULTIPLIER_MAX: f32 = 4.0;
+
+/// Default value for waiting for hashboard to heat up before mining (in PID mode)
+pub const DEFAULT_PREHEAT: bool = true;
+
+/// Default base delay quantum of hashboard reset sequence
+pub const DEFAULT_INIT_DELAY: Duration = Duration::from_secs(1);
+
+/// Range of base delay quantum of hashboard reset sequence (delays can only be prolonged, the
+/// whole sequence takes four quanta and it has to fit into monitor start timeout)
+pub const INIT_DELAY_MIN: Duration = DEFAULT_INIT_DELAY;
+pub const INIT_DELAY_MAX: Duration = Duration::from_secs(10);
+
+/// Range of voltage controller heart beat period (it's shortened further to satisfy watchdog of
+/// known PIC firmware)
+pub const HEART_BEAT_PERIOD_MIN: Duration = Duration::from_millis(100);
+pub const HEART_BEAT_PERIOD_MAX: Duration = Duration::from_secs(10);
+
+/// Maximal time voltage ramp may take over the whole range of working voltage (so that it doesn't
+/// delay hashchain start too much)
+pub const MAX_VOLTAGE_RAMP_DURATION: Duration = Duration::from_secs(30);
+
+/// Default maximal time to wait for hashboard to heat up
+pub const DEFAULT_PREHEAT_TIMEOUT: Duration = Duration::from_secs(180);
+
+/// Default tolerance of preheat temperature (temperature this much below target is hot enough)
+pub const DEFAULT_PREHEAT_TEMP_EPSILON_C: f32 = 2.0;
+
+/// Longest allowed wait for hashboard to heat up
+pub const MAX_PREHEAT_TIMEOUT: Duration = Duration::from_secs(1800);
+
 /// Maximum time it takes to compute one job under normal circumstances
 pub const JOB_TIMEOUT: Duration = Duration::from_secs(5);
 
@@ -147,6 +254,167 @@ pub struct ResolvedChainConfig {
     pub frequency: FrequencySettings,
     pub voltage: power::Voltage,
     pub enabled: bool,
+    pub core_address_layout: bm1387::CoreAddressLayout,
+    pub raw_solution_history: usize,
+    pub sensor_chip_address: usize,
+    /// 0 means the hashchain is never quarantined
+    pub max_enumeration_failures: usize,
+    pub chip_revision_policy: ChipRevisionPolicy,
+    pub enum_retry_policy: EnumRetryPolicy,
+    pub short_chain_policy: ShortChainPolicy,
+    /// `None` means default for detected voltage controller firmware
+    pub heart_beat_period: Option<Duration>,
+    /// 0 means no open-core work is sent
+    pub init_work_multiplier: f32,
+    /// `None` means voltage is lowered after open-core work in one step
+    pub voltage_ramp: Option<power::VoltageRamp>,
+    /// Voltage range allowed on a running hashchain (open-core voltage is exempt)
+    pub voltage_bounds: power::VoltageBounds,
+    /// If false, `wait_for_preheat` returns immediately
+    pub preheat: bool,
+    pub preheat_timeout: Duration,
+    pub preheat_temp_epsilon: f32,
+    /// Base clock speed of the FPGA IP core in Hz
+    pub fpga_clock_frequency: usize,
+    /// Base delay quantum of hashboard reset sequence
+    pub init_delay: Duration,
+    /// Number of chips that make a complete chain
+    pub expected_chip_count: usize,
+    pub sensor_config: SensorConfig,
+    /// How often temperature update is sent to monitor (see `monitor::Timeouts`)
+    pub temp_update_interval: Duration,
+    /// Maximum difference between requested frequency and the nearest PLL setting (in Hz)
+    pub pll_freq_error_tolerance: usize,
+    /// Fudge factor of work delay (see `utils::WORK_DELAY_FUDGE`)
+    pub work_delay_fudge: f64,
+}
+
+/// How to treat failures of hashboard temperature sensors
+#[derive(Serialize, Deserialize, Copy, Clone, Debug, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct SensorConfig {
+    /// Sensor is not read anymore after this many consecutive failed reads
+    pub max_read_errors: usize,
+}
+
+impl Default for SensorConfig {
+    fn default() -> Self {
+        Self {
+            max_read_errors: DEFAULT_MAX_SENSOR_READ_ERRORS,
+        }
+    }
+}
+
+/// How to retry failed hashchain start (enumeration)
+///
+/// Delay between attempts grows exponentially from `base_delay` by `multiplier` and it's capped
+/// at `max_delay`. Delays are configured in seconds.
+#[derive(Serialize, Deserialize, Copy, Clone, Debug, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct EnumRetryPolicy {
+    #[serde(with = "support::duration_secs")]
+    pub base_delay: Duration,
+    pub multiplier: u32,
+    #[serde(with = "support::duration_secs")]
+    pub max_delay: Duration,
+    /// Number of retries after the first failed attempt
+    pub retry_count: usize,
+}
+
+impl EnumRetryPolicy {
+    /// Delay before retry number `retry` (starting from 0)
+    pub fn delay(&self, retry: usize) -> Duration {
+        let mut delay = self.base_delay;
+        for _ in 0..retry {
+            if delay >= self.max_delay {
+                break;
+            }
+            delay *= self.multiplier;
+        }
+        delay.min(self.max_delay)
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.multiplier < 1 {
+            return Err(format!(
+                "'multiplier' ({}) must be at least 1",
+                self.multiplier
+            ));
+        }
+        if self.base_delay > self.max_delay {
+            return Err(format!(
+                "'base_delay' ({:?}) must not be greater than 'max_delay' ({:?})",
+                self.base_delay, self.max_delay
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl Default for EnumRetryPolicy {
+    fn default() -> Self {
+        Self {
+            base_delay: DEFAULT_ENUM_RETRY_DELAY,
+            multiplier: DEFAULT_ENUM_RETRY_MULTIPLIER,
+            max_delay: DEFAULT_ENUM_RETRY_MAX_DELAY,
+            retry_count: DEFAULT_ENUM_RETRY_COUNT,
+        }
+    }
+}
+
+/// When to accept hashchain with less chips than expected
+///
+/// It's configured as `"never"`, `"always"` or `{ after_tries = <n> }`.
+#[derive(Serialize, Deserialize, Copy, Clone, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ShortChainPolicy {
+    /// Treat it as enumeration error (until retries are exhausted)
+    Never,
+    /// Accept it after given number of failed initialization attempts
+    AfterTries(usize),
+    /// Accept it right away
+    Always,
+}
+
+impl ShortChainPolicy {
+    /// Default policy for given retry policy: accept short chain after half of the retries
+    /// have been exhausted
+    pub fn default_for(retry_policy: &EnumRetryPolicy) -> Self {
+        Self::AfterTries(retry_policy.retry_count / 2)
+    }
+
+    /// Returns true if short chain can be accepted after `failed_tries` initialization attempts
+    pub fn accept(&self, failed_tries: usize) -> bool {
+        match *self {
+            Self::Never => false,
+            Self::AfterTries(tries) => failed_tries >= tries,
+            Self::Always => true,
+        }
+    }
+}
+
+impl Default for ShortChainPolicy {
+    fn default() -> Self {
+        DEFAULT_SHORT_CHAIN_POLICY
+    }
+}
+
+/// What to do with chips of unexpected revision found during enumeration
+#[derive(Serialize, Deserialize, Copy, Clone, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ChipRevisionPolicy {
+    /// Treat it as enumeration error
+    Strict,
+    /// Log a warning and use the chip as usual
+    Warn,
+    /// Log a warning and ignore solutions coming from the chip
+    Exclude,
+}
+
+impl Default for ChipRevisionPolicy {
+    fn default() -> Self {
+        Self::Strict
+    }
 }
 
 #[derive(Serialize, Deserialize, Copy, Clone, Debug)]
@@ -180,8 +448,6 @@ pub struct Format {
 #[derive(Serialize, Deserialize, Default, Clone, Debug)]
 #[serde(deny_unknown_fields)]
 pub struct HashChainGlobal {
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub asic_boost: Option<bool>,
     #[serde(flatten)]
     pub overridable: Option<HashChain>,
 }
@@ -189,12 +455,18 @@ pub struct HashChainGlobal {
 #[derive(Serialize, Deserialize, Default, Clone, Debug)]
 #[serde(deny_unknown_fields)]
 pub struct HashChain {
+    /// Determines midstate count of the hash chain (see `ASIC_BOOST_MIDSTATE_COUNT`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub asic_boost: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub enabled: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub frequency: Option<f64>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub voltage: Option<f64>,
+    /// Frequency (MHz) of individual chips (key is chip index) overriding `frequency`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub chip_frequency: Option<BTreeMap<String, f64>>,
 }
 
 #[derive(Serialize, Deserialize, Default, Clone, Debug)]
@@ -207,7 +479,11 @@ pub struct TempControl {
     #[serde(skip_serializing_if = "Option::is_none")]
     hot_temp: Option<f64>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    hot_temp_hysteresis: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     dangerous_temp: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cold_temp: Option<f64>,
 }
 
 #[derive(Serialize, Deserialize, Default, Clone, Debug)]
@@ -217,6 +493,8 @@ pub struct FanControl {
     speed: Option<usize>,
     #[serde(skip_serializing_if = "Option::is_none")]
     min_fans: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cold_speed: Option<usize>,
 }
 
 #[derive(Serialize, Deserialize, Default, Debug)]
@@ -234,17 +512,135 @@ pub struct Backend {
     #[serde(rename = "hash_chain")]
     #[serde(skip_serializing_if = "Option::is_none")]
     hash_chains: Option<BTreeMap<String, HashChain>>,
+    /// Use just one midstate on all hash chains regardless of their `asic_boost` setting
+    #[serde(skip)]
+    pub disable_asic_boost: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
     temp_control: Option<TempControl>,
     #[serde(skip_serializing_if = "Option::is_none")]
     fan_control: Option<FanControl>,
+    /// Explicit list of hashboard slots to be probed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hashboard_slots: Option<Vec<usize>>,
     #[serde(rename = "group")]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub groups: Option<Vec<bosminer_config::GroupConfig>>,
     #[serde(skip)]
     pub hooks: Option<Arc<dyn hooks::Hooks>>,
+    /// Hook `Ctrl-C`, `SIGTERM` and other termination signals to halt the miner. Applications
+    /// embedding the backend can disable it and drive shutdown with `halt::Sender` (passed to
+    /// `Hooks::halt_created`) instead.
+    #[serde(skip)]
+    pub hook_termination_signals: Option<bool>,
     #[serde(skip)]
     pub fans_on_while_warming_up: Option<bool>,
+    /// Halt only failed hashchain instead of the whole miner (on multi-board rigs)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub isolate_failed_chains: Option<bool>,
+    /// Trust board (local) sensor on boards with noisy chip (remote) sensor
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub temp_sensor_preference: Option<monitor::TempSensorPreference>,
+    /// Difference (in °C) of chip and board temperature considered as disagreement
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub temp_sensor_disagreement: Option<f32>,
+    /// Ramp fan speed gradually by at most this many percent per monitor tick
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fan_max_speed_step_per_tick: Option<u8>,
+    /// Tolerate glitchy fan feedback: `min_fans` has to be violated for this many consecutive
+    /// monitor ticks to shut the miner down
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fan_min_fans_ticks: Option<usize>,
+    /// Override monitor timeouts (e.g. for slow-starting boards with many chips)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub monitor_timeouts: Option<monitor::Timeouts>,
+    /// What to do with fans on normal shutdown (e.g. cool down boards before stopping them)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub shutdown_fan_mode: Option<monitor::ShutdownFanMode>,
+    /// Limit total power drawn by hashboards (monitor lowers frequency when it's exceeded).
+    /// Power is measured by `power::PowerMeter` provided by `hooks::Hooks::power_meter`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub power_limit: Option<monitor::PowerControlConfig>,
+    /// Override core address decoding for non-standard hardware
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub core_address_layout: Option<bm1387::CoreAddressLayout>,
+    /// Debugging: number of raw FPGA solutions to keep per hash chain (0 disables the history)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub raw_solution_history: Option<usize>,
+    /// Override chip with temperature sensor for different board revisions
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sensor_chip_address: Option<usize>,
+    /// Power off hashboard (and do not start it again) after this many consecutive failed
+    /// initializations (0 never gives up)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_enumeration_failures: Option<usize>,
+    /// Allow running chains with chips of unexpected revision (e.g. remarked chips), default is
+    /// `strict`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub chip_revision_policy: Option<ChipRevisionPolicy>,
+    /// Retry failed hashchain start with different delays (e.g. on boards that need longer to
+    /// recover)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enum_retry_policy: Option<EnumRetryPolicy>,
+    /// Whether to mine on hashchain with missing chips (default depends on `enum_retry_policy`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub short_chain_policy: Option<ShortChainPolicy>,
+    /// Override period of voltage controller heart beat (e.g. for different PIC firmware), in
+    /// milliseconds
+    #[serde(
+        default,
+        with = "support::option_duration_millis",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub heart_beat_period: Option<Duration>,
+    /// Tune amount of open-core work sent at high voltage during hashchain initialization
+    /// (0 skips it, which speeds up start but cores may not be warmed-up properly)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub init_work_multiplier: Option<f32>,
+    /// Lower voltage gradually after open-core work (to avoid stressing the voltage controller)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub voltage_ramp: Option<power::VoltageRamp>,
+    /// Narrow the range of working voltage (e.g. to protect boards from a misbehaving tuner)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_voltage: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_voltage: Option<f64>,
+    /// Skip waiting for hashboard to heat up to target temperature on start (e.g. in warm
+    /// environments where it only delays start)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub preheat: Option<bool>,
+    /// Give up waiting for hashboard to heat up after this time (in seconds)
+    #[serde(
+        default,
+        with = "support::option_duration_secs",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub preheat_timeout: Option<Duration>,
+    /// Consider hashboard hot enough when it's at most this many degrees below target
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub preheat_temp_epsilon: Option<f32>,
+    /// Override base clock speed of the FPGA IP core (in Hz) for differently clocked bitstreams
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fpga_clock_frequency: Option<usize>,
+    /// Prolong delays of hashboard reset sequence for board revisions that need longer to
+    /// settle (they fail enumeration otherwise), in seconds
+    #[serde(
+        default,
+        with = "support::option_duration_secs",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub init_delay: Option<Duration>,
+    /// Number of chips on a complete chain for boards that differ from S9
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expected_chip_count: Option<usize>,
+    /// Tolerate more read failures on sensors that are placed on noisy part of the board
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sensor_config: Option<SensorConfig>,
+    /// Refuse frequencies that are further than this from the nearest PLL setting (in Hz)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pll_freq_error_tolerance: Option<usize>,
+    /// Send work faster/slower than chips compute it (see `utils::WORK_DELAY_FUDGE`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub work_delay_fudge: Option<f64>,
 }
 
 pub trait ConfigBody
@@ -340,6 +736,627 @@ where
     }
 }
 
+/// One problem found by `validate_config`
+#[derive(Clone, Debug, PartialEq)]
+pub struct ConfigError {
+    /// Part of configuration the problem relates to
+    pub section: String,
+    pub message: String,
+}
+
+impl ConfigError {
+    fn new<S: ToString, M: ToString>(section: S, message: M) -> Self {
+        Self {
+            section: section.to_string(),
+            message: message.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.section, self.message)
+    }
+}
+
+fn asic_boost_midstate_count(asic_boost: bool) -> usize {
+    if asic_boost {
+        ASIC_BOOST_MIDSTATE_COUNT
+    } else {
+        1
+    }
+}
+
+/// Convert per-chip frequency table to chip indices and frequencies (MHz)
+fn parse_chip_frequency(
+    chip_frequency: &BTreeMap<String, f64>,
+    expected_chip_count: usize,
+) -> Result<Vec<(usize, f64)>, String> {
+    chip_frequency
+        .iter()
+        .map(|(chip_idx, frequency)| {
+            chip_idx
+                .parse::<usize>()
+                .ok()
+                .filter(|chip_idx| *chip_idx < expected_chip_count)
+                .map(|chip_idx| (chip_idx, *frequency))
+                .ok_or_else(|| {
+                    format!(
+                        "chip index '{}' is out of range '0..{}'",
+                        chip_idx,
+                        expected_chip_count - 1
+                    )
+                })
+        })
+        .collect()
+}
+
+/// Build frequency settings with all chips at `frequency` (MHz) overlaid by per-chip
+/// frequencies
+fn chip_frequency_settings(
+    frequency: f64,
+    chip_frequency: &[(usize, f64)],
+    expected_chip_count: usize,
+) -> FrequencySettings {
+    let mut frequency_settings =
+        FrequencySettings::from_frequency((frequency * 1_000_000.0) as usize, expected_chip_count);
+    for (chip_idx, frequency) in chip_frequency.iter() {
+        frequency_settings.chip[*chip_idx] = (*frequency * 1_000_000.0) as usize;
+    }
+    frequency_settings
+}
+
+fn validate_hash_chain(
+    section: String,
+    hash_chain: &HashChain,
+    expected_chip_count: usize,
+    pll_freq_error_tolerance: usize,
+    errors: &mut Vec<ConfigError>,
+) {
+    if let Some(frequency) = hash_chain.frequency {
+        if !(FREQUENCY_MHZ_MIN..=FREQUENCY_MHZ_MAX).contains(&frequency) {
+            errors.push(ConfigError::new(
+                &section,
+                format!(
+                    "frequency {} MHz is out of range '{}..{}'",
+                    frequency, FREQUENCY_MHZ_MIN, FREQUENCY_MHZ_MAX
+                ),
+            ));
+        } else if let Err(e) = FrequencySettings::from_frequency(
+            (frequency * 1_000_000.0) as usize,
+            expected_chip_count,
+        )
+        .validate(&bm1387::PRECOMPUTED_PLL, pll_freq_error_tolerance)
+        {
+            errors.push(ConfigError::new(&section, e));
+        }
+    }
+    if let Some(chip_frequency) = hash_chain.chip_frequency.as_ref() {
+        match parse_chip_frequency(chip_frequency, expected_chip_count) {
+            Ok(chip_frequency) => {
+                let mut in_range = true;
+                for (chip_idx, frequency) in chip_frequency.iter() {
+                    if !(FREQUENCY_MHZ_MIN..=FREQUENCY_MHZ_MAX).contains(frequency) {
+                        in_range = false;
+                        errors.push(ConfigError::new(
+                            &section,
+                            format!(
+                                "chip {} frequency {} MHz is out of range '{}..{}'",
+                                chip_idx, frequency, FREQUENCY_MHZ_MIN, FREQUENCY_MHZ_MAX
+                            ),
+                        ));
+                    }
+                }
+                let frequency = hash_chain.frequency.unwrap_or(DEFAULT_FREQUENCY_MHZ);
+                if in_range {
+                    if let Err(e) =
+                        chip_frequency_settings(frequency, &chip_frequency, expected_chip_count)
+                            .validate(&bm1387::PRECOMPUTED_PLL, pll_freq_error_tolerance)
+                    {
+                        errors.push(ConfigError::new(&section, e));
+                    }
+                }
+            }
+            Err(e) => errors.push(ConfigError::new(&section, e)),
+        }
+    }
+    if let Some(voltage) = hash_chain.voltage {
+        if !(VOLTAGE_V_MIN..=VOLTAGE_V_MAX).contains(&voltage) {
+            errors.push(ConfigError::new(
+                &section,
+                format!(
+                    "voltage {} V is out of range '{}..{}'",
+                    voltage, VOLTAGE_V_MIN, VOLTAGE_V_MAX
+                ),
+            ));
+        } else if let Err(e) = power::Voltage::from_volts(voltage as f32) {
+            errors.push(ConfigError::new(&section, e));
+        }
+    }
+}
+
+/// Validate configuration without touching any hardware
+///
+/// In addition to `sanity_check` (which stops at the first problem) it checks values that are
+/// otherwise only found out when the miner starts: frequency against PLL table, voltage range,
+/// midstate count and temperature/fan settings. All problems found are reported.
+pub fn validate_config(config: &Backend) -> Result<(), Vec<ConfigError>> {
+    let mut errors = Vec::new();
+
+    if let Err(message) = config.sanity_check() {
+        errors.push(ConfigError::new("config", message));
+    }
+
+    if let Err(e) = config.resolve_monitor_timeouts().validate() {
+        errors.push(ConfigError::new("monitor", e));
+    }
+    if let Some(power_limit) = config.power_limit.as_ref() {
+        if !(power_limit.max_watts > 0.0) {
+            errors.push(ConfigError::new(
+                "power_limit",
+                format!("'max_watts' ({}) must be positive", power_limit.max_watts),
+            ));
+        }
+    }
+
+    if let Some(enum_retry_policy) = config.enum_retry_policy.as_ref() {
+        if let Err(e) = enum_retry_policy.validate() {
+            errors.push(ConfigError::new("enum_retry_policy", e));
+        }
+    }
+    if let Some(init_work_multiplier) = config.init_work_multiplier {
+        if !(INIT_WORK_MULTIPLIER_MIN..=INIT_WORK_MULTIPLIER_MAX).contains(&init_work_multiplier) {
+            errors.push(ConfigError::new(
+                "init_work_multiplier",
+                format!(
+                    "open-core work multiplier {} is out of range '{}..{}'",
+                    init_work_multiplier, INIT_WORK_MULTIPLIER_MIN, INIT_WORK_MULTIPLIER_MAX
+                ),
+            ));
+        }
+    }
+    if let Some(fpga_clock_frequency) = config.fpga_clock_frequency {
+        if !(FPGA_CLOCK_FREQUENCY_HZ_MIN..=FPGA_CLOCK_FREQUENCY_HZ_MAX)
+            .contains(&fpga_clock_frequency)
+        {
+            errors.push(ConfigError::new(
+                "fpga_clock_frequency",
+                format!(
+                    "FPGA clock frequency {} Hz is out of range '{}..{}'",
+                    fpga_clock_frequency, FPGA_CLOCK_FREQUENCY_HZ_MIN, FPGA_CLOCK_FREQUENCY_HZ_MAX
+                ),
+            ));
+        }
+    }
+    if let Some(ShortChainPolicy::AfterTries(tries)) = config.short_chain_policy {
+        let retry_count = config.enum_retry_policy.unwrap_or_default().retry_count;
+        if tries > retry_count {
+            errors.push(ConfigError::new(
+                "short_chain_policy",
+                format!(
+                    "short chain would never be accepted after {} tries with {} retries \
+                     (use 'never' instead)",
+                    tries, retry_count
+                ),
+            ));
+        }
+    }
+    if let Some(voltage_ramp) = config.voltage_ramp.as_ref() {
+        if !(voltage_ramp.step > 0.0 && voltage_ramp.step.is_finite()) {
+            errors.push(ConfigError::new(
+                "voltage_ramp",
+                format!("'step' ({}) must be positive", voltage_ramp.step),
+            ));
+        } else if voltage_ramp.duration((VOLTAGE_V_MAX - VOLTAGE_V_MIN) as f32)
+            > MAX_VOLTAGE_RAMP_DURATION
+        {
+            errors.push(ConfigError::new(
+                "voltage_ramp",
+                format!(
+                    "ramp over working voltage range takes longer than {:?} \
+                     (use longer 'step' or shorter 'delay')",
+                    MAX_VOLTAGE_RAMP_DURATION
+                ),
+            ));
+        }
+    }
+    if let Some(init_delay) = config.init_delay {
+        if !(INIT_DELAY_MIN..=INIT_DELAY_MAX).contains(&init_delay) {
+            errors.push(ConfigError::new(
+                "init_delay",
+                format!(
+                    "reset sequence delay {:?} is out of range '{:?}..{:?}'",
+                    init_delay, INIT_DELAY_MIN, INIT_DELAY_MAX
+                ),
+            ));
+        }
+    }
+    if let Some(raw_solution_history) = config.raw_solution_history {
+        if raw_solution_history > RAW_SOLUTION_HISTORY_MAX {
+            errors.push(ConfigError::new(
+                "raw_solution_history",
+                format!(
+                    "raw solution history {} is out of range '0..{}'",
+                    raw_solution_history, RAW_SOLUTION_HISTORY_MAX
+                ),
+            ));
+        }
+    }
+    if let Some(heart_beat_period) = config.heart_beat_period {
+        if !(HEART_BEAT_PERIOD_MIN..=HEART_BEAT_PERIOD_MAX).contains(&heart_beat_period) {
+            errors.push(ConfigError::new(
+                "heart_beat_period",
+                format!(
+                    "heart beat period {:?} is out of range '{:?}..{:?}'",
+                    heart_beat_period, HEART_BEAT_PERIOD_MIN, HEART_BEAT_PERIOD_MAX
+                ),
+            ));
+        }
+    }
+    if let Some(max_enumeration_failures) = config.max_enumeration_failures {
+        if max_enumeration_failures > MAX_ENUMERATION_FAILURES_MAX {
+            errors.push(ConfigError::new(
+                "max_enumeration_failures",
+                format!(
+                    "maximal number of enumeration failures {} is out of range '0..{}'",
+                    max_enumeration_failures, MAX_ENUMERATION_FAILURES_MAX
+                ),
+            ));
+        }
+    }
+    if let Some(work_delay_fudge) = config.work_delay_fudge {
+        if let Err(e) = utils::validate_work_delay_fudge(work_delay_fudge) {
+            errors.push(ConfigError::new("work_delay_fudge", e));
+        }
+    }
+    if let Some(core_address_layout) = config.core_address_layout.as_ref() {
+        if let Err(e) = core_address_layout.validate() {
+            errors.push(ConfigError::new("core_address_layout", e));
+        }
+    }
+
+    // Hash chain settings
+    if let Err(e) = MidstateCount::try_new(config.midstate_count()) {
+        errors.push(ConfigError::new("hash_chain_global", e));
+    }
+    let mut expected_chip_count = config.resolve_expected_chip_count();
+    if !(1..crate::MAX_CHIPS_ON_CHAIN).contains(&expected_chip_count) {
+        errors.push(ConfigError::new(
+            "expected_chip_count",
+            format!(
+                "expected chip count {} is out of range '1..{}'",
+                expected_chip_count,
+                crate::MAX_CHIPS_ON_CHAIN - 1
+            ),
+        ));
+        // check the rest against S9 chain
+        expected_chip_count = DEFAULT_EXPECTED_CHIPS_ON_CHAIN;
+    }
+    let pll_freq_error_tolerance = config.resolve_pll_freq_error_tolerance();
+    if pll_freq_error_tolerance > PLL_FREQ_ERROR_TOLERANCE_HZ_MAX {
+        errors.push(ConfigError::new(
+            "pll_freq_error_tolerance",
+            format!(
+                "PLL frequency error tolerance {} Hz is out of range '0..{}'",
+                pll_freq_error_tolerance, PLL_FREQ_ERROR_TOLERANCE_HZ_MAX
+            ),
+        ));
+    }
+    if let Some(sensor_chip_address) = config.sensor_chip_address {
+        if sensor_chip_address >= expected_chip_count {
+            errors.push(ConfigError::new(
+                "sensor_chip_address",
+                format!(
+                    "sensor chip address {} must be lower than expected chip count {}",
+                    sensor_chip_address, expected_chip_count
+                ),
+            ));
+        }
+    }
+    if let Some(hash_chain) = config
+        .hash_chain_global
+        .as_ref()
+        .and_then(|v| v.overridable.as_ref())
+    {
+        validate_hash_chain(
+            "hash_chain_global".to_string(),
+            hash_chain,
+            expected_chip_count,
+            pll_freq_error_tolerance,
+            &mut errors,
+        );
+    }
+    if let Some(hash_chains) = &config.hash_chains {
+        for (idx, hash_chain) in hash_chains.iter() {
+            validate_hash_chain(
+                format!("hash_chain.{}", idx),
+                hash_chain,
+                expected_chip_count,
+                pll_freq_error_tolerance,
+                &mut errors,
+            );
+        }
+    }
+
+    // Working voltage range
+    let mut bounds_in_range = true;
+    for (name, voltage) in [
+        ("min_voltage", config.min_voltage),
+        ("max_voltage", config.max_voltage),
+    ]
+    .iter()
+    {
+        if let Some(voltage) = voltage {
+            if !(VOLTAGE_V_MIN..=VOLTAGE_V_MAX).contains(voltage) {
+                bounds_in_range = false;
+                errors.push(ConfigError::new(
+                    name,
+                    format!(
+                        "voltage {} V is out of range '{}..{}'",
+                        voltage, VOLTAGE_V_MIN, VOLTAGE_V_MAX
+                    ),
+                ));
+            }
+        }
+    }
+    if bounds_in_range {
+        match config.resolve_voltage_bounds() {
+            Ok(voltage_bounds) => {
+                let global_voltage = config
+                    .hash_chain_global
+                    .as_ref()
+                    .and_then(|v| v.overridable.as_ref())
+                    .and_then(|v| v.voltage)
+                    .unwrap_or(DEFAULT_VOLTAGE_V);
+                let chain_voltages = config
+                    .hash_chains
+                    .iter()
+                    .flat_map(|hash_chains| hash_chains.iter())
+                    .filter_map(|(idx, hash_chain)| {
+                        hash_chain
+                            .voltage
+                            .map(|voltage| (format!("hash_chain.{}", idx), voltage))
+                    });
+                let voltages = std::iter::once(("hash_chain_global".to_string(), global_voltage))
+                    .chain(chain_voltages);
+                for (section, voltage) in voltages {
+                    // voltage out of range is reported by `validate_hash_chain`
+                    if !(VOLTAGE_V_MIN..=VOLTAGE_V_MAX).contains(&voltage) {
+                        continue;
+                    }
+                    if let Err(e) = power::Voltage::from_volts(voltage as f32)
+                        .and_then(|voltage| voltage_bounds.check(voltage))
+                    {
+                        errors.push(ConfigError::new(section, e));
+                    }
+                }
+            }
+            Err(e) => errors.push(ConfigError::new("min_voltage", e)),
+        }
+    }
+
+    // Temperature control settings
+    let temp_control = config.temp_control.clone().unwrap_or_default();
+    let mode = temp_control.mode.unwrap_or(DEFAULT_TEMP_CONTROL_MODE);
+    let target_temp = temp_control.target_temp.unwrap_or(DEFAULT_TARGET_TEMP_C);
+    let hot_temp = temp_control.hot_temp.unwrap_or(DEFAULT_HOT_TEMP_C);
+    let dangerous_temp = temp_control
+        .dangerous_temp
+        .unwrap_or(DEFAULT_DANGEROUS_TEMP_C);
+    let cold_temp = temp_control.cold_temp.unwrap_or(DEFAULT_COLD_TEMP_C);
+    let hot_temp_hysteresis = temp_control
+        .hot_temp_hysteresis
+        .unwrap_or(DEFAULT_HOT_TEMP_HYSTERESIS_C);
+    for (name, temp) in [
+        ("target_temp", target_temp),
+        ("cold_temp", cold_temp),
+        ("hot_temp_hysteresis", hot_temp_hysteresis),
+        ("hot_temp", hot_temp),
+        ("dangerous_temp", dangerous_temp),
+    ]
+    .iter()
+    {
+        if !(TEMPERATURE_C_MIN..=TEMPERATURE_C_MAX).contains(temp) {
+            errors.push(ConfigError::new(
+                "temp_control",
+                format!(
+                    "'{}' ({}) is out of range '{}..{}'",
+                    name, temp, TEMPERATURE_C_MIN, TEMPERATURE_C_MAX
+                ),
+            ));
+        }
+    }
+    match mode {
+        TempControlMode::Auto | TempControlMode::Manual => {
+            if let TempControlMode::Auto = mode {
+                if target_temp >= hot_temp {
+                    errors.push(ConfigError::new(
+                        "temp_control",
+                        format!(
+                            "'target_temp' ({}) must be lower than 'hot_temp' ({})",
+                            target_temp, hot_temp
+                        ),
+                    ));
+                }
+                if cold_temp >= target_temp {
+                    errors.push(ConfigError::new(
+                        "temp_control",
+                        format!(
+                            "'cold_temp' ({}) must be lower than 'target_temp' ({})",
+                            cold_temp, target_temp
+                        ),
+                    ));
+                }
+            }
+            if hot_temp - hot_temp_hysteresis < 0.0 {
+                errors.push(ConfigError::new(
+                    "temp_control",
+                    format!(
+                        "'hot_temp_hysteresis' ({}) must not be greater than 'hot_temp' ({})",
+                        hot_temp_hysteresis, hot_temp
+                    ),
+                ));
+            }
+            if hot_temp >= dangerous_temp {
+                errors.push(ConfigError::new(
+                    "temp_control",
+                    format!(
+                        "'hot_temp' ({}) must be lower than 'dangerous_temp' ({})",
+                        hot_temp, dangerous_temp
+                    ),
+                ));
+            }
+        }
+        TempControlMode::Disabled => {}
+    }
+
+    if let Some(disagreement) = config.temp_sensor_disagreement {
+        if !(disagreement > 0.0 && disagreement as f64 <= TEMPERATURE_C_MAX) {
+            errors.push(ConfigError::new(
+                "temp_sensor_disagreement",
+                format!(
+                    "temperature disagreement {} is out of range '{}..{}'",
+                    disagreement, TEMPERATURE_C_MIN, TEMPERATURE_C_MAX
+                ),
+            ));
+        }
+    }
+
+    if let Some(sensor_config) = config.sensor_config.as_ref() {
+        // sensor would be disabled right away
+        if sensor_config.max_read_errors == 0 {
+            errors.push(ConfigError::new(
+                "sensor_config",
+                "'max_read_errors' must not be zero",
+            ));
+        }
+    }
+    if let Some(preheat_timeout) = config.preheat_timeout {
+        if preheat_timeout > MAX_PREHEAT_TIMEOUT {
+            errors.push(ConfigError::new(
+                "preheat_timeout",
+                format!(
+                    "preheat timeout {:?} is longer than {:?}",
+                    preheat_timeout, MAX_PREHEAT_TIMEOUT
+                ),
+            ));
+        }
+    }
+    if let Some(epsilon) = config.preheat_temp_epsilon {
+        if !(TEMPERATURE_C_MIN..=TEMPERATURE_C_MAX).contains(&(epsilon as f64)) {
+            errors.push(ConfigError::new(
+                "preheat_temp_epsilon",
+                format!(
+                    "preheat temperature tolerance {} is out of range '{}..{}'",
+                    epsilon, TEMPERATURE_C_MIN, TEMPERATURE_C_MAX
+                ),
+            ));
+        }
+    }
+
+    // Fan control settings
+    let fan_control = config.fan_control.clone().unwrap_or_default();
+    if let Some(speed) = fan_control.speed {
+        if !(FAN_SPEED_MIN..=FAN_SPEED_MAX).contains(&speed) {
+            errors.push(ConfigError::new(
+                "fan_control",
+                format!(
+                    "'speed' ({}) is out of range '{}..{}'",
+                    speed, FAN_SPEED_MIN, FAN_SPEED_MAX
+                ),
+            ));
+        }
+    }
+    if let Some(cold_speed) = fan_control.cold_speed {
+        if !(FAN_SPEED_MIN..=FAN_SPEED_MAX).contains(&cold_speed) {
+            errors.push(ConfigError::new(
+                "fan_control",
+                format!(
+                    "'cold_speed' ({}) is out of range '{}..{}'",
+                    cold_speed, FAN_SPEED_MIN, FAN_SPEED_MAX
+                ),
+            ));
+        }
+    }
+    if let Some(step) = config.fan_max_speed_step_per_tick {
+        if !(1..=FAN_SPEED_MAX).contains(&(step as usize)) {
+            errors.push(ConfigError::new(
+                "fan_max_speed_step_per_tick",
+                format!(
+                    "fan speed step {} is out of range '1..{}'",
+                    step, FAN_SPEED_MAX
+                ),
+            ));
+        }
+    }
+    if let Some(monitor::ShutdownFanMode::Cooldown { duration, .. }) = config.shutdown_fan_mode {
+        if duration > monitor::MAX_SHUTDOWN_COOLDOWN {
+            errors.push(ConfigError::new(
+                "shutdown_fan_mode",
+                format!(
+                    "cooldown duration {:?} is longer than {:?}",
+                    duration,
+                    monitor::MAX_SHUTDOWN_COOLDOWN
+                ),
+            ));
+        }
+    }
+    if config.fan_min_fans_ticks == Some(0) {
+        errors.push(ConfigError::new(
+            "fan_min_fans_ticks",
+            "number of ticks with missing fans must be at least 1",
+        ));
+    }
+    if let Some(min_fans) = fan_control.min_fans {
+        if !(FANS_MIN..=FANS_MAX).contains(&min_fans) {
+            errors.push(ConfigError::new(
+                "fan_control",
+                format!(
+                    "'min_fans' ({}) is out of range '{}..{}'",
+                    min_fans, FANS_MIN, FANS_MAX
+                ),
+            ));
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+/// Parse configuration file and validate it with `validate_config`
+///
+/// Format model and version of the file are checked as well.
+pub fn validate_config_file(config_path: &str) -> Result<(), Vec<ConfigError>> {
+    let config: FormatWrapper<Backend> = bosminer_config::parse(config_path)
+        .map_err(|message| vec![ConfigError::new("config", message)])?;
+
+    let mut errors = Vec::new();
+    if config.format.model != Backend::model() {
+        errors.push(ConfigError::new(
+            "format",
+            FormatWrapperError::<Backend>::IncompatibleFormat(config.format.model.clone()),
+        ));
+    }
+    if !Backend::version_is_supported(&config.format.version) {
+        errors.push(ConfigError::new(
+            "format",
+            FormatWrapperError::<Backend>::IncompatibleVersion(config.format.version.clone(), None),
+        ));
+    }
+    if let Err(mut body_errors) = validate_config(&config.body) {
+        errors.append(&mut body_errors);
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
 impl Backend {
     pub fn has_groups(&self) -> bool {
         self.groups.as_ref().map(|v| !v.is_empty()).unwrap_or(false)
@@ -354,7 +1371,10 @@ impl Backend {
         }
     }
 
-    pub fn resolve_chain_config(&self, hash_chain_idx: usize) -> ResolvedChainConfig {
+    pub fn resolve_chain_config(
+        &self,
+        hash_chain_idx: usize,
+    ) -> error::Result<ResolvedChainConfig> {
         // Take global hash chain configuration or default value
         let overridable = self
             .hash_chain_global
@@ -368,7 +1388,9 @@ impl Backend {
             overridable.as_ref().and_then(|v| v.voltage),
             DEFAULT_VOLTAGE_V,
         );
+        let mut chip_frequency = overridable.as_ref().and_then(|v| v.chip_frequency.as_ref());
         let mut enabled = DEFAULT_HASH_CHAIN_ENABLED;
+        let mut asic_boost = self.resolve_asic_boost();
 
         // If there's a per-chain override then apply it
         if let Some(hash_chain) = self
@@ -377,6 +1399,7 @@ impl Backend {
             .and_then(|m| m.get(&hash_chain_idx.to_string()))
         {
             enabled = hash_chain.enabled.unwrap_or(enabled);
+            asic_boost = hash_chain.asic_boost.unwrap_or(asic_boost);
             frequency = hash_chain
                 .frequency
                 .map(|v| OptionDefault::Some(v))
@@ -385,17 +1408,116 @@ impl Backend {
                 .voltage
                 .map(|v| OptionDefault::Some(v))
                 .unwrap_or(voltage);
+            chip_frequency = hash_chain.chip_frequency.as_ref().or(chip_frequency);
         }
+        if self.is_asic_boost_disabled() {
+            asic_boost = false;
+        }
+        let expected_chip_count = self.resolve_expected_chip_count();
+        let chip_frequency = match chip_frequency {
+            Some(chip_frequency) => parse_chip_frequency(chip_frequency, expected_chip_count)
+                .map_err(|e| {
+                    error::ErrorKind::General(format!(
+                        "hash chain {}: invalid chip frequency: {}",
+                        hash_chain_idx, e
+                    ))
+                })?,
+            None => vec![],
+        };
+
+        let voltage_bounds = self.resolve_voltage_bounds()?;
+        let enum_retry_policy = self.enum_retry_policy.unwrap_or_default();
+        let work_delay_fudge = self.work_delay_fudge.unwrap_or(utils::WORK_DELAY_FUDGE);
+        utils::validate_work_delay_fudge(work_delay_fudge).map_err(error::ErrorKind::General)?;
+        // TODO: handle config errors
+        let voltage =
+            power::Voltage::from_volts(*voltage as f32).expect("TODO: bad voltage requested");
+        voltage_bounds.check(voltage)?;
 
         // Computed s9-specific values
-        ResolvedChainConfig {
-            midstate_count: MidstateCount::new(self.midstate_count()),
-            frequency: FrequencySettings::from_frequency((*frequency * 1_000_000.0) as usize),
-            // TODO: handle config errors
-            voltage: power::Voltage::from_volts(*voltage as f32)
-                .expect("TODO: bad voltage requested"),
+        Ok(ResolvedChainConfig {
+            midstate_count: MidstateCount::try_new(asic_boost_midstate_count(asic_boost))?,
+            frequency: chip_frequency_settings(*frequency, &chip_frequency, expected_chip_count),
+            voltage,
             enabled,
-        }
+            core_address_layout: self.core_address_layout.unwrap_or_default(),
+            raw_solution_history: self.raw_solution_history.unwrap_or(0),
+            sensor_chip_address: self
+                .sensor_chip_address
+                .unwrap_or(DEFAULT_SENSOR_CHIP_ADDRESS),
+            max_enumeration_failures: self
+                .max_enumeration_failures
+                .unwrap_or(DEFAULT_MAX_ENUMERATION_FAILURES),
+            chip_revision_policy: self.chip_revision_policy.unwrap_or_default(),
+            enum_retry_policy,
+            short_chain_policy: self
+                .short_chain_policy
+                .unwrap_or_else(|| ShortChainPolicy::default_for(&enum_retry_policy)),
+            heart_beat_period: self.heart_beat_period,
+            init_work_multiplier: self
+                .init_work_multiplier
+                .unwrap_or(DEFAULT_INIT_WORK_MULTIPLIER),
+            voltage_ramp: self.voltage_ramp,
+            voltage_bounds,
+            preheat: self.preheat.unwrap_or(DEFAULT_PREHEAT),
+            preheat_timeout: self.preheat_timeout.unwrap_or(DEFAULT_PREHEAT_TIMEOUT),
+            preheat_temp_epsilon: self
+                .preheat_temp_epsilon
+                .unwrap_or(DEFAULT_PREHEAT_TEMP_EPSILON_C),
+            fpga_clock_frequency: self.fpga_clock_frequency.unwrap_or(io::F_CLK_SPEED_HZ),
+            init_delay: self.init_delay.unwrap_or(DEFAULT_INIT_DELAY),
+            expected_chip_count,
+            sensor_config: self.sensor_config.unwrap_or_default(),
+            temp_update_interval: self.resolve_monitor_timeouts().temp_update_interval,
+            pll_freq_error_tolerance: self.resolve_pll_freq_error_tolerance(),
+            work_delay_fudge,
+        })
+    }
+
+    /// Monitor timeouts are shared by monitor and hashchains (which have to send updates
+    /// often enough)
+    pub fn resolve_monitor_timeouts(&self) -> monitor::Timeouts {
+        self.monitor_timeouts.clone().unwrap_or_default()
+    }
+
+    /// Global AsicBoost setting (hash chains can override it)
+    pub fn resolve_asic_boost(&self) -> bool {
+        self.hash_chain_global
+            .as_ref()
+            .and_then(|v| v.overridable.as_ref())
+            .and_then(|v| v.asic_boost)
+            .unwrap_or(DEFAULT_ASIC_BOOST)
+    }
+
+    /// AsicBoost has been disabled for all hash chains (e.g. from command line)
+    fn is_asic_boost_disabled(&self) -> bool {
+        self.disable_asic_boost.unwrap_or(false)
+    }
+
+    /// Working voltage range, defaults to the range allowed in configuration
+    pub fn resolve_voltage_bounds(&self) -> error::Result<power::VoltageBounds> {
+        let min = self.min_voltage.unwrap_or(VOLTAGE_V_MIN);
+        let max = self.max_voltage.unwrap_or(VOLTAGE_V_MAX);
+        power::VoltageBounds::new(
+            power::Voltage::from_volts(min as f32)?,
+            power::Voltage::from_volts(max as f32)?,
+        )
+    }
+
+    pub fn resolve_pll_freq_error_tolerance(&self) -> usize {
+        self.pll_freq_error_tolerance
+            .unwrap_or(bm1387::PllFrequency::MAX_FREQ_ERROR_HZ)
+    }
+
+    pub fn resolve_expected_chip_count(&self) -> usize {
+        self.expected_chip_count
+            .unwrap_or(DEFAULT_EXPECTED_CHIPS_ON_CHAIN)
+    }
+
+    pub fn resolve_hashboard_slots(&self) -> Vec<usize> {
+        self.hashboard_slots
+            .clone()
+            .unwrap_or_else(|| DEFAULT_HASHBOARD_SLOTS.to_vec())
     }
 
     pub fn resolve_monitor_config(&self) -> monitor::Config {
@@ -416,6 +1538,16 @@ impl Backend {
             self.temp_control.as_ref().and_then(|v| v.dangerous_temp),
             DEFAULT_DANGEROUS_TEMP_C,
         );
+        let hot_temp_hysteresis = OptionDefault::new(
+            self.temp_control
+                .as_ref()
+                .and_then(|v| v.hot_temp_hysteresis),
+            DEFAULT_HOT_TEMP_HYSTERESIS_C,
+        );
+        let cold_temp = OptionDefault::new(
+            self.temp_control.as_ref().and_then(|v| v.cold_temp),
+            DEFAULT_COLD_TEMP_C,
+        );
 
         // Get fan control settings
         let fan_speed = OptionDefault::new(
@@ -426,8 +1558,12 @@ impl Backend {
             self.fan_control.as_ref().and_then(|v| v.min_fans),
             DEFAULT_MIN_FANS,
         );
+        let cold_fan_speed = OptionDefault::new(
+            self.fan_control.as_ref().and_then(|v| v.cold_speed),
+            DEFAULT_COLD_FAN_SPEED,
+        );
 
-        let temp_config;
+        let mut temp_config;
         let fan_config;
 
         // Configure temperature controller
@@ -436,6 +1572,8 @@ impl Backend {
                 temp_config = Some(monitor::TempControlConfig {
                     dangerous_temp: *dangerous_temp as f32,
                     hot_temp: *hot_temp as f32,
+                    hot_temp_hysteresis: *hot_temp_hysteresis as f32,
+                    cold_temp: *cold_temp as f32,
                 });
             }
             TempControlMode::Disabled => {
@@ -460,8 +1598,17 @@ impl Backend {
         match *mode {
             TempControlMode::Auto => {
                 fan_config = Some(monitor::FanControlConfig {
-                    mode: monitor::FanControlMode::TargetTemperature(*target_temp as f32),
+                    // PID mode takes over temperature control configuration
+                    mode: monitor::FanControlMode::TargetTemperature {
+                        target_temp: *target_temp as f32,
+                        temp_config: temp_config
+                            .take()
+                            .expect("BUG: temperature control missing in 'auto' mode"),
+                    },
                     min_fans: *min_fans,
+                    cold_fan_speed: fan::Speed::new(*cold_fan_speed),
+                    max_speed_step_per_tick: self.fan_max_speed_step_per_tick,
+                    min_fans_ticks: self.fan_min_fans_ticks.unwrap_or(DEFAULT_MIN_FANS_TICKS),
                 });
                 // do sanity checks
                 if fan_speed.is_some() {
@@ -479,6 +1626,9 @@ impl Backend {
                     Some(monitor::FanControlConfig {
                         mode: monitor::FanControlMode::FixedSpeed(fan::Speed::new(*fan_speed)),
                         min_fans: *min_fans,
+                        cold_fan_speed: fan::Speed::new(*cold_fan_speed),
+                        max_speed_step_per_tick: self.fan_max_speed_step_per_tick,
+                        min_fans_ticks: self.fan_min_fans_ticks.unwrap_or(DEFAULT_MIN_FANS_TICKS),
                     })
                 };
                 // do sanity checks
@@ -491,11 +1641,25 @@ impl Backend {
             }
         };
 
-        monitor::Config {
+        let sensor_config = monitor::TempSensorConfig {
+            preference: self
+                .temp_sensor_preference
+                .unwrap_or(DEFAULT_TEMP_SENSOR_PREFERENCE),
+            disagreement_threshold: self
+                .temp_sensor_disagreement
+                .unwrap_or(DEFAULT_TEMP_SENSOR_DISAGREEMENT_C),
+        };
+        let mut config = monitor::Config::new(
             temp_config,
             fan_config,
-            fans_on_while_warming_up: self.fans_on_while_warming_up.unwrap_or(true),
-        }
+            sensor_config,
+            self.resolve_monitor_timeouts(),
+        );
+        config.fans_on_while_warming_up = self.fans_on_while_warming_up.unwrap_or(true);
+        config.isolate_failed_chains = self.isolate_failed_chains.unwrap_or(false);
+        config.shutdown_fan_mode = self.shutdown_fan_mode.unwrap_or(DEFAULT_SHUTDOWN_FAN_MODE);
+        config.power_config = self.power_limit.clone();
+        config
     }
 
     pub fn fill_info<T>(&mut self) -> Result<(), std::io::Error>
@@ -542,6 +1706,18 @@ impl ConfigBody for Backend {
             }
         }
 
+        // Check that hashboard slots exist on this control board
+        if let Some(hashboard_slots) = &self.hashboard_slots {
+            for &slot in hashboard_slots {
+                if !(HASHBOARD_SLOT_MIN..=HASHBOARD_SLOT_MAX).contains(&slot) {
+                    Err(format!(
+                        "hashboard slot '{}' is out of range '{}..{}'",
+                        slot, HASHBOARD_SLOT_MIN, HASHBOARD_SLOT_MAX
+                    ))?;
+                }
+            }
+        }
+
         // Analyze group configuration, make sure the groups are unique, and build descriptor
         // topology out of the configuration data
         // Don't worry if is this section missing, maybe there are some pools on command line
@@ -553,11 +1729,15 @@ impl ConfigBody for Backend {
                 }
                 if let Some(pools) = &group.pools {
                     for pool in pools {
-                        let _ = ClientDescriptor::create(
+                        ClientDescriptor::create(
                             pool.url.as_str(),
                             &ClientUserInfo::new(pool.user.as_str(), pool.password.as_deref()),
                             pool.enabled.unwrap_or(DEFAULT_POOL_ENABLED),
                         )
+                        .and_then(|descriptor| {
+                            descriptor.check_insecure(pool.allow_insecure.unwrap_or(false))?;
+                            descriptor.with_connection(pool.connection.clone().unwrap_or_default())
+                        })
                         .map_err(|e| {
                             format!("{} in pool '{}@{}'", e.to_string(), pool.url, pool.user)
                         })?;
@@ -579,18 +1759,23 @@ impl ConfigBody for Backend {
 }
 
 impl hal::BackendConfig for Backend {
+    /// Work is generated with the biggest midstate count of all hash chains, chains with less
+    /// midstates use just the first ones
     #[inline]
     fn midstate_count(&self) -> usize {
-        if self
-            .hash_chain_global
-            .as_ref()
-            .and_then(|v| v.asic_boost)
-            .unwrap_or(DEFAULT_ASIC_BOOST)
-        {
-            ASIC_BOOST_MIDSTATE_COUNT
-        } else {
-            1
+        if self.is_asic_boost_disabled() {
+            return asic_boost_midstate_count(false);
         }
+        let per_chain_asic_boost = self
+            .hash_chains
+            .iter()
+            .flat_map(|hash_chains| hash_chains.values())
+            .filter_map(|hash_chain| hash_chain.asic_boost);
+        std::iter::once(self.resolve_asic_boost())
+            .chain(per_chain_asic_boost)
+            .map(asic_boost_midstate_count)
+            .max()
+            .expect("BUG: no midstate count")
     }
 
     fn set_client_manager(&mut self, client_manager: client::Manager) {
@@ -243,6 +243,14 @@ pub fn for_backend() -> serde_json::Value {
                                 "span": 1
                             }
                         ],
+                        [
+                            "asic_boost",
+                            {
+                                "type": "bool",
+                                "label": "AsicBoost",
+                                "default": ["$get", "hash_chain_global", "asic_boost"]
+                            }
+                        ],
                         [
                             "frequency",
                             {
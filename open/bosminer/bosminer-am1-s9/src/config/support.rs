@@ -65,3 +65,80 @@ impl<T> Deref for OptionDefault<T> {
         }
     }
 }
+
+/// (De)serialize `Duration` as number of seconds (fractions are allowed, e.g. `timeout = 0.5`)
+pub mod duration_secs {
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    use std::time::Duration;
+
+    pub fn serialize<S: Serializer>(value: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_f64(value.as_secs_f64())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Duration, D::Error> {
+        let secs = f64::deserialize(deserializer)?;
+        if !(secs.is_finite() && secs >= 0.0) {
+            return Err(D::Error::custom(format!(
+                "duration {} s is not a non-negative number",
+                secs
+            )));
+        }
+        Ok(Duration::from_secs_f64(secs))
+    }
+}
+
+/// Same as `duration_secs` for optional values
+pub mod option_duration_secs {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    use std::time::Duration;
+
+    #[derive(Deserialize)]
+    struct Wrapper(#[serde(with = "super::duration_secs")] Duration);
+
+    pub fn serialize<S: Serializer>(
+        value: &Option<Duration>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match value {
+            Some(value) => super::duration_secs::serialize(value, serializer),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Duration>, D::Error> {
+        Ok(Option::<Wrapper>::deserialize(deserializer)?.map(|Wrapper(value)| value))
+    }
+}
+
+/// (De)serialize optional `Duration` as whole number of milliseconds (for short periods)
+pub mod option_duration_millis {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    use std::convert::TryInto;
+    use std::time::Duration;
+
+    pub fn serialize<S: Serializer>(
+        value: &Option<Duration>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match value {
+            Some(value) => serializer.serialize_u64(
+                value
+                    .as_millis()
+                    .try_into()
+                    .expect("BUG: duration does not fit into milliseconds"),
+            ),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Duration>, D::Error> {
+        Ok(Option::<u64>::deserialize(deserializer)?.map(Duration::from_millis))
+    }
+}
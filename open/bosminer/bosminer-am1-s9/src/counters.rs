@@ -33,6 +33,9 @@ use std::time::{Duration, Instant};
 pub struct Core {
     pub valid: usize,
     pub errors: usize,
+    /// When the core produced last valid solution (`None` if it never did).
+    /// It's kept across `reset` so that cores that went silent can be detected.
+    pub last_valid: Option<Instant>,
 }
 
 impl Core {
@@ -45,23 +48,40 @@ impl Core {
         Self {
             valid: 0,
             errors: 0,
+            last_valid: None,
         }
     }
+
+    /// Subtract counters of `earlier` snapshot, `last_valid` is kept from `self`
+    pub fn diff(&self, earlier: &Self) -> Self {
+        Self {
+            valid: self.valid.saturating_sub(earlier.valid),
+            errors: self.errors.saturating_sub(earlier.errors),
+            last_valid: self.last_valid,
+        }
+    }
+
+    /// Core has produced valid solution in the past but not in the last `threshold`
+    pub fn is_silent(&self, threshold: Duration, now: Instant) -> bool {
+        self.last_valid
+            .map(|last_valid| now.duration_since(last_valid) > threshold)
+            .unwrap_or(false)
+    }
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone)]
 pub struct Chip {
-    pub core: [Core; super::CORE_ADR_SPACE_SIZE],
+    pub core: Vec<Core>,
     pub valid: usize,
     pub errors: usize,
 }
 
 impl Chip {
-    pub fn new() -> Self {
+    pub fn new(core_space_size: usize) -> Self {
         Self {
             valid: 0,
             errors: 0,
-            core: [Core::new(); super::CORE_ADR_SPACE_SIZE],
+            core: vec![Core::new(); core_space_size],
         }
     }
 
@@ -72,6 +92,23 @@ impl Chip {
             core.reset();
         }
     }
+
+    /// Subtract counters of `earlier` snapshot (cores missing in `earlier` are kept as they are)
+    pub fn diff(&self, earlier: &Self) -> Self {
+        Self {
+            valid: self.valid.saturating_sub(earlier.valid),
+            errors: self.errors.saturating_sub(earlier.errors),
+            core: self
+                .core
+                .iter()
+                .enumerate()
+                .map(|(idx, core)| match earlier.core.get(idx) {
+                    Some(earlier_core) => core.diff(earlier_core),
+                    None => *core,
+                })
+                .collect(),
+        }
+    }
 }
 
 #[derive(Clone)]
@@ -79,26 +116,54 @@ pub struct HashChain {
     pub chip: Vec<Chip>,
     pub valid: usize,
     pub errors: usize,
+    /// Valid solutions that were not submitted because solution submission was paused
+    pub dropped: usize,
+    /// Solutions with core address outside of expected address space (non-existent chip or
+    /// core). Non-zero value indicates wrong `CoreAddressLayout`.
+    pub out_of_space: usize,
+    /// Solutions that were already received for the same work (also counted in `errors`)
+    pub duplicate: usize,
+    /// Solutions whose nonce didn't match the work midstates (also counted in `errors`)
+    pub mismatched_nonce: usize,
+    /// Solutions referring to work that is not present in work registry (they can't be
+    /// attributed to any core)
+    pub orphaned: usize,
     pub started: Instant,
     pub stopped: Option<Instant>,
     pub asic_difficulty: usize,
+    pub core_address_layout: bm1387::CoreAddressLayout,
 }
 
 impl HashChain {
-    pub fn new(chip_count: usize, asic_difficulty: usize) -> Self {
+    pub fn new(
+        chip_count: usize,
+        asic_difficulty: usize,
+        core_address_layout: bm1387::CoreAddressLayout,
+    ) -> Self {
         Self {
             valid: 0,
             errors: 0,
+            dropped: 0,
+            out_of_space: 0,
+            duplicate: 0,
+            mismatched_nonce: 0,
+            orphaned: 0,
             started: Instant::now(),
             stopped: None,
-            chip: vec![Chip::new(); chip_count],
+            chip: vec![Chip::new(core_address_layout.core_space_size); chip_count],
             asic_difficulty,
+            core_address_layout,
         }
     }
 
     pub fn reset(&mut self) {
         self.valid = 0;
         self.errors = 0;
+        self.dropped = 0;
+        self.out_of_space = 0;
+        self.duplicate = 0;
+        self.mismatched_nonce = 0;
+        self.orphaned = 0;
         for chip in self.chip.iter_mut() {
             chip.reset();
         }
@@ -114,27 +179,67 @@ impl HashChain {
         snapshot
     }
 
+    /// Return counters accumulated since `earlier` snapshot was taken.
+    /// This allows computing per-interval statistics without resetting the counters. The
+    /// resulting interval starts at the time `earlier` was taken (see `snapshot`) and chips
+    /// missing in `earlier` are kept as they are. Counters are saturated at zero in case they
+    /// were reset in between.
+    pub fn diff(&self, earlier: &Self) -> Self {
+        Self {
+            chip: self
+                .chip
+                .iter()
+                .enumerate()
+                .map(|(idx, chip)| match earlier.chip.get(idx) {
+                    Some(earlier_chip) => chip.diff(earlier_chip),
+                    None => chip.clone(),
+                })
+                .collect(),
+            valid: self.valid.saturating_sub(earlier.valid),
+            errors: self.errors.saturating_sub(earlier.errors),
+            dropped: self.dropped.saturating_sub(earlier.dropped),
+            out_of_space: self.out_of_space.saturating_sub(earlier.out_of_space),
+            duplicate: self.duplicate.saturating_sub(earlier.duplicate),
+            mismatched_nonce: self
+                .mismatched_nonce
+                .saturating_sub(earlier.mismatched_nonce),
+            orphaned: self.orphaned.saturating_sub(earlier.orphaned),
+            started: earlier.stopped.unwrap_or(earlier.started).max(self.started),
+            stopped: self.stopped,
+            asic_difficulty: self.asic_difficulty,
+            core_address_layout: self.core_address_layout,
+        }
+    }
+
     pub fn duration(&self) -> Duration {
         self.stopped
             .unwrap_or_else(|| Instant::now())
             .duration_since(self.started)
     }
 
+    /// Check that `addr` belongs to existing core, account it as out of space otherwise
+    fn check_address(&mut self, addr: bm1387::CoreAddress) -> bool {
+        if addr.chip >= self.chip.len() || !self.core_address_layout.contains(addr) {
+            // nonce from non-existent chip or core
+            self.out_of_space += 1;
+            return false;
+        }
+        true
+    }
+
     pub fn add_valid(&mut self, addr: bm1387::CoreAddress) {
-        if addr.chip >= self.chip.len() {
-            // nonce from non-existent chip
-            // TODO: what to do?
+        if !self.check_address(addr) {
             return;
         }
         self.valid += self.asic_difficulty;
         self.chip[addr.chip].valid += self.asic_difficulty;
-        self.chip[addr.chip].core[addr.core].valid += self.asic_difficulty;
+        let core = &mut self.chip[addr.chip].core[addr.core];
+        core.valid += self.asic_difficulty;
+        core.last_valid = Some(Instant::now());
     }
 
     pub fn add_error(&mut self, addr: bm1387::CoreAddress) {
-        if addr.chip >= self.chip.len() {
-            // nonce from non-existent chip
-            // TODO: what to do?
+        if !self.check_address(addr) {
             return;
         }
         self.errors += 1;
@@ -142,11 +247,85 @@ impl HashChain {
         self.chip[addr.chip].core[addr.core].errors += 1;
     }
 
+    pub fn add_duplicate(&mut self, addr: bm1387::CoreAddress) {
+        self.duplicate += 1;
+        self.add_error(addr);
+    }
+
+    pub fn add_mismatched_nonce(&mut self, addr: bm1387::CoreAddress) {
+        self.mismatched_nonce += 1;
+        self.add_error(addr);
+    }
+
+    pub fn add_orphaned(&mut self) {
+        self.orphaned += 1;
+    }
+
+    pub fn add_dropped(&mut self) {
+        self.dropped += 1;
+    }
+
     pub fn set_chip_count(&mut self, chip_count: usize) {
-        self.chip.resize(chip_count, Chip::new());
+        self.chip.resize(
+            chip_count,
+            Chip::new(self.core_address_layout.core_space_size),
+        );
     }
 
     pub fn chip_count(&self) -> usize {
         self.chip.len()
     }
+
+    /// List cores that used to produce valid solutions, but haven't produced any in longer
+    /// than `threshold`. Snapshots are evaluated at the time they were taken.
+    pub fn silent_cores(&self, threshold: Duration) -> Vec<bm1387::CoreAddress> {
+        let now = self.stopped.unwrap_or_else(|| Instant::now());
+        let mut silent = Vec::new();
+        for (chip_idx, chip) in self.chip.iter().enumerate() {
+            for (core_idx, core) in chip.core.iter().enumerate() {
+                if core.is_silent(threshold, now) {
+                    silent.push(bm1387::CoreAddress {
+                        chip: chip_idx,
+                        core: core_idx,
+                    });
+                }
+            }
+        }
+        silent
+    }
+
+    /// Estimate hashrate (in hashes per second) realized over `elapsed` from valid solutions.
+    /// Each solution at `asic_difficulty` represents `asic_difficulty * 2^32` hashes on
+    /// average (`valid` is already in shares, so it's just `valid * 2^32`).
+    /// Returns 0 for zero `elapsed`.
+    pub fn estimated_hashrate(&self, elapsed: Duration) -> f64 {
+        let secs = elapsed.as_secs_f64();
+        if secs == 0.0 {
+            return 0.0;
+        }
+        self.valid as f64 * (1u64 << 32) as f64 / secs
+    }
+
+    /// Error ratio (errors per solution) of each core. Cores that haven't produced any
+    /// solution nor error are omitted.
+    pub fn core_error_rates(&self) -> Vec<(bm1387::CoreAddress, f64)> {
+        let mut rates = Vec::new();
+        for (chip_idx, chip) in self.chip.iter().enumerate() {
+            for (core_idx, core) in chip.core.iter().enumerate() {
+                // `valid` is in shares, convert it to solutions
+                let samples = core.valid / self.asic_difficulty + core.errors;
+                if samples == 0 {
+                    continue;
+                }
+                rates.push((
+                    bm1387::CoreAddress {
+                        chip: chip_idx,
+                        core: core_idx,
+                    },
+                    core.errors as f64 / samples as f64,
+                ));
+            }
+        }
+        rates
+    }
 }
@@ -106,6 +106,10 @@ pub enum ErrorKind {
     /// Error when dealing with sensors.
     #[fail(display = "Sensors: {}", _0)]
     Sensors(String),
+
+    /// Request that has not been served in time.
+    #[fail(display = "Timeout: {}", _0)]
+    Timeout(String),
 }
 
 #[derive(Clone, Eq, PartialEq, Debug, Fail)]
@@ -27,10 +27,15 @@ pub mod pid;
 use crate::error::{self, ErrorKind};
 use failure::ResultExt;
 
+use serde::{Deserialize, Serialize};
+
 use uio_async;
 
+use std::convert::TryFrom;
+
 /// Structure representing PWM of fan
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(try_from = "usize", into = "usize")]
 pub struct Speed(usize);
 
 impl Speed {
@@ -48,8 +53,26 @@ impl Speed {
     }
 }
 
+impl TryFrom<usize> for Speed {
+    type Error = String;
+
+    fn try_from(speed: usize) -> Result<Self, Self::Error> {
+        if speed <= Self::FULL_SPEED.0 {
+            Ok(Speed(speed))
+        } else {
+            Err(format!("fan speed {} is out of range '0..100'", speed))
+        }
+    }
+}
+
+impl From<Speed> for usize {
+    fn from(speed: Speed) -> Self {
+        speed.0
+    }
+}
+
 /// Speed of fans read from feedback pins
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct Feedback {
     pub rpm: Vec<usize>,
 }
@@ -58,6 +58,17 @@ impl TempControl {
         self.pid.set_limits(1.0, 100.0);
     }
 
+    /// Retune PID. Accumulated integral term is reset so that new gains don't cause a spike.
+    pub fn set_pid_params(&mut self, kp: f64, ki: f64, kd: f64) {
+        self.pid.set_gains(kp, ki, kd);
+        self.pid.reset();
+    }
+
+    /// Return `(kp, ki, kd)`
+    pub fn pid_params(&self) -> (f64, f64, f64) {
+        self.pid.gains()
+    }
+
     pub fn set_target(&mut self, target: f64) {
         self.pid.set_target(target);
     }
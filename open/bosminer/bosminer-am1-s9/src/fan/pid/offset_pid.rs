@@ -41,6 +41,17 @@ impl OffsetPIDController {
     pub fn set_limits(&mut self, min: f64, max: f64) {
         self.pid.set_limits(min - self.offset, max - self.offset);
     }
+
+    pub fn set_gains(&mut self, p_gain: f64, i_gain: f64, d_gain: f64) {
+        self.pid.p_gain = p_gain;
+        self.pid.i_gain = i_gain;
+        self.pid.d_gain = d_gain;
+    }
+
+    /// Return `(p_gain, i_gain, d_gain)`
+    pub fn gains(&self) -> (f64, f64, f64) {
+        (self.pid.p_gain, self.pid.i_gain, self.pid.d_gain)
+    }
 }
 
 impl Controller for OffsetPIDController {
@@ -74,4 +85,14 @@ mod test {
         pid.set_limits(60.0, 60.0);
         assert_relative_eq!(pid.update(0.0, 1.0), 60.0);
     }
+
+    /// Verify that gains can be changed
+    #[test]
+    fn test_pid_gains() {
+        let mut pid = OffsetPIDController::new(0.0, 0.0, 0.0, 50.0);
+        pid.set_gains(-5.0, -0.03, -0.15);
+        assert_eq!(pid.gains(), (-5.0, -0.03, -0.15));
+        pid.set_target(10.0);
+        assert_relative_eq!(pid.update(10.0, 1.0), 50.0);
+    }
 }
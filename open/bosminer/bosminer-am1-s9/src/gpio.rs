@@ -39,10 +39,26 @@ impl embedded_hal::digital::v2::OutputPin for PinOut {
     }
 }
 
+#[cfg(test)]
+impl PinOut {
+    /// Create pin without exporting it (for tests that never access the pin)
+    pub fn unexported(pin_num: u64) -> Self {
+        PinOut(sysfs_gpio::Pin::new(pin_num))
+    }
+}
+
 /// Helper struct for reading input pins which implements InputPin trait
 #[derive(Clone)]
 pub struct PinIn(sysfs_gpio::Pin);
 
+#[cfg(test)]
+impl PinIn {
+    /// Create pin without exporting it (for tests that never access the pin)
+    pub fn unexported(pin_num: u64) -> Self {
+        PinIn(sysfs_gpio::Pin::new(pin_num))
+    }
+}
+
 impl embedded_hal::digital::v2::InputPin for PinIn {
     type Error = sysfs_gpio::Error;
 
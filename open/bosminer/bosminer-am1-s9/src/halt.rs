@@ -40,6 +40,7 @@ use futures::channel::mpsc;
 use futures::future::{select, Either};
 use futures::lock::Mutex;
 use ii_async_compat::prelude::*;
+use ii_logging::macros::*;
 use tokio::signal::unix::{signal, SignalKind};
 use tokio::task;
 
@@ -170,9 +171,13 @@ impl Receiver {
 /// One halt context capable of notifying all of registered `clients`
 pub struct Sender {
     clients: Mutex<Vec<NotifySender>>,
+    /// Names of clients that have been sent halt but haven't confirmed it yet (in halt order)
+    halting_clients: Mutex<Vec<String>>,
     exit_hooks: Mutex<Vec<Pin<Box<dyn Future<Output = ()> + 'static + Send>>>>,
     /// How long to wait for client to finish
     halt_timeout: Duration,
+    /// Reason of the last halt (if it was given)
+    last_reason: Mutex<Option<String>>,
 }
 
 impl Sender {
@@ -180,8 +185,10 @@ impl Sender {
     fn new(halt_timeout: Duration) -> Arc<Self> {
         Arc::new(Self {
             clients: Mutex::new(Vec::new()),
+            halting_clients: Mutex::new(Vec::new()),
             halt_timeout,
             exit_hooks: Mutex::new(Vec::new()),
+            last_reason: Mutex::new(None),
         })
     }
 
@@ -192,6 +199,22 @@ impl Sender {
         notify_receiver
     }
 
+    /// List names of clients that are registered and haven't been halted yet
+    pub async fn registered_clients(&self) -> Vec<String> {
+        self.clients
+            .lock()
+            .await
+            .iter()
+            .map(|client| client.name.clone())
+            .collect()
+    }
+
+    /// List names of clients that are being halted, but haven't acknowledged the halt yet
+    /// (useful for finding out which task is stuck when miner won't shut down)
+    pub async fn unacknowledged_clients(&self) -> Vec<String> {
+        self.halting_clients.lock().await.clone()
+    }
+
     /// Register hook that is to be executed after all futures terminated
     pub async fn add_exit_hook<F>(&self, f: F)
     where
@@ -208,29 +231,31 @@ impl Sender {
     async fn send_halt_internal(self: Arc<Self>) -> error::Result<()> {
         // take the list of clients
         let mut clients: Vec<_> = self.clients.lock().await.drain(..).collect();
+        *self.halting_clients.lock().await =
+            clients.iter().map(|client| client.name.clone()).collect();
 
         // notify clients one-by-one
         for client in clients.drain(..) {
             // try to halt them
-            let mut done_wait = match client.send_halt() {
-                // client has already ended
-                None => continue,
-                // extract handle, wait on it later
-                Some(handle) => handle,
-            };
-            match done_wait.done_rx.next().timeout(self.halt_timeout).await {
-                Ok(confirm) => match confirm {
-                    Some(_) => (),
-                    None => Err(ErrorKind::Halt(format!(
-                        "failed to halt client {}: dropped handle",
-                        client.name
+            if let Some(mut done_wait) = client.send_halt() {
+                match done_wait.done_rx.next().timeout(self.halt_timeout).await {
+                    Ok(confirm) => match confirm {
+                        Some(_) => (),
+                        None => Err(ErrorKind::Halt(format!(
+                            "failed to halt client {}: dropped handle (unacknowledged: {:?})",
+                            client.name,
+                            self.unacknowledged_clients().await
+                        )))?,
+                    },
+                    Err(_) => Err(ErrorKind::Halt(format!(
+                        "failed to halt client {}: timeout (unacknowledged: {:?})",
+                        client.name,
+                        self.unacknowledged_clients().await
                     )))?,
-                },
-                Err(_) => Err(ErrorKind::Halt(format!(
-                    "failed to halt client {}: timeout",
-                    client.name
-                )))?,
+                }
             }
+            // client has confirmed halt or it has already ended
+            self.halting_clients.lock().await.remove(0);
         }
 
         // run exit hooks (in order they came in)
@@ -244,10 +269,10 @@ impl Sender {
     /// additional threads.
     pub fn hook_termination_signals(self: Arc<Self>) {
         // Hook `SIGINT`, `SIGHUP` and `SIGTERM`
-        for signal_type in vec![
-            SignalKind::interrupt(),
-            SignalKind::hangup(),
-            SignalKind::terminate(),
+        for (signal_type, signal_name) in vec![
+            (SignalKind::interrupt(), "SIGINT"),
+            (SignalKind::hangup(), "SIGHUP"),
+            (SignalKind::terminate(), "SIGTERM"),
         ] {
             let halt_sender = self.clone();
             tokio::spawn(async move {
@@ -257,7 +282,9 @@ impl Sender {
                     .await
                 {
                     // Exit after receiving signal
-                    halt_sender.send_halt().await;
+                    halt_sender
+                        .send_halt_with_reason(format!("received {}", signal_name))
+                        .await;
                 }
             });
         }
@@ -276,6 +303,18 @@ impl Sender {
             .expect("halt task has panicked")
             .expect("halt failed");
     }
+
+    /// Same as `send_halt`, but remember why the halt was issued (see `last_halt_reason`)
+    pub async fn send_halt_with_reason(self: Arc<Self>, reason: String) {
+        info!("Halt requested: {}", reason);
+        self.last_reason.lock().await.replace(reason);
+        self.send_halt().await
+    }
+
+    /// Reason given to the last `send_halt_with_reason`
+    pub async fn last_halt_reason(&self) -> Option<String> {
+        self.last_reason.lock().await.clone()
+    }
 }
 
 /// Build a halt sender/receiver pair
@@ -331,6 +370,34 @@ mod test {
         sender.send_halt().await;
     }
 
+    // Test that registered clients are listed until they acknowledge halt
+    #[tokio::test]
+    async fn test_halt_clients() {
+        let (sender, receiver) = make_pair(Duration::from_millis(50));
+        let notify_receiver1 = receiver.register_client("task1".into()).await;
+        let notify_receiver2 = receiver.register_client("task2".into()).await;
+        assert_eq!(
+            sender.registered_clients().await,
+            vec!["task1".to_string(), "task2".to_string()]
+        );
+        assert!(sender.unacknowledged_clients().await.is_empty());
+
+        // Task 1 checks it's being halted and task 2 still waits for the halt
+        let checking_sender = sender.clone();
+        notify_receiver1.spawn_halt_handler(async move {
+            assert!(checking_sender.registered_clients().await.is_empty());
+            assert_eq!(
+                checking_sender.unacknowledged_clients().await,
+                vec!["task1".to_string(), "task2".to_string()]
+            );
+        });
+        notify_receiver2.spawn_halt_handler(async {});
+
+        sender.clone().send_halt().await;
+        assert!(sender.registered_clients().await.is_empty());
+        assert!(sender.unacknowledged_clients().await.is_empty());
+    }
+
     // Test that `wait_for_halt` works
     #[tokio::test]
     async fn test_halt_done() {
@@ -347,6 +414,23 @@ mod test {
         sender.send_halt().await;
     }
 
+    // Test that halt reason is recorded
+    #[tokio::test]
+    async fn test_halt_reason() {
+        let (sender, receiver) = make_pair(Duration::from_millis(10));
+        receiver
+            .register_client("test".into())
+            .await
+            .spawn_halt_handler(async {});
+        assert_eq!(sender.last_halt_reason().await, None);
+
+        sender
+            .clone()
+            .send_halt_with_reason("overheating".into())
+            .await;
+        assert_eq!(sender.last_halt_reason().await, Some("overheating".into()));
+    }
+
     // Test that spawning task in termination context works
     #[tokio::test]
     async fn test_halt_spawn() {
@@ -22,6 +22,7 @@
 
 use crate::halt;
 use crate::monitor;
+use crate::power;
 use crate::Manager;
 
 use bosminer::client;
@@ -45,6 +46,12 @@ pub trait Hooks: Send + Sync + Debug {
     ) {
     }
 
+    /// Called before `Monitor` is started to get source of hashboard power readings. Power is
+    /// not measured (and power limit is not enforced) without it.
+    fn power_meter(&self) -> Option<Arc<dyn power::PowerMeter>> {
+        None
+    }
+
     /// Called when `Monitor` has been started
     async fn monitor_started(&self, _monitor: Arc<monitor::Monitor>) {}
 
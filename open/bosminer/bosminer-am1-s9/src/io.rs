@@ -59,6 +59,9 @@ const EXPECTED_S9IO_VERSION: Version = Version {
     patch: 0,
 };
 
+/// Midstate counts that can be set in `CTRL_REG` of the expected s9-io version
+const S9IO_SUPPORTED_MIDSTATE_COUNTS: [usize; 3] = [1, 2, 4];
+
 /// Base clock speed of the IP core running in the FPGA
 pub const F_CLK_SPEED_HZ: usize = 50_000_000;
 /// Divisor of the base clock. The resulting clock is connected to UART
@@ -102,6 +105,18 @@ struct Version {
     patch: usize,
 }
 
+impl Version {
+    /// Return midstate counts that IP core of this version is able to handle
+    fn supported_midstate_counts(&self) -> &'static [usize] {
+        if *self == EXPECTED_S9IO_VERSION {
+            &S9IO_SUPPORTED_MIDSTATE_COUNTS
+        } else {
+            // single midstate is the only configuration every bitstream handles
+            &[1]
+        }
+    }
+}
+
 impl fmt::Display for Version {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let model;
@@ -180,6 +195,20 @@ impl WorkRxFifo {
     }
 }
 
+/// Fill level of work TX FIFO
+///
+/// FPGA doesn't report the exact number of used words, only whether the FIFO is empty, full
+/// or above threshold that leaves room for one more work.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum WorkTxLevel {
+    Empty,
+    /// There's room for at least one more work
+    HasRoom,
+    /// FIFO is above threshold, next work has to wait
+    NoRoom,
+    Full,
+}
+
 struct WorkTxFifo {
     regs: uio_async::UioTypedMapping<ii_fpga_io_am1_s9::worktx::RegisterBlock>,
     uio: uio_async::UioDevice,
@@ -201,11 +230,29 @@ impl WorkTxFifo {
         self.regs.work_tx_stat_reg.read().tx_full().bit()
     }
 
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.regs.work_tx_stat_reg.read().tx_empty().bit()
+    }
+
     #[inline]
     pub fn has_space_for_one_job(&self) -> bool {
         self.regs.work_tx_stat_reg.read().irq_pend().bit()
     }
 
+    pub fn level(&self) -> WorkTxLevel {
+        let stat = self.regs.work_tx_stat_reg.read();
+        if stat.tx_empty().bit() {
+            WorkTxLevel::Empty
+        } else if stat.tx_full().bit() {
+            WorkTxLevel::Full
+        } else if stat.irq_pend().bit() {
+            WorkTxLevel::HasRoom
+        } else {
+            WorkTxLevel::NoRoom
+        }
+    }
+
     /// Return the value of last work ID send to ASICs
     #[inline]
     #[allow(dead_code)]
@@ -426,6 +473,23 @@ impl WorkTx {
         self.fifo.async_wait_for_room().await
     }
 
+    /// Return current fill level of work FIFO
+    pub fn level(&self) -> WorkTxLevel {
+        self.fifo.level()
+    }
+
+    /// Wait for work FIFO to become empty, i.e. until all work has been sent out to chips
+    /// Uses timed polling
+    pub async fn wait_empty(&self) {
+        while !self.fifo.is_empty() {
+            delay_for(Duration::from_millis(1)).await;
+        }
+    }
+
+    pub fn midstate_count(&self) -> usize {
+        self.midstate_count.to_count()
+    }
+
     pub fn assert_midstate_count(&self, expected_midstate_count: usize) {
         assert_eq!(
             expected_midstate_count,
@@ -648,6 +712,11 @@ impl Common {
         Ok(())
     }
 
+    /// Return midstate counts supported by s9-io bitstream
+    pub fn supported_midstate_counts(&mut self) -> &'static [usize] {
+        self.get_version().supported_midstate_counts()
+    }
+
     pub fn set_midstate_count(&self) {
         self.set_ip_core_midstate_count(self.midstate_count.to_reg());
     }
@@ -690,6 +759,12 @@ impl Core {
         })
     }
 
+    /// Return midstate counts supported by the IP core
+    /// The IP core doesn't have to be initialized for this
+    pub fn supported_midstate_counts(&mut self) -> &'static [usize] {
+        self.common_io.supported_midstate_counts()
+    }
+
     /// Initialize the IP core and split it into components
     /// That way it's not possible to access un-initialized IO blocks
     pub fn init_and_split(mut self) -> error::Result<(Common, CommandRxTx, WorkRx, WorkTx)> {
@@ -808,6 +883,26 @@ mod test {
         assert_eq!(version.to_string(), "1.2.3 for Unknown[10, 19]");
     }
 
+    #[test]
+    fn test_version_supported_midstate_counts() {
+        assert_eq!(
+            EXPECTED_S9IO_VERSION.supported_midstate_counts(),
+            &S9IO_SUPPORTED_MIDSTATE_COUNTS
+        );
+
+        // any other bitstream is expected to handle only single midstate
+        let version = Version {
+            patch: 1,
+            ..EXPECTED_S9IO_VERSION
+        };
+        assert_eq!(version.supported_midstate_counts(), &[1]);
+        let version = Version {
+            miner_type: MinerType::Unknown(10),
+            ..EXPECTED_S9IO_VERSION
+        };
+        assert_eq!(version.supported_midstate_counts(), &[1]);
+    }
+
     #[test]
     fn test_build_id_display() {
         let build_id = BuildId(0x5D8255F0);
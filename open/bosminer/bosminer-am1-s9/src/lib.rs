@@ -36,6 +36,7 @@ pub mod i2c;
 pub mod io;
 pub mod monitor;
 pub mod null_work;
+pub mod ownership;
 pub mod power;
 pub mod registry;
 pub mod sensor;
@@ -54,14 +55,16 @@ use bosminer::work;
 
 use bosminer_macros::WorkSolverNode;
 
+use std::collections::VecDeque;
 use std::fmt;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex as StdMutex};
 use std::time::{Duration, Instant};
 
 use error::ErrorKind;
 use failure::ResultExt;
 
-use futures::channel::mpsc;
+use futures::channel::{mpsc, oneshot};
 use futures::lock::{Mutex, MutexGuard};
 use futures::stream::StreamExt;
 use ii_async_compat::futures;
@@ -82,18 +85,10 @@ use tokio::time::delay_for;
 
 /// Timing constants
 const INACTIVATE_FROM_CHAIN_DELAY: Duration = Duration::from_millis(100);
-/// Base delay quantum during hashboard initialization
-const INIT_DELAY: Duration = Duration::from_secs(1);
-/// Time to wait between successive hashboard initialization attempts
-const ENUM_RETRY_DELAY: Duration = Duration::from_secs(10);
-/// How many times to retry the enumeration
-const ENUM_RETRY_COUNT: usize = 10;
 
 /// Maximum number of chips is limitted by the fact that there is only 8-bit address field and
 /// addresses to the chips need to be assigned with step of 4 (e.g. 0, 4, 8, etc.)
 pub const MAX_CHIPS_ON_CHAIN: usize = 64;
-/// Number of chips to consider OK for initialization
-pub const EXPECTED_CHIPS_ON_CHAIN: usize = 63;
 
 /// Oscillator speed for all chips on S9 hash boards
 pub const CHIP_OSC_CLK_HZ: usize = 25_000_000;
@@ -102,15 +97,26 @@ pub const CHIP_OSC_CLK_HZ: usize = 25_000_000;
 const INIT_CHIP_BAUD_RATE: usize = 115740;
 /// Exact desired target baud rate when hashing at full speed (matches the divisor, too)
 const TARGET_CHIP_BAUD_RATE: usize = 1562500;
+/// Achieved baud rate that differs from the requested one by more than this is reported
+const BAUD_RATE_WARN_PERC: usize = 1;
 
-/// Address of chip with connected temp sensor
-const TEMP_CHIP: ChipAddress = ChipAddress::One(61);
+/// Chips that are tried when temp sensor isn't found on the configured chip
+const SENSOR_CHIP_CANDIDATES: [usize; 2] = [61, 62];
+
+/// Solutions without work are summarized in log at most once per this interval
+const ORPHANED_SOLUTION_LOG_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Solutions from unexpected core address are summarized in log at most once per this interval
+const UNEXPECTED_CORE_LOG_INTERVAL: Duration = Duration::from_secs(10);
 
 /// Timeout for completion of haschain halt
 const HALT_TIMEOUT: Duration = Duration::from_secs(30);
 
-/// Core address space size (it should be 114, but the addresses are non-consecutive)
-const CORE_ADR_SPACE_SIZE: usize = 128;
+/// How long to wait for work already in TX FIFO to be sent out to chips when stopping
+const WORK_TX_DRAIN_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// Maximum time solution submission can be paused for
+pub const MAX_SOLUTION_PAUSE: Duration = Duration::from_secs(3600);
 
 /// Power type alias
 /// TODO: Implement it as a proper type (not just alias)
@@ -168,6 +174,407 @@ impl ResetPin {
     }
 }
 
+/// Counter of events that are summarized in log at most once per `interval` (logging each of
+/// them would flood the log on misbehaving chain)
+#[derive(Debug)]
+struct LogRateLimiter {
+    interval: Duration,
+    /// Events since they were last logged
+    unlogged: usize,
+    logged_at: Option<Instant>,
+}
+
+impl LogRateLimiter {
+    fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            unlogged: 0,
+            logged_at: None,
+        }
+    }
+
+    /// Count one event and return number of events to be logged if it's time to log them
+    fn add(&mut self) -> Option<usize> {
+        self.unlogged += 1;
+        let log_due = self
+            .logged_at
+            .map(|logged_at| logged_at.elapsed() >= self.interval)
+            .unwrap_or(true);
+        if log_due {
+            self.logged_at = Some(Instant::now());
+            Some(std::mem::replace(&mut self.unlogged, 0))
+        } else {
+            None
+        }
+    }
+
+    /// Return number of events that haven't been logged yet (if any) regardless of interval
+    fn flush(&mut self) -> Option<usize> {
+        match std::mem::replace(&mut self.unlogged, 0) {
+            0 => None,
+            count => Some(count),
+        }
+    }
+}
+
+/// Summary of solutions without work present in work registry. The count that hasn't been logged
+/// yet is flushed to log when the solution task terminates (hashchain halt drops it).
+struct OrphanedSolutionLog {
+    hashboard_idx: usize,
+    limiter: LogRateLimiter,
+}
+
+impl OrphanedSolutionLog {
+    fn new(hashboard_idx: usize) -> Self {
+        Self {
+            hashboard_idx,
+            limiter: LogRateLimiter::new(ORPHANED_SOLUTION_LOG_INTERVAL),
+        }
+    }
+
+    fn add(&mut self) {
+        if let Some(count) = self.limiter.add() {
+            self.log(count);
+        }
+    }
+
+    fn log(&self, count: usize) {
+        warn!(
+            "chain {}: {} solution(s) without work present since last report",
+            self.hashboard_idx, count
+        );
+    }
+}
+
+impl Drop for OrphanedSolutionLog {
+    fn drop(&mut self) {
+        if let Some(count) = self.limiter.flush() {
+            self.log(count);
+        }
+    }
+}
+
+/// Switch shared by all hashchains that allows to temporarily stop forwarding solutions to the
+/// pool while hashchains keep hashing. Paused submission is always resumed automatically after
+/// a timeout so that the miner cannot be forgotten in the paused state.
+#[derive(Debug, Default)]
+pub struct SolutionPause {
+    /// Solutions are dropped until this instant
+    paused_until: StdMutex<Option<Instant>>,
+}
+
+impl SolutionPause {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Stop submitting solutions for `timeout`
+    pub fn pause(&self, timeout: Duration) {
+        self.paused_until
+            .lock()
+            .expect("BUG: failed to lock mutex")
+            .replace(Instant::now() + timeout);
+    }
+
+    /// Resume solution submission immediately
+    pub fn resume(&self) {
+        self.paused_until
+            .lock()
+            .expect("BUG: failed to lock mutex")
+            .take();
+    }
+
+    /// Return time remaining until submission is resumed or `None` when it is not paused
+    pub fn remaining(&self) -> Option<Duration> {
+        let mut paused_until = self.paused_until.lock().expect("BUG: failed to lock mutex");
+        match *paused_until {
+            Some(deadline) => {
+                let now = Instant::now();
+                if now < deadline {
+                    Some(deadline - now)
+                } else {
+                    // timeout expired, resume automatically
+                    paused_until.take();
+                    None
+                }
+            }
+            None => None,
+        }
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.remaining().is_some()
+    }
+}
+
+/// Send solution to the pool unless submission is paused, solutions are only counted as dropped
+/// while it's paused
+async fn submit_solution(
+    solution_pause: &SolutionPause,
+    solution_sender: &work::SolutionSender,
+    counter: &Mutex<counters::HashChain>,
+    solution: work::Solution,
+) {
+    if solution_pause.is_paused() {
+        // keep hashing, but do not bother the pool
+        counter.lock().await.add_dropped();
+    } else {
+        solution_sender.send(solution);
+    }
+}
+
+/// Rolling statistics of work TX FIFO level sampled by work TX task right before writing each
+/// work (i.e. after waiting for room in the FIFO)
+///
+/// It helps to find out whether `work_time` matches chip frequency: chips that run out of work
+/// leave the FIFO empty, chips that don't take work keep it full so that work TX task has to wait
+/// for room much longer than it takes to send one work.
+#[derive(Clone, Debug, Default)]
+pub struct WorkTxOccupancy {
+    /// Moving average of samples with empty FIFO (0.0 - 1.0)
+    pub empty: f64,
+    /// Moving average of samples with full FIFO (0.0 - 1.0)
+    pub full: f64,
+    pub samples: u64,
+}
+
+impl WorkTxOccupancy {
+    /// Weight of a new sample in moving averages
+    const SMOOTHING: f64 = 0.01;
+    /// Averages are not trusted until there's enough samples
+    const MIN_SAMPLES: u64 = 100;
+    /// Averages above which the FIFO is considered consistently empty/full
+    const STARVING_THRESHOLD: f64 = 0.5;
+    const BACKED_UP_THRESHOLD: f64 = 0.5;
+    /// Waiting for room longer than this means the FIFO is backed up (one work is sent out in
+    /// a few milliseconds)
+    const BACKED_UP_WAIT: Duration = Duration::from_millis(100);
+
+    /// Add sample of FIFO `level` taken before writing work and time spent by waiting for room
+    pub fn add_sample(&mut self, level: io::WorkTxLevel, wait: Duration) {
+        let (empty, full) = match level {
+            io::WorkTxLevel::Empty => (1.0, 0.0),
+            io::WorkTxLevel::Full => (0.0, 1.0),
+            _ if wait >= Self::BACKED_UP_WAIT => (0.0, 1.0),
+            io::WorkTxLevel::HasRoom | io::WorkTxLevel::NoRoom => (0.0, 0.0),
+        };
+        self.empty += (empty - self.empty) * Self::SMOOTHING;
+        self.full += (full - self.full) * Self::SMOOTHING;
+        self.samples += 1;
+    }
+
+    /// Return hint for tuning if the FIFO is consistently starving or backed up
+    pub fn tuning_hint(&self) -> Option<&'static str> {
+        if self.samples < Self::MIN_SAMPLES {
+            None
+        } else if self.empty >= Self::STARVING_THRESHOLD {
+            Some("work TX FIFO is starving (work_time may be too short for chip frequency)")
+        } else if self.full >= Self::BACKED_UP_THRESHOLD {
+            Some("work TX FIFO is backed up (work_time may be too long for chip frequency)")
+        } else {
+            None
+        }
+    }
+}
+
+/// State of one chip position in the chain as found by enumeration
+#[derive(Clone, Debug, PartialEq)]
+pub enum ChipEnumStatus {
+    Ok,
+    /// Chip responded, but its revision is not BM1387
+    UnexpectedRevision(bm1387::ChipRevision),
+}
+
+impl ChipEnumStatus {
+    fn from_chip_rev(chip_rev: &bm1387::ChipRevision) -> Self {
+        if *chip_rev == bm1387::CHIP_REV_BM1387 {
+            ChipEnumStatus::Ok
+        } else {
+            ChipEnumStatus::UnexpectedRevision(chip_rev.clone())
+        }
+    }
+}
+
+/// Return addresses of chips that were expected on the chain, but didn't respond with valid
+/// chip revision
+fn find_enumeration_gaps(chip_map: &[ChipEnumStatus], expected_chips: usize) -> Vec<usize> {
+    (0..expected_chips)
+        .filter(|&address| chip_map.get(address) != Some(&ChipEnumStatus::Ok))
+        .collect()
+}
+
+/// Decide whether enumerated chain is acceptable according to chip revision policy
+///
+/// Returns addresses of chips that should be excluded from mining.
+fn check_chip_map(
+    chip_map: &[ChipEnumStatus],
+    policy: config::ChipRevisionPolicy,
+) -> error::Result<Vec<usize>> {
+    let unexpected: Vec<_> = chip_map
+        .iter()
+        .enumerate()
+        .filter_map(|(address, status)| match status {
+            ChipEnumStatus::Ok => None,
+            ChipEnumStatus::UnexpectedRevision(chip_rev) => Some((address, chip_rev)),
+        })
+        .collect();
+    if unexpected.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let msg = format!(
+        "unexpected revision of {} chip(s) (expected: {:#x?} received: {})",
+        unexpected.len(),
+        bm1387::CHIP_REV_BM1387,
+        unexpected
+            .iter()
+            .map(|(address, chip_rev)| format!("chip {}: {:#x?}", address, chip_rev))
+            .collect::<Vec<_>>()
+            .join(", "),
+    );
+    match policy {
+        config::ChipRevisionPolicy::Strict => Err(ErrorKind::ChipEnumeration(msg))?,
+        config::ChipRevisionPolicy::Warn => {
+            warn!("{}", msg);
+            Ok(vec![])
+        }
+        config::ChipRevisionPolicy::Exclude => {
+            warn!("{}", msg);
+            Ok(unexpected.iter().map(|(address, _)| *address).collect())
+        }
+    }
+}
+
+/// Append raw solution to debugging history that keeps at most `capacity` most recent ones
+fn record_raw_solution(
+    raw_solutions: &mut VecDeque<io::Solution>,
+    capacity: usize,
+    solution: &io::Solution,
+) {
+    if capacity == 0 {
+        return;
+    }
+    while raw_solutions.len() >= capacity {
+        raw_solutions.pop_front();
+    }
+    raw_solutions.push_back(solution.clone());
+}
+
+/// Result of one-shot hashchain diagnostic (see `StoppedChain::self_test`)
+#[derive(Clone, Debug)]
+pub struct SelfTestReport {
+    pub chip_count: usize,
+    /// One entry per discovered chip, `false` for chips with unexpected revision
+    pub chip_revision_ok: Vec<bool>,
+    /// Models of temperature sensors that responded (`None` if there are none)
+    pub sensor_info: Option<String>,
+    /// One reading per sensor
+    pub temperatures: Vec<sensor::Temperature>,
+    /// Hashrate (in hashes per second) reported by each chip in `HashrateReg`
+    pub chip_hashrates: Vec<u64>,
+    pub chip_baud_rate: usize,
+    pub ip_core_baud_rate: usize,
+    pub baud_rate_off_target: bool,
+}
+
+impl SelfTestReport {
+    pub fn is_sensor_present(&self) -> bool {
+        self.sensor_info.is_some()
+    }
+}
+
+/// Orderly teardown of hashchain: work TX task is stopped first and the work that is already
+/// in TX FIFO is left to drain, then voltage is disabled and only then the remaining hashchain
+/// tasks are halted.
+///
+/// Voltage is cut before halt (same as when hashchain fails to start), so it doesn't depend on
+/// the remaining tasks acknowledging the halt in time. It doesn't touch the FPGA.
+struct Teardown {
+    hashboard_idx: usize,
+    /// Voltage controller on this hashboard
+    voltage_ctrl: Arc<power::Control>,
+    /// Set once the hashchain has been shut down
+    is_shut_down: AtomicBool,
+    /// halter to stop the remaining hashchain tasks
+    halt_sender: Arc<halt::Sender>,
+    /// Signal for work TX task to stop generating new work
+    stop_work_sender: watch::Sender<bool>,
+    stop_work_receiver: watch::Receiver<bool>,
+    /// Notification that work TX task has stopped and drained the FIFO (present once started)
+    work_tx_stopped: Mutex<Option<oneshot::Receiver<()>>>,
+}
+
+impl Teardown {
+    fn new(
+        hashboard_idx: usize,
+        voltage_ctrl: Arc<power::Control>,
+        halt_sender: Arc<halt::Sender>,
+    ) -> Self {
+        let (stop_work_sender, stop_work_receiver) = watch::channel(false);
+        Self {
+            hashboard_idx,
+            voltage_ctrl,
+            is_shut_down: AtomicBool::new(false),
+            halt_sender,
+            stop_work_sender,
+            stop_work_receiver,
+            work_tx_stopped: Mutex::new(None),
+        }
+    }
+
+    /// Channels for work TX task that is being started: signal to stop generating work and
+    /// notification that the task has stopped
+    async fn work_tx_channels(&self) -> (watch::Receiver<bool>, oneshot::Sender<()>) {
+        let (work_tx_stopped_sender, work_tx_stopped) = oneshot::channel();
+        self.work_tx_stopped.lock().await.replace(work_tx_stopped);
+        (self.stop_work_receiver.clone(), work_tx_stopped_sender)
+    }
+
+    /// Stop sending new work to chips and wait until work already sent to TX FIFO drains
+    ///
+    /// The wait is bounded by `WORK_TX_DRAIN_TIMEOUT` (plus some slack). Does nothing when
+    /// the hashchain hasn't been started or its work has already been stopped.
+    async fn stop_work(&self) {
+        let work_tx_stopped = match self.work_tx_stopped.lock().await.take() {
+            Some(work_tx_stopped) => work_tx_stopped,
+            None => return,
+        };
+        self.stop_work_sender
+            .broadcast(true)
+            .expect("BUG: stop work receiver missing");
+        // error means the task has already ended (e.g. it has been halted)
+        if tokio::time::timeout(WORK_TX_DRAIN_TIMEOUT * 2, work_tx_stopped)
+            .await
+            .is_err()
+        {
+            warn!("Chain {}: work TX didn't stop in time", self.hashboard_idx);
+        }
+    }
+
+    /// Disable voltage and stop voltage controller heart beat (only the first call does
+    /// anything)
+    async fn shutdown(&self) {
+        if self.is_shut_down.swap(true, Ordering::Relaxed) {
+            return;
+        }
+        info!("Chain {}: shutting down", self.hashboard_idx);
+        self.voltage_ctrl.stop_heart_beat();
+        if let Err(e) = self.voltage_ctrl.disable_voltage().await {
+            error!(
+                "Chain {}: failed to disable voltage: {}",
+                self.hashboard_idx, e
+            );
+        }
+    }
+
+    /// Run the whole teardown sequence
+    async fn stop(&self) {
+        self.stop_work().await;
+        self.shutdown().await;
+        self.halt_sender.clone().send_halt().await;
+    }
+}
+
 /// Hash Chain Controller provides abstraction of the FPGA interface for operating hashing boards.
 /// It is the user-space driver for the IP Core
 ///
@@ -175,16 +582,19 @@ impl ResetPin {
 /// - memory mapping of the FPGA control interface
 /// - mining work submission and solution processing
 ///
-/// TODO: disable voltage controller via async `Drop` trait (which doesn't exist yet)
+/// Voltage controller is not disabled on `Drop` (async `Drop` doesn't exist yet), call
+/// `shutdown()` explicitly instead.
 pub struct HashChain {
-    /// Number of chips that have been detected
-    chip_count: usize,
+    /// Number of chips that have been detected (can change when chain is re-enumerated)
+    chip_count: AtomicUsize,
     /// Eliminates the need to query the IP core about the current number of configured midstates
     midstate_count: MidstateCount,
-    /// ASIC difficulty
-    asic_difficulty: usize,
-    /// ASIC target (matches difficulty)
-    asic_target: ii_bitcoin::Target,
+    /// ASIC difficulty (can change at runtime, see `set_asic_difficulty`)
+    asic_difficulty: AtomicUsize,
+    /// How to decode chip and core address from nonce
+    core_address_layout: bm1387::CoreAddressLayout,
+    /// Address of chip with connected temp sensor
+    sensor_chip_address: usize,
     /// Voltage controller on this hashboard
     voltage_ctrl: Arc<power::Control>,
     /// Pin for resetting the hashboard
@@ -198,9 +608,37 @@ pub struct HashChain {
     /// Do not send open-core work if this is true (some tests that test chip initialization may
     /// want to do this).
     disable_init_work: bool,
-    /// channels through which temperature status is sent
-    temperature_sender: Mutex<Option<watch::Sender<Option<sensor::Temperature>>>>,
-    temperature_receiver: watch::Receiver<Option<sensor::Temperature>>,
+    /// Amount of open-core work relative to number of cores on chip (0 skips it)
+    init_work_multiplier: f32,
+    /// Lower voltage from open-core to working level gradually (`None` does it in one step)
+    voltage_ramp: Option<power::VoltageRamp>,
+    /// Base clock speed of the FPGA IP core (depends on bitstream)
+    fpga_clock_frequency: usize,
+    /// Base delay quantum of reset sequence during hashboard initialization
+    init_delay: Duration,
+    /// Number of chips to consider OK for initialization
+    expected_chip_count: usize,
+    /// How to treat temperature sensor failures
+    sensor_config: config::SensorConfig,
+    /// How often to send temperature update to monitor
+    temp_update_interval: Duration,
+    /// Maximum acceptable difference between requested and achievable PLL frequency (in Hz)
+    pll_freq_error_tolerance: usize,
+    /// Worst difference between requested and achieved frequency of the last `set_pll`
+    pll_freq_error: AtomicUsize,
+    /// Fudge factor of work delay (see `utils::WORK_DELAY_FUDGE`)
+    work_delay_fudge: f64,
+    /// Baud rate actually set on the chips (0 until it's configured)
+    chip_baud_rate: AtomicUsize,
+    /// Baud rate actually set in the FPGA IP core (0 until it's configured)
+    ip_core_baud_rate: AtomicUsize,
+    /// Set when the last chip baud rate is off the requested one (see `BAUD_RATE_WARN_PERC`)
+    chip_baud_rate_off_target: AtomicBool,
+    /// Set when the last IP core baud rate is off the requested one
+    ip_core_baud_rate_off_target: AtomicBool,
+    /// channels through which temperature status is sent (one reading per sensor)
+    temperature_sender: Mutex<Option<watch::Sender<Option<Vec<sensor::Temperature>>>>>,
+    temperature_receiver: watch::Receiver<Option<Vec<sensor::Temperature>>>,
     /// nonce counter
     pub counter: Arc<Mutex<counters::HashChain>>,
     /// halter to stop this hashchain
@@ -210,6 +648,30 @@ pub struct HashChain {
     halt_receiver: halt::Receiver,
     /// Current hashchain settings
     frequency: Mutex<FrequencySettings>,
+    /// Last hashrate (in hashes per second) reported by each chip in `HashrateReg`
+    chip_hashrates: Arc<Mutex<Vec<u64>>>,
+    /// How many raw solutions to keep for debugging (0 disables it)
+    raw_solution_history: usize,
+    /// Last raw solutions as received from FPGA
+    raw_solutions: Mutex<VecDeque<io::Solution>>,
+    /// How to treat chips of unexpected revision
+    chip_revision_policy: config::ChipRevisionPolicy,
+    /// Voltage controller heart beat period override
+    heart_beat_period: Option<Duration>,
+    /// Chips whose solutions are ignored (unexpected revision)
+    excluded_chips: Mutex<Vec<usize>>,
+    /// State of each chip as found by the last enumeration
+    chip_map: Mutex<Vec<ChipEnumStatus>>,
+    /// Expected chips that were missing in the last enumeration
+    enumeration_gaps: StdMutex<Vec<usize>>,
+    /// Models of temperature sensors found on this hashchain (`None` until they're probed)
+    sensor_info: StdMutex<Option<String>>,
+    /// Work registry shared with work tasks (present once the hashchain is started)
+    work_registry: Mutex<Option<Arc<Mutex<registry::WorkRegistry>>>>,
+    /// Work TX FIFO level statistics (updated by work TX task)
+    work_tx_occupancy: Arc<StdMutex<WorkTxOccupancy>>,
+    /// Stops work, voltage and hashchain tasks in the right order
+    teardown: Teardown,
 }
 
 impl HashChain {
@@ -220,6 +682,8 @@ impl HashChain {
     /// * `hashboard_idx` - index of this hashboard determines which FPGA IP core is to be mapped
     /// * `midstate_count` - see Self
     /// * `asic_difficulty` - to what difficulty set the hardware target filter
+    /// * `core_address_layout` - where to find chip and core address in nonce
+    /// * `sensor_chip_address` - chip with I2C bus to which temp sensor is connected
     pub fn new(
         reset_pin: ResetPin,
         plug_pin: PlugPin,
@@ -227,6 +691,8 @@ impl HashChain {
         hashboard_idx: usize,
         midstate_count: MidstateCount,
         asic_difficulty: usize,
+        core_address_layout: bm1387::CoreAddressLayout,
+        sensor_chip_address: usize,
         monitor_tx: mpsc::UnboundedSender<monitor::Message>,
     ) -> error::Result<Self> {
         let core = io::Core::new(hashboard_idx, midstate_count)?;
@@ -247,12 +713,16 @@ impl HashChain {
         // create halt notification channel
         let (halt_sender, halt_receiver) = halt::make_pair(HALT_TIMEOUT);
 
+        let voltage_ctrl = Arc::new(power::Control::new(voltage_ctrl_backend, hashboard_idx));
+        let teardown = Teardown::new(hashboard_idx, voltage_ctrl.clone(), halt_sender.clone());
+
         Ok(Self {
-            chip_count: 0,
+            chip_count: AtomicUsize::new(0),
             midstate_count,
-            asic_difficulty,
-            asic_target: ii_bitcoin::Target::from_pool_difficulty(asic_difficulty),
-            voltage_ctrl: Arc::new(power::Control::new(voltage_ctrl_backend, hashboard_idx)),
+            asic_difficulty: AtomicUsize::new(asic_difficulty),
+            core_address_layout,
+            sensor_chip_address,
+            voltage_ctrl,
             reset_pin,
             hashboard_idx,
             common_io,
@@ -261,22 +731,136 @@ impl HashChain {
             work_tx_io: Mutex::new(Some(work_tx_io)),
             monitor_tx,
             disable_init_work: false,
+            init_work_multiplier: config::DEFAULT_INIT_WORK_MULTIPLIER,
+            voltage_ramp: None,
+            fpga_clock_frequency: io::F_CLK_SPEED_HZ,
+            init_delay: config::DEFAULT_INIT_DELAY,
+            expected_chip_count: config::DEFAULT_EXPECTED_CHIPS_ON_CHAIN,
+            sensor_config: Default::default(),
+            temp_update_interval: monitor::DEFAULT_TEMP_UPDATE_INTERVAL,
+            pll_freq_error_tolerance: bm1387::PllFrequency::MAX_FREQ_ERROR_HZ,
+            pll_freq_error: AtomicUsize::new(0),
+            work_delay_fudge: utils::WORK_DELAY_FUDGE,
+            chip_baud_rate: AtomicUsize::new(0),
+            ip_core_baud_rate: AtomicUsize::new(0),
+            chip_baud_rate_off_target: AtomicBool::new(false),
+            ip_core_baud_rate_off_target: AtomicBool::new(false),
             temperature_sender: Mutex::new(Some(temperature_sender)),
             temperature_receiver,
             counter: Arc::new(Mutex::new(counters::HashChain::new(
                 MAX_CHIPS_ON_CHAIN,
                 asic_difficulty,
+                core_address_layout,
             ))),
             halt_sender,
             halt_receiver,
-            frequency: Mutex::new(FrequencySettings::from_frequency(0)),
+            frequency: Mutex::new(FrequencySettings::from_frequency(
+                0,
+                config::DEFAULT_EXPECTED_CHIPS_ON_CHAIN,
+            )),
+            chip_hashrates: Arc::new(Mutex::new(Vec::new())),
+            raw_solution_history: 0,
+            chip_revision_policy: Default::default(),
+            heart_beat_period: None,
+            excluded_chips: Mutex::new(Vec::new()),
+            chip_map: Mutex::new(Vec::new()),
+            enumeration_gaps: StdMutex::new(Vec::new()),
+            sensor_info: StdMutex::new(None),
+            raw_solutions: Mutex::new(VecDeque::new()),
+            work_registry: Mutex::new(None),
+            work_tx_occupancy: Arc::new(StdMutex::new(Default::default())),
+            teardown,
         })
     }
 
-    pub fn current_temperature(&self) -> Option<sensor::Temperature> {
+    /// Last temperature readings of all sensors (empty if hashboard has no sensor)
+    pub fn current_temperature(&self) -> Option<Vec<sensor::Temperature>> {
         self.temperature_receiver.borrow().clone()
     }
 
+    /// Keep last `size` raw solutions received from FPGA for debugging (0 disables it)
+    pub fn set_raw_solution_history(&mut self, size: usize) {
+        self.raw_solution_history = size;
+    }
+
+    pub fn set_chip_revision_policy(&mut self, policy: config::ChipRevisionPolicy) {
+        self.chip_revision_policy = policy;
+    }
+
+    /// Scale amount of open-core work sent during initialization, 0 disables it completely
+    ///
+    /// Open-core work is sent while the chain is still at high (open-core) voltage, so more
+    /// work means the board spends more time at that voltage before it's lowered to the
+    /// working level.
+    pub fn set_init_work_multiplier(&mut self, multiplier: f32) {
+        self.init_work_multiplier = multiplier.max(0.0);
+    }
+
+    /// Ramp voltage down after open-core work instead of changing it in one step
+    pub fn set_voltage_ramp(&mut self, ramp: Option<power::VoltageRamp>) {
+        self.voltage_ramp = ramp;
+    }
+
+    /// Override base clock speed of the FPGA IP core (in Hz) for bitstreams that are clocked
+    /// differently. Both baud rate and work time are derived from it.
+    pub fn set_fpga_clock_frequency(&mut self, frequency: usize) {
+        self.fpga_clock_frequency = frequency;
+    }
+
+    /// Override base delay of reset sequence (for boards that need longer to settle)
+    pub fn set_init_delay(&mut self, delay: Duration) {
+        self.init_delay = delay;
+    }
+
+    /// Override number of chips that make a complete chain (for boards that differ from S9)
+    pub fn set_expected_chip_count(&mut self, expected_chip_count: usize) {
+        assert!(expected_chip_count < MAX_CHIPS_ON_CHAIN);
+        self.expected_chip_count = expected_chip_count;
+    }
+
+    pub fn set_sensor_config(&mut self, sensor_config: config::SensorConfig) {
+        self.sensor_config = sensor_config;
+    }
+
+    pub fn set_temp_update_interval(&mut self, interval: Duration) {
+        self.temp_update_interval = interval;
+    }
+
+    pub fn set_pll_freq_error_tolerance(&mut self, tolerance: usize) {
+        self.pll_freq_error_tolerance = tolerance;
+    }
+
+    pub fn set_work_delay_fudge(&mut self, fudge: f64) {
+        self.work_delay_fudge = fudge;
+    }
+
+    pub fn set_heart_beat_period(&mut self, period: Option<Duration>) {
+        self.heart_beat_period = period;
+    }
+
+    /// Addresses of chips that were expected, but silent (or invalid) in the last enumeration
+    pub fn last_enumeration_gaps(&self) -> Vec<usize> {
+        self.enumeration_gaps
+            .lock()
+            .expect("BUG: lock failed")
+            .clone()
+    }
+
+    /// Temperature sensors that were found on this hashchain (e.g. "TMP451 at 0x98 on chip 61")
+    pub fn get_sensor_info(&self) -> Option<String> {
+        self.sensor_info.lock().expect("BUG: lock failed").clone()
+    }
+
+    /// State of all chips found by the last enumeration (including chips with bad revision)
+    pub async fn get_chip_map(&self) -> Vec<ChipEnumStatus> {
+        self.chip_map.lock().await.clone()
+    }
+
+    /// Return last raw solutions (oldest first), empty if history is disabled
+    pub async fn get_raw_solutions(&self) -> Vec<io::Solution> {
+        self.raw_solutions.lock().await.iter().cloned().collect()
+    }
+
     async fn take_work_rx_io(&self) -> io::WorkRx {
         self.work_rx_io
             .lock()
@@ -298,10 +882,12 @@ impl HashChain {
     /// Returns number of ticks (suitable to be written to `WORK_TIME` register)
     #[inline]
     fn calculate_work_time(&self, max_pll_frequency: usize) -> u32 {
-        secs_to_fpga_ticks(calculate_work_delay_for_pll(
+        utils::compute_work_time(
             self.midstate_count.to_count(),
             max_pll_frequency,
-        ))
+            self.fpga_clock_frequency,
+            self.work_delay_fudge,
+        )
     }
 
     /// Set work time depending on current PLL frequency
@@ -338,8 +924,30 @@ impl HashChain {
         Ok(())
     }
 
+    /// Park hashboard in a safe state: voltage disabled and chips held in reset
+    async fn power_off(&mut self) {
+        self.shutdown().await;
+        if let Err(e) = self.enter_reset() {
+            error!("Chain {}: failed to enter reset: {}", self.hashboard_idx, e);
+        }
+    }
+
+    /// Disable voltage and stop voltage controller heart beat
+    ///
+    /// This is an explicit replacement for async `Drop`: it's safe to call it multiple times,
+    /// only the first call does anything.
+    pub async fn shutdown(&self) {
+        self.teardown.shutdown().await;
+    }
+
+    /// Stop running hashchain: stop work and let TX FIFO drain, disable voltage and halt
+    /// all hashchain tasks (see `Teardown`)
+    pub async fn stop(&self) {
+        self.teardown.stop().await;
+    }
+
     /// Configures difficulty globally on all chips within the hashchain
-    async fn set_asic_diff(&mut self, difficulty: usize) -> error::Result<()> {
+    async fn set_asic_diff(&self, difficulty: usize) -> error::Result<()> {
         let tm_reg = bm1387::TicketMaskReg::new(difficulty as u32)?;
         trace!(
             "Setting ticket mask register for difficulty {}, value {:#010x?}",
@@ -352,6 +960,63 @@ impl HashChain {
         Ok(())
     }
 
+    pub fn get_asic_difficulty(&self) -> usize {
+        self.asic_difficulty.load(Ordering::Relaxed)
+    }
+
+    /// Read back ticket mask register of all chips and return difficulty that is actually
+    /// applied by hardware
+    ///
+    /// Mismatch with requested difficulty (see `get_asic_difficulty`) is reported as warning.
+    /// When chips disagree, the lowest difficulty is returned.
+    pub async fn get_asic_difficulty_applied(&self) -> error::Result<usize> {
+        let requested = self.get_asic_difficulty();
+        let tm_regs = self
+            .command_context
+            .read_register::<bm1387::TicketMaskReg>(ChipAddress::All)
+            .await?;
+        let mut applied = None;
+        for (chip_address, tm_reg) in tm_regs.iter().enumerate() {
+            let difficulty = tm_reg.difficulty() as usize;
+            if difficulty != requested {
+                warn!(
+                    "Chain {}: chip {} applies ASIC difficulty {} instead of requested {}",
+                    self.hashboard_idx, chip_address, difficulty, requested
+                );
+            }
+            applied = Some(applied.map_or(difficulty, |v: usize| v.min(difficulty)));
+        }
+        applied.ok_or_else(|| {
+            ErrorKind::Hashchip("no chip replied with ticket mask register".to_string()).into()
+        })
+    }
+
+    /// Change ASIC difficulty of running hashchain
+    ///
+    /// Only work submitted after the change is checked against the new ASIC target, solutions
+    /// of work that is already in flight are still validated against the old one.
+    /// Counters credit valid solutions with the new difficulty right away, so callers should
+    /// reset the counters afterwards.
+    pub async fn set_asic_difficulty(&self, difficulty: usize) -> error::Result<()> {
+        let work_registry = self.work_registry.lock().await.clone();
+        match work_registry {
+            Some(work_registry) => {
+                // hold the registry so that no new work is stored while the ticket mask changes
+                let mut work_registry = work_registry.lock().await;
+                self.set_asic_diff(difficulty).await?;
+                work_registry.set_asic_target(ii_bitcoin::Target::from_pool_difficulty(difficulty));
+            }
+            None => self.set_asic_diff(difficulty).await?,
+        }
+        self.asic_difficulty.store(difficulty, Ordering::Relaxed);
+        self.counter.lock().await.asic_difficulty = difficulty;
+        info!(
+            "Chain {}: ASIC difficulty set to {}",
+            self.hashboard_idx, difficulty
+        );
+        Ok(())
+    }
+
     /// Reset hashboard and try to enumerate the chips.
     /// If not enough chips were found and `accept_less_chips` is not specified,
     /// treat it as error.
@@ -364,28 +1029,32 @@ impl HashChain {
         info!("Resetting hash board");
         self.enter_reset()?;
         self.voltage_ctrl.disable_voltage().await?;
-        delay_for(INIT_DELAY).await;
+        delay_for(self.init_delay).await;
         self.voltage_ctrl.enable_voltage().await?;
-        delay_for(INIT_DELAY * 2).await;
+        delay_for(self.init_delay * 2).await;
         self.exit_reset()?;
-        delay_for(INIT_DELAY).await;
+        delay_for(self.init_delay).await;
 
         // Enumerate chips
         info!("Starting chip enumeration");
-        self.enumerate_chips().await?;
+        let chip_count = self.enumerate_chips().await?;
 
         // Figure out if we found enough chips
-        info!("Discovered {} chips", self.chip_count);
-        self.command_context.set_chip_count(self.chip_count).await;
-        self.counter.lock().await.set_chip_count(self.chip_count);
-        self.frequency.lock().await.set_chip_count(self.chip_count);
+        info!("Discovered {} chips", chip_count);
+        self.update_chip_count(chip_count).await;
 
         // If we don't have full number of chips and we do not want incomplete chain, then raise
         // an error
-        if self.chip_count < EXPECTED_CHIPS_ON_CHAIN && !accept_less_chips {
-            Err(ErrorKind::ChipEnumeration(
-                "Not enough chips on chain".into(),
-            ))?;
+        if chip_count < self.expected_chip_count {
+            if !accept_less_chips {
+                Err(ErrorKind::ChipEnumeration(
+                    "Not enough chips on chain".into(),
+                ))?;
+            }
+            warn!(
+                "Chain {}: accepting short chain with {} chips ({} expected)",
+                self.hashboard_idx, chip_count, self.expected_chip_count
+            );
         }
 
         // set PLL
@@ -397,7 +1066,7 @@ impl HashChain {
             .await?;
         self.set_ip_core_baud_rate(TARGET_CHIP_BAUD_RATE)?;
 
-        self.set_asic_diff(self.asic_difficulty).await?;
+        self.set_asic_diff(self.get_asic_difficulty()).await?;
 
         Ok(())
     }
@@ -416,12 +1085,13 @@ impl HashChain {
         info!("Hashboard IP core initialized");
         self.voltage_ctrl
             .clone()
-            .init(self.halt_receiver.clone())
+            .init(self.halt_receiver.clone(), self.heart_beat_period)
             .await?;
 
         info!(
             "Initializing hash chain {}, (difficulty {})",
-            self.hashboard_idx, self.asic_difficulty
+            self.hashboard_idx,
+            self.get_asic_difficulty()
         );
         self.ip_core_init().await?;
 
@@ -431,60 +1101,141 @@ impl HashChain {
 
         // Build shared work registry
         // TX fifo determines the size of work registry
-        let work_registry = Arc::new(Mutex::new(registry::WorkRegistry::new(
+        let mut work_registry = registry::WorkRegistry::new(
             self.work_tx_io
                 .lock()
                 .await
                 .as_ref()
                 .expect("work-tx io missing")
                 .work_id_count(),
-        )));
+        );
+        work_registry.set_asic_target(ii_bitcoin::Target::from_pool_difficulty(
+            self.get_asic_difficulty(),
+        ));
+        let work_registry = Arc::new(Mutex::new(work_registry));
 
         // send opencore work (at high voltage) unless someone disabled it
-        if !self.disable_init_work {
+        if !self.disable_init_work && self.init_work_multiplier > 0.0 {
             self.send_init_work(work_registry.clone()).await;
         }
 
         // lower voltage to working level
-        self.voltage_ctrl
-            .set_voltage(initial_voltage)
-            .await
-            .expect("lowering voltage failed");
+        match self.voltage_ramp {
+            Some(ramp) => {
+                self.voltage_ctrl
+                    .set_voltage_ramped(initial_voltage, ramp.step, ramp.delay)
+                    .await
+            }
+            None => self.voltage_ctrl.set_voltage(initial_voltage).await,
+        }
+        .expect("lowering voltage failed");
 
         // return work registry we created
         Ok(work_registry)
     }
 
+    /// Reset and enumerate the hashboard, then read everything that can be read once without
+    /// mining: temperature sensors, hashrate registers and baud rates
+    ///
+    /// Short chain is not treated as an error, it's up to the caller to judge the report.
+    /// Caller is responsible for shutting the hashchain down afterwards.
+    async fn self_test(
+        &mut self,
+        initial_frequency: &FrequencySettings,
+    ) -> error::Result<SelfTestReport> {
+        self.voltage_ctrl
+            .clone()
+            .init(self.halt_receiver.clone(), self.heart_beat_period)
+            .await?;
+        self.ip_core_init().await?;
+        self.reset_and_enumerate_and_init(true, initial_frequency)
+            .await?;
+
+        let chip_revision_ok = self
+            .get_chip_map()
+            .await
+            .iter()
+            .map(|status| *status == ChipEnumStatus::Ok)
+            .collect();
+
+        let mut temperatures = Vec::new();
+        match self.try_to_initialize_sensors().await {
+            Ok(sensors) => {
+                for sensor in sensors {
+                    let mut sensor =
+                        sensor::CheckedSensor::new(sensor, self.sensor_config.max_read_errors);
+                    temperatures.push(sensor.read().await);
+                }
+            }
+            Err(e) => warn!("Chain {}: sensor probing failed: {}", self.hashboard_idx, e),
+        }
+
+        let chip_hashrates = self.read_chip_hashrates().await?;
+        let (chip_baud_rate, ip_core_baud_rate) = self.get_baud_rates();
+
+        Ok(SelfTestReport {
+            chip_count: self.get_chip_count(),
+            chip_revision_ok,
+            sensor_info: self.get_sensor_info(),
+            temperatures,
+            chip_hashrates,
+            chip_baud_rate,
+            ip_core_baud_rate,
+            baud_rate_off_target: self.is_baud_rate_off_target(),
+        })
+    }
+
     /// Detects the number of chips on the hashing chain and assigns an address to each chip
-    async fn enumerate_chips(&mut self) -> error::Result<()> {
+    ///
+    /// Returns number of detected chips. Chip count of this instance is left for the caller
+    /// to update (see `update_chip_count`).
+    async fn enumerate_chips(&self) -> error::Result<usize> {
+        // Disable check of the number of replies (we might get called multiple times and
+        // the number of chips may have changed since the last time)
+        self.command_context.reset_chip_count().await;
+
         // Enumerate all chips (broadcast read address register request)
         let responses = self
             .command_context
             .read_register::<bm1387::GetAddressReg>(ChipAddress::All)
             .await?;
 
-        // Reset chip count (we might get called multiple times)
-        self.chip_count = 0;
+        // Scan the whole chain first and decide once we know the state of all chips
+        let chip_map: Vec<_> = responses
+            .iter()
+            .map(|addr_reg| ChipEnumStatus::from_chip_rev(&addr_reg.chip_rev))
+            .collect();
+        let chip_count = chip_map.len();
+        *self.chip_map.lock().await = chip_map.clone();
+
+        let gaps = find_enumeration_gaps(&chip_map, self.expected_chip_count);
+        if let Some(first_gap) = gaps.first() {
+            warn!(
+                "Chain {}: {} of {} expected chips missing or invalid, first one is chip {}",
+                self.hashboard_idx,
+                gaps.len(),
+                self.expected_chip_count,
+                first_gap
+            );
+        }
+        *self.enumeration_gaps.lock().expect("BUG: lock failed") = gaps;
+
         // Check if are responses meaningful
-        for (address, addr_reg) in responses.iter().enumerate() {
-            if addr_reg.chip_rev != bm1387::CHIP_REV_BM1387 {
-                Err(ErrorKind::ChipEnumeration(format!(
-                    "unexpected revision of chip {} (expected: {:#x?} received: {:#x?})",
-                    address,
-                    bm1387::CHIP_REV_BM1387,
-                    addr_reg.chip_rev,
-                )))?
-            }
-            self.chip_count += 1;
+        let excluded_chips = check_chip_map(&chip_map, self.chip_revision_policy)?;
+        for &address in excluded_chips.iter() {
+            warn!(
+                "Chain {}: ignoring solutions of chip {} ({:?})",
+                self.hashboard_idx, address, chip_map[address]
+            );
         }
-        if self.chip_count >= MAX_CHIPS_ON_CHAIN {
+        if chip_count >= MAX_CHIPS_ON_CHAIN {
             Err(ErrorKind::ChipEnumeration(format!(
                 "detected {} chips, expected less than {} chips on one chain. Possibly a hardware issue?",
-                self.chip_count,
+                chip_count,
                 MAX_CHIPS_ON_CHAIN,
             )))?
         }
-        if self.chip_count == 0 {
+        if chip_count == 0 {
             Err(ErrorKind::ChipEnumeration(
                 "no chips detected on the current chain".to_string(),
             ))?
@@ -502,14 +1253,68 @@ impl HashChain {
         }
 
         // Assign address to each chip
-        for i in 0..self.chip_count {
+        for i in 0..chip_count {
             let cmd = bm1387::SetChipAddressCmd::new(ChipAddress::One(i));
             self.command_context
                 .send_raw_command(cmd.pack().to_vec(), false)
                 .await;
         }
+        *self.excluded_chips.lock().await = excluded_chips;
 
-        Ok(())
+        Ok(chip_count)
+    }
+
+    /// Propagate new number of chips to all structures that depend on it
+    async fn update_chip_count(&self, chip_count: usize) {
+        self.chip_count.store(chip_count, Ordering::Relaxed);
+        self.command_context.set_chip_count(chip_count).await;
+        self.counter.lock().await.set_chip_count(chip_count);
+        self.frequency.lock().await.set_chip_count(chip_count);
+    }
+
+    /// Enumerate chips of running chain again and update chip count
+    ///
+    /// Voltage is not touched. When the number of chips is the same, nothing else is changed.
+    /// Chips that (re)appeared on the chain are loaded with PLL from `initial_frequency`. When
+    /// loading PLL fails, the chain is shrunk back to the chips that have been loaded (so that no
+    /// chip is reported with frequency it's not running at).
+    ///
+    /// Returns new number of chips.
+    pub async fn reenumerate(&self, initial_frequency: &FrequencySettings) -> error::Result<usize> {
+        let old_chip_count = self.get_chip_count();
+        let chip_count = match self.enumerate_chips().await {
+            Ok(chip_count) => chip_count,
+            Err(e) => {
+                // restore check of replies for chips we know about
+                self.command_context.set_chip_count(old_chip_count).await;
+                return Err(e);
+            }
+        };
+
+        info!(
+            "chain {}: re-enumerated {} chips (previously {})",
+            self.hashboard_idx, chip_count, old_chip_count
+        );
+        self.update_chip_count(chip_count).await;
+
+        // Load PLL to chips that came back
+        let added_chips = initial_frequency.added_chips(old_chip_count, chip_count);
+        let mut result = Ok(chip_count);
+        if !added_chips.is_empty() {
+            for (i, freq) in added_chips {
+                if let Err(e) = self.set_chip_pll(ChipAddress::One(i), freq).await {
+                    self.update_chip_count(i).await;
+                    result = Err(e);
+                    break;
+                }
+                self.frequency.lock().await.chip[i] = freq;
+            }
+            // chips that have been loaded already run at the new frequency
+            let max_frequency = self.frequency.lock().await.max();
+            self.set_work_time(max_frequency).await;
+        }
+
+        result
     }
 
     /// Loads PLL register with a starting value
@@ -528,7 +1333,8 @@ impl HashChain {
         );
 
         // NOTE: When PLL register is read back, it is or-ed with 0x8000_0000, not sure why.
-        //  Avoid reading it back to prevent disappointment.
+        //  Avoid reading it back to prevent disappointment (use `read_chip_frequencies` to
+        //  check what the chips are actually running at).
         self.command_context
             .write_register(chip_addr, &pll.reg)
             .await?;
@@ -536,12 +1342,36 @@ impl HashChain {
         Ok(())
     }
 
+    /// Read back PLL register of all chips and decode it to frequency (in Hz)
+    ///
+    /// Returns one entry per chip in chain order. The extra bit (0x8000_0000) chips set in
+    /// PLL register on readback is ignored, so frequencies from `PRECOMPUTED_PLL` table are
+    /// returned exactly as they were set.
+    pub async fn read_chip_frequencies(&self) -> error::Result<Vec<usize>> {
+        let plls = self
+            .command_context
+            .read_register::<bm1387::PllReg>(ChipAddress::All)
+            .await?;
+        Ok(plls.iter().map(|pll| pll.calc(CHIP_OSC_CLK_HZ)).collect())
+    }
+
     /// Load PLL register of all chips
     ///
-    /// Takes care of adjusting `work_time`
+    /// Takes care of adjusting `work_time`. Nothing is set when some chip frequency is further
+    /// than `pll_freq_error_tolerance` from the nearest PLL setting.
     pub async fn set_pll(&self, frequency: &FrequencySettings) -> error::Result<()> {
         // TODO: find a better way - how to communicate with frequency setter how many chips we have?
-        assert!(frequency.chip.len() >= self.chip_count);
+        let chip_count = self.get_chip_count();
+        assert!(frequency.chip.len() >= chip_count);
+
+        // Find the worst approximation before touching the chips
+        let max_error = frequency
+            .pll_error(
+                &bm1387::PRECOMPUTED_PLL,
+                chip_count,
+                self.pll_freq_error_tolerance,
+            )
+            .map_err(|e| ErrorKind::PLL(format!("chain {}: {}", self.hashboard_idx, e)))?;
 
         // Check if the frequencies are identical
         if frequency.min() == frequency.max() {
@@ -549,21 +1379,20 @@ impl HashChain {
             self.set_chip_pll(ChipAddress::All, frequency.chip[0])
                 .await?;
         } else {
-            // Update chips one-by-one
-            for i in 0..self.chip_count {
-                let new_freq = self.frequency.lock().await.chip[i];
-                if new_freq != frequency.chip[i] {
-                    self.set_chip_pll(ChipAddress::One(i), new_freq).await?;
-                }
+            // Update chips one-by-one (only those that are not running at requested frequency)
+            let changed_chips = frequency.changed_chips(&*self.frequency.lock().await, chip_count);
+            for (i, new_freq) in changed_chips {
+                self.set_chip_pll(ChipAddress::One(i), new_freq).await?;
             }
         }
 
         // Update worktime
         self.set_work_time(frequency.max()).await;
+        self.pll_freq_error.store(max_error, Ordering::Relaxed);
 
         // Remember what frequencies are set
         let mut cur_frequency = self.frequency.lock().await;
-        for i in 0..self.chip_count {
+        for i in 0..chip_count {
             cur_frequency.chip[i] = frequency.chip[i];
         }
 
@@ -597,6 +1426,12 @@ impl HashChain {
             "Setting Hash chain baud rate @ requested: {}, actual: {}, divisor {:#04x}",
             baud_rate, actual_baud_rate, baud_clock_div
         );
+        self.chip_baud_rate
+            .store(actual_baud_rate, Ordering::Relaxed);
+        self.chip_baud_rate_off_target.store(
+            self.check_baud_rate("chip", baud_rate, actual_baud_rate),
+            Ordering::Relaxed,
+        );
         // Each chip is always configured with inverted clock
         let ctl_reg =
             bm1387::MiscCtrlReg::new(not_set_baud, true, baud_clock_div, gate_block, true)?;
@@ -614,32 +1449,72 @@ impl HashChain {
     /// chain have been reconfigured for a different speed, too.
     fn set_ip_core_baud_rate(&self, baud: usize) -> error::Result<()> {
         let (baud_clock_div, actual_baud_rate) =
-            calc_baud_clock_div(baud, io::F_CLK_SPEED_HZ, io::F_CLK_BASE_BAUD_DIV)?;
+            calc_baud_clock_div(baud, self.fpga_clock_frequency, io::F_CLK_BASE_BAUD_DIV)?;
         info!(
             "Setting IP core baud rate @ requested: {}, actual: {}, divisor {:#04x}",
             baud, actual_baud_rate, baud_clock_div
         );
+        self.ip_core_baud_rate
+            .store(actual_baud_rate, Ordering::Relaxed);
+        self.ip_core_baud_rate_off_target.store(
+            self.check_baud_rate("IP core", baud, actual_baud_rate),
+            Ordering::Relaxed,
+        );
 
         self.common_io.set_baud_clock_div(baud_clock_div as u32);
         Ok(())
     }
 
+    /// Warn about baud rate that is off the requested one (but still within the limit accepted
+    /// by `calc_baud_clock_div`) and return whether it is off
+    fn check_baud_rate(&self, what: &str, requested: usize, actual: usize) -> bool {
+        let off_target = baud_rate_off_target(requested, actual);
+        if off_target {
+            warn!(
+                "Chain {}: {} baud rate {} is off the requested {}",
+                self.hashboard_idx, what, actual, requested
+            );
+        }
+        off_target
+    }
+
+    /// Return baud rates actually set on the chips and in the FPGA IP core
+    pub fn get_baud_rates(&self) -> (usize, usize) {
+        (
+            self.chip_baud_rate.load(Ordering::Relaxed),
+            self.ip_core_baud_rate.load(Ordering::Relaxed),
+        )
+    }
+
+    /// Worst difference between requested and achieved chip frequency (in Hz) of the last
+    /// `set_pll`
+    pub fn get_pll_freq_error(&self) -> usize {
+        self.pll_freq_error.load(Ordering::Relaxed)
+    }
+
+    /// Some of the baud rates differs from the requested one more than `BAUD_RATE_WARN_PERC`
+    pub fn is_baud_rate_off_target(&self) -> bool {
+        self.chip_baud_rate_off_target.load(Ordering::Relaxed)
+            || self.ip_core_baud_rate_off_target.load(Ordering::Relaxed)
+    }
+
     pub fn get_chip_count(&self) -> usize {
-        self.chip_count
+        self.chip_count.load(Ordering::Relaxed)
     }
 
     /// Initialize cores by sending open-core work with correct nbits to each core
     async fn send_init_work(&mut self, work_registry: Arc<Mutex<registry::WorkRegistry>>) {
-        // Each core gets one work
-        const NUM_WORK: usize = bm1387::NUM_CORES_ON_CHIP;
+        // Each core gets one work by default
+        let num_work =
+            (bm1387::NUM_CORES_ON_CHIP as f32 * self.init_work_multiplier).round() as usize;
         trace!(
             "Sending out {} pieces of dummy work to initialize chips",
-            NUM_WORK
+            num_work
         );
         let midstate_count = self.midstate_count.to_count();
         let mut work_tx_io = self.work_tx_io.lock().await;
         let tx_fifo = work_tx_io.as_mut().expect("tx fifo missing");
-        for _ in 0..NUM_WORK {
+        for _ in 0..num_work {
             let work = &null_work::prepare_opencore(true, midstate_count);
             // store work to registry as "initial work" so that later we can properly ignore
             // solutions
@@ -654,24 +1529,64 @@ impl HashChain {
     /// It makes sure that TX fifo is empty before requesting work from
     /// generator.
     /// It exits when generator returns `None`.
+    /// This task generates work and sends it to hardware until it's told to stop via
+    /// `stop_work_receiver`. Work that is already in TX FIFO is then left to drain (with
+    /// a bounded wait) and `stopped_sender` is notified.
     async fn work_tx_task(
         work_registry: Arc<Mutex<registry::WorkRegistry>>,
         mut tx_fifo: io::WorkTx,
         mut work_generator: work::Generator,
+        mut stop_work_receiver: watch::Receiver<bool>,
+        stopped_sender: oneshot::Sender<()>,
+        occupancy: Arc<StdMutex<WorkTxOccupancy>>,
     ) {
-        loop {
-            tx_fifo.wait_for_room().await.expect("wait for tx room");
-            let work = work_generator.generate().await;
-            match work {
-                None => return,
-                Some(work) => {
-                    // assign `work_id` to `work`
-                    let work_id = work_registry.lock().await.store_work(work.clone(), false);
-                    // send work is synchronous
-                    tx_fifo.send_work(&work, work_id).expect("send work");
+        {
+            let send_work = async {
+                loop {
+                    let wait_started = Instant::now();
+                    tx_fifo.wait_for_room().await.expect("wait for tx room");
+                    // sample FIFO before it's refilled by the work that is going to be sent
+                    occupancy
+                        .lock()
+                        .expect("BUG: lock failed")
+                        .add_sample(tx_fifo.level(), wait_started.elapsed());
+                    let work = work_generator.generate().await;
+                    match work {
+                        None => return,
+                        Some(mut work) => {
+                            // work is generated for the hashchain with the most midstates,
+                            // use just as many of them as this one supports
+                            work.midstates.truncate(tx_fifo.midstate_count());
+                            // assign `work_id` to `work`
+                            let work_id =
+                                work_registry.lock().await.store_work(work.clone(), false);
+                            // send work is synchronous
+                            tx_fifo.send_work(&work, work_id).expect("send work");
+                        }
+                    }
                 }
-            }
+            };
+            let wait_for_stop = async {
+                while let Some(stop) = stop_work_receiver.recv().await {
+                    if stop {
+                        return;
+                    }
+                }
+                // stop sender is gone, nobody is going to stop us
+                futures::future::pending::<()>().await;
+            };
+            futures::future::select(Box::pin(send_work), Box::pin(wait_for_stop)).await;
         }
+
+        // let the chips pick up work that has already been sent to FIFO
+        if tokio::time::timeout(WORK_TX_DRAIN_TIMEOUT, tx_fifo.wait_empty())
+            .await
+            .is_err()
+        {
+            warn!("Work TX FIFO not drained in {:?}", WORK_TX_DRAIN_TIMEOUT);
+        }
+        // the other side may not be interested anymore
+        let _ = stopped_sender.send(());
     }
 
     /// This task receives solutions from hardware, looks up `Assignment` in
@@ -686,15 +1601,25 @@ impl HashChain {
         work_registry: Arc<Mutex<registry::WorkRegistry>>,
         mut rx_fifo: io::WorkRx,
         solution_sender: work::SolutionSender,
+        solution_pause: Arc<SolutionPause>,
         counter: Arc<Mutex<counters::HashChain>>,
     ) {
+        let mut orphaned_log = OrphanedSolutionLog::new(self.hashboard_idx);
+        let mut unexpected_core_log = LogRateLimiter::new(UNEXPECTED_CORE_LOG_INTERVAL);
+
         // solution receiving/filtering part
         loop {
             let (rx_fifo_out, hw_solution) =
                 rx_fifo.recv_solution().await.expect("recv solution failed");
             rx_fifo = rx_fifo_out;
+            if self.raw_solution_history > 0 {
+                record_raw_solution(
+                    &mut *self.raw_solutions.lock().await,
+                    self.raw_solution_history,
+                    &hw_solution,
+                );
+            }
             let work_id = hw_solution.hardware_id;
-            let solution = Solution::from_hw_solution(&hw_solution, self.asic_target);
             let mut work_registry = work_registry.lock().await;
 
             let work = work_registry.find_work(work_id as usize);
@@ -704,7 +1629,34 @@ impl HashChain {
                     if work_item.initial_work {
                         continue;
                     }
-                    let core_addr = bm1387::CoreAddress::new(solution.nonce);
+                    // validate against target the work has been submitted with
+                    let solution = Solution::from_hw_solution(&hw_solution, work_item.asic_target);
+                    let core_addr =
+                        bm1387::CoreAddress::with_layout(solution.nonce, &self.core_address_layout);
+                    if !self.core_address_layout.contains(core_addr)
+                        || core_addr.chip >= self.get_chip_count()
+                    {
+                        trace!(
+                            "chain {}: solution from unexpected core address {:?} (nonce {:#010x})",
+                            self.hashboard_idx,
+                            core_addr,
+                            solution.nonce
+                        );
+                        if let Some(count) = unexpected_core_log.add() {
+                            info!(
+                                "chain {}: {} solution(s) from unexpected core address since last report (last {:?}, nonce {:#010x}), check core address layout",
+                                self.hashboard_idx, count, core_addr, solution.nonce
+                            );
+                        }
+                    }
+                    if self.excluded_chips.lock().await.contains(&core_addr.chip) {
+                        trace!(
+                            "chain {}: ignoring solution from excluded chip {}",
+                            self.hashboard_idx,
+                            core_addr.chip
+                        );
+                        continue;
+                    }
                     let status = work_item.insert_solution(solution);
 
                     // work item detected a new unique solution, we will push it for further processing
@@ -717,53 +1669,116 @@ impl HashChain {
                             } else {
                                 counter.lock().await.add_valid(core_addr);
                             }
-                            solution_sender.send(unique_solution);
+                            submit_solution(
+                                &solution_pause,
+                                &solution_sender,
+                                &counter,
+                                unique_solution,
+                            )
+                            .await;
                         }
                     }
                     if status.duplicate {
-                        counter.lock().await.add_error(core_addr);
+                        counter.lock().await.add_duplicate(core_addr);
                     }
                     if status.mismatched_nonce {
-                        counter.lock().await.add_error(core_addr);
+                        counter.lock().await.add_mismatched_nonce(core_addr);
                     }
                 }
                 None => {
-                    info!(
+                    trace!(
                         "No work present for solution, ID:{:#x} {:#010x?}",
-                        work_id, solution
+                        work_id,
+                        hw_solution
                     );
+                    counter.lock().await.add_orphaned();
+                    orphaned_log.add();
                 }
             }
         }
     }
 
-    async fn try_to_initialize_sensor(
+    /// Try to find temperature sensors on configured chip, then on other usual candidates
+    async fn try_to_initialize_sensors(&self) -> error::Result<Vec<Box<dyn sensor::Sensor>>> {
+        let mut chip_addresses = vec![self.sensor_chip_address];
+        for &candidate in SENSOR_CHIP_CANDIDATES.iter() {
+            if !chip_addresses.contains(&candidate) {
+                chip_addresses.push(candidate);
+            }
+        }
+
+        let mut last_error = None;
+        for chip_address in chip_addresses {
+            if chip_address >= self.get_chip_count() {
+                continue;
+            }
+            match Self::try_to_initialize_sensors_on_chip(
+                self.command_context.clone(),
+                ChipAddress::One(chip_address),
+            )
+            .await
+            {
+                Ok(sensors) => {
+                    let sensor_info = format!(
+                        "{} on chip {}",
+                        sensors
+                            .iter()
+                            .map(|sensor| sensor.name())
+                            .collect::<Vec<_>>()
+                            .join(", "),
+                        chip_address
+                    );
+                    info!(
+                        "chain {}: temperature sensor(s) responded: {}",
+                        self.hashboard_idx, sensor_info
+                    );
+                    self.sensor_info
+                        .lock()
+                        .expect("BUG: lock failed")
+                        .replace(sensor_info);
+                    return Ok(sensors);
+                }
+                Err(e) => {
+                    warn!(
+                        "chain {}: no temperature sensor on chip {}: {}",
+                        self.hashboard_idx, chip_address, e
+                    );
+                    last_error = Some(e);
+                }
+            }
+        }
+        Err(last_error
+            .unwrap_or_else(|| ErrorKind::Sensors("no sensor chip on chain".into()).into()))
+    }
+
+    async fn try_to_initialize_sensors_on_chip(
         command_context: command::Context,
-    ) -> error::Result<Box<dyn sensor::Sensor>> {
+        chip_address: ChipAddress,
+    ) -> error::Result<Vec<Box<dyn sensor::Sensor>>> {
         // construct I2C bus via command interface
-        let i2c_bus = bm1387::i2c::Bus::new_and_init(command_context, TEMP_CHIP)
+        let i2c_bus = bm1387::i2c::Bus::new_and_init(command_context, chip_address)
             .await
             .with_context(|_| ErrorKind::Sensors("bus construction failed".into()))?;
 
-        // try to probe sensor
-        let sensor = sensor::probe_i2c_sensors(i2c_bus)
+        // try to probe all sensors on the bus
+        let sensors = sensor::probe_all_i2c_sensors(i2c_bus)
             .await
             .with_context(|_| ErrorKind::Sensors("error when probing sensors".into()))?;
 
-        // did we find anything?
-        let mut sensor = match sensor {
-            Some(sensor) => sensor,
-            None => Err(ErrorKind::Sensors("no sensors found".into()))?,
-        };
-
-        // try to initialize sensor
-        sensor
-            .init()
-            .await
-            .with_context(|_| ErrorKind::Sensors("failed to initialize sensors".into()))?;
+        // try to initialize sensors, the ones that fail are left out
+        let mut initialized = Vec::new();
+        for mut sensor in sensors {
+            match sensor.init().await {
+                Ok(()) => initialized.push(sensor),
+                Err(e) => warn!("Failed to initialize temperature sensor: {}", e),
+            }
+        }
 
-        // done
-        Ok(sensor)
+        // did we find anything?
+        if initialized.is_empty() {
+            Err(ErrorKind::Sensors("no sensors found".into()))?
+        }
+        Ok(initialized)
     }
 
     /// Monitor watchdog task.
@@ -790,45 +1805,44 @@ impl HashChain {
         // a hashing chip only if the hashchain allows it (hashchain is in operation etc.)
         delay_for(Duration::from_secs(5)).await;
 
-        // Try to probe sensor
-        // This may fail - in which case we put `None` into `sensor`
-        let mut sensor = match Self::try_to_initialize_sensor(self.command_context.clone())
+        // Try to probe sensors
+        // This may fail - in which case there are no sensors to read
+        let mut sensors: Vec<_> = match self
+            .try_to_initialize_sensors()
             .await
             .with_context(|_| ErrorKind::Hashboard(self.hashboard_idx, "sensor error".into()))
             .map_err(|e| e.into())
         {
             error::Result::Err(e) => {
                 error!("Sensor probing failed: {}", e);
-                None
+                vec![]
             }
-            error::Result::Ok(sensor) => Some(sensor),
+            error::Result::Ok(sensors) => sensors
+                .into_iter()
+                .map(|sensor| {
+                    sensor::CheckedSensor::new(sensor, self.sensor_config.max_read_errors)
+                })
+                .collect(),
         };
 
         // "Watchdog" loop that pings monitor every some seconds
         loop {
-            // If we have temperature sensor, try to read it
-            let temp = if let Some(sensor) = sensor.as_mut() {
-                match sensor
-                    .read_temperature()
-                    .await
-                    .with_context(|_| {
-                        ErrorKind::Hashboard(self.hashboard_idx, "temperature read fail".into())
-                    })
-                    .map_err(|e| e.into())
-                {
-                    error::Result::Ok(temp) => {
-                        info!("Measured temperature: {:?}", temp);
-                        temp
-                    }
-                    error::Result::Err(e) => {
-                        error!("Sensor temperature read failed: {}", e);
-                        sensor::INVALID_TEMPERATURE_READING
-                    }
+            // Read all sensors, each of them keeps its own count of consecutive failures so
+            // that a dead sensor is disabled without affecting the others.
+            // No readings at all let monitor know there's no sensor to wait for.
+            let mut temp = Vec::with_capacity(sensors.len());
+            for (idx, sensor) in sensors.iter_mut().enumerate() {
+                if sensor.is_disabled() {
+                    temp.push(sensor::INVALID_TEMPERATURE_READING);
+                    continue;
                 }
-            } else {
-                // Otherwise just make empty temperature reading
-                sensor::INVALID_TEMPERATURE_READING
-            };
+                let reading = sensor.read().await;
+                info!(
+                    "Chain {}: measured temperature (sensor {}): {:?}",
+                    self.hashboard_idx, idx, reading
+                );
+                temp.push(reading);
+            }
 
             // Broadcast
             temperature_sender
@@ -840,46 +1854,96 @@ impl HashChain {
                 .unbounded_send(monitor::Message::Running(temp))
                 .expect("send failed");
 
-            // TODO: sync this delay with monitor task
-            delay_for(Duration::from_secs(5)).await;
+            delay_for(self.temp_update_interval).await;
         }
     }
 
     /// Hashrate monitor task
-    /// Fetch perodically information about hashrate
-    #[allow(dead_code)]
+    /// Fetch perodically information about hashrate and keep the last snapshot
     async fn hashrate_monitor_task(self: Arc<Self>) {
         info!("Hashrate monitor task started");
+        // report tuning hint only when it changes
+        let mut last_tuning_hint = None;
         loop {
             delay_for(Duration::from_secs(5)).await;
 
-            let responses = self
-                .command_context
-                .read_register::<bm1387::HashrateReg>(ChipAddress::All)
-                .await
-                .expect("reading hashrate_reg failed");
+            let hashrates = match self.read_chip_hashrates().await {
+                Ok(hashrates) => hashrates,
+                Err(e) => {
+                    warn!(
+                        "chain {}: reading hashrate_reg failed: {}",
+                        self.hashboard_idx, e
+                    );
+                    continue;
+                }
+            };
 
             let mut sum = 0;
-            for (chip_address, hashrate_reg) in responses.iter().enumerate() {
+            for (chip_address, hashrate) in hashrates.iter().enumerate() {
                 trace!(
                     "chip {} hashrate {} GHash/s",
                     chip_address,
-                    hashrate_reg.hashrate() as f64 / 1e9
+                    *hashrate as f64 / 1e9
                 );
-                sum += hashrate_reg.hashrate() as u128;
+                sum += *hashrate as u128;
+            }
+            debug!(
+                "chain {}: total chip hashrate {} GH/s",
+                self.hashboard_idx,
+                sum as f64 / 1e9
+            );
+
+            *self.chip_hashrates.lock().await = hashrates;
+
+            let tuning_hint = self.get_work_tx_occupancy().tuning_hint();
+            if tuning_hint != last_tuning_hint {
+                if let Some(hint) = tuning_hint {
+                    warn!("chain {}: {}", self.hashboard_idx, hint);
+                }
+                last_tuning_hint = tuning_hint;
             }
-            info!("Total chip hashrate {} GH/s", sum as f64 / 1e9);
         }
     }
 
+    pub fn get_work_tx_occupancy(&self) -> WorkTxOccupancy {
+        self.work_tx_occupancy
+            .lock()
+            .expect("BUG: lock failed")
+            .clone()
+    }
+
+    /// Read hashrate (in hashes per second) from `HashrateReg` of all chips
+    async fn read_chip_hashrates(&self) -> error::Result<Vec<u64>> {
+        let responses = self
+            .command_context
+            .read_register::<bm1387::HashrateReg>(ChipAddress::All)
+            .await?;
+        Ok(responses
+            .iter()
+            .map(|hashrate_reg| hashrate_reg.hashrate())
+            .collect())
+    }
+
+    /// Return last per-chip hashrate (in hashes per second) reported by chips themselves
+    pub async fn get_chip_hashrates(&self) -> Vec<u64> {
+        self.chip_hashrates.lock().await.clone()
+    }
+
     async fn start(
         self: Arc<Self>,
         work_generator: work::Generator,
         solution_sender: work::SolutionSender,
+        solution_pause: Arc<SolutionPause>,
         work_registry: Arc<Mutex<registry::WorkRegistry>>,
     ) {
+        self.work_registry
+            .lock()
+            .await
+            .replace(work_registry.clone());
+
         // spawn tx task
         let tx_fifo = self.take_work_tx_io().await;
+        let (stop_work_receiver, work_tx_stopped_sender) = self.teardown.work_tx_channels().await;
         self.halt_receiver
             .register_client("work-tx".into())
             .await
@@ -887,6 +1951,9 @@ impl HashChain {
                 work_registry.clone(),
                 tx_fifo,
                 work_generator,
+                stop_work_receiver,
+                work_tx_stopped_sender,
+                self.work_tx_occupancy.clone(),
             ));
 
         // spawn rx task
@@ -899,23 +1966,21 @@ impl HashChain {
                 work_registry.clone(),
                 rx_fifo,
                 solution_sender,
+                solution_pause,
                 self.counter.clone(),
             ));
 
-        // spawn hashrate monitor
-        // Disabled until we found a use for this
-        /*
+        // spawn temperature monitor
         self.halt_receiver
-            .register_client("hashrate monitor".into())
+            .register_client("temperature monitor".into())
             .await
-            .spawn(Self::hashrate_monitor_task(self.clone()));
-        */
+            .spawn(Self::monitor_watchdog_temp_task(self.clone()));
 
-        // spawn temperature monitor
+        // spawn hashrate monitor
         self.halt_receiver
-            .register_client("temperature monitor".into())
+            .register_client("hashrate monitor".into())
             .await
-            .spawn(Self::monitor_watchdog_temp_task(self.clone()));
+            .spawn(Self::hashrate_monitor_task(self.clone()));
     }
 
     pub async fn reset_counter(&self) {
@@ -958,18 +2023,44 @@ pub struct FrequencySettings {
 }
 
 impl FrequencySettings {
-    /// Build frequency settings with all chips having the same frequency
-    pub fn from_frequency(frequency: usize) -> Self {
+    /// Build frequency settings with all `chip_count` chips having the same frequency
+    pub fn from_frequency(frequency: usize, chip_count: usize) -> Self {
         Self {
-            chip: vec![frequency; EXPECTED_CHIPS_ON_CHAIN],
+            chip: vec![frequency; chip_count],
         }
     }
 
+    /// Adjust number of chips. Chips that are added have no frequency set (it's up to the
+    /// caller to fill it in).
     pub fn set_chip_count(&mut self, chip_count: usize) {
-        assert!(self.chip.len() >= chip_count);
         self.chip.resize(chip_count, 0);
     }
 
+    /// List chips `old_chip_count..chip_count` that (re)appeared on the chain together with
+    /// frequency they should be loaded with. Chips that are not covered by these settings get
+    /// the maximum frequency. Nothing is returned when the chain shrunk.
+    pub fn added_chips(&self, old_chip_count: usize, chip_count: usize) -> Vec<(usize, Frequency)> {
+        (old_chip_count..chip_count)
+            .map(|i| (i, self.chip.get(i).cloned().unwrap_or_else(|| self.max())))
+            .collect()
+    }
+
+    /// List chips among the first `chip_count` whose frequency in these (requested) settings
+    /// differs from the `current` one together with the requested frequency. Chips that have no
+    /// `current` frequency (e.g. they haven't been set yet) are always listed.
+    pub fn changed_chips(
+        &self,
+        current: &FrequencySettings,
+        chip_count: usize,
+    ) -> Vec<(usize, Frequency)> {
+        self.chip[..chip_count]
+            .iter()
+            .enumerate()
+            .filter(|(i, frequency)| current.chip.get(*i) != Some(frequency))
+            .map(|(i, frequency)| (i, *frequency))
+            .collect()
+    }
+
     pub fn total(&self) -> u64 {
         self.chip.iter().fold(0, |total_f, &f| total_f + f as u64)
     }
@@ -990,6 +2081,73 @@ impl FrequencySettings {
         (sum / self.chip.len() as u64) as usize
     }
 
+    /// Median of chip frequencies (mean of the two middle ones for even number of chips)
+    pub fn median(&self) -> usize {
+        assert!(self.chip.len() > 0, "BUG: no chips on chain");
+        let mut sorted = self.chip.clone();
+        sorted.sort_unstable();
+        let middle = sorted.len() / 2;
+        if sorted.len() % 2 == 0 {
+            ((sorted[middle - 1] as u64 + sorted[middle] as u64) / 2) as usize
+        } else {
+            sorted[middle]
+        }
+    }
+
+    /// Population standard deviation of chip frequencies
+    pub fn std_dev(&self) -> f32 {
+        assert!(self.chip.len() > 0, "BUG: no chips on chain");
+        let count = self.chip.len() as f64;
+        let mean = self.total() as f64 / count;
+        let variance = self
+            .chip
+            .iter()
+            .map(|&frequency| (frequency as f64 - mean).powi(2))
+            .sum::<f64>()
+            / count;
+        variance.sqrt() as f32
+    }
+
+    /// Check that frequencies of the first `chip_count` chips can be set by some PLL divider
+    /// from `table` with error not exceeding `tolerance` (in Hz) and return the biggest error
+    pub fn pll_error(
+        &self,
+        table: &bm1387::PllTable,
+        chip_count: usize,
+        tolerance: usize,
+    ) -> Result<usize, String> {
+        let mut max_error = 0;
+        for (idx, &frequency) in self.chip[..chip_count].iter().enumerate() {
+            let freq_error = bm1387::PllFrequency::lookup_freq_in(table, frequency)
+                .map_err(|_| {
+                    format!(
+                        "chip {}: frequency {} has no PLL setting",
+                        idx,
+                        Self::pretty_frequency(frequency)
+                    )
+                })?
+                .freq_error(frequency);
+            if freq_error > tolerance {
+                return Err(format!(
+                    "chip {}: frequency {} is {} MHz off the nearest PLL setting",
+                    idx,
+                    Self::pretty_frequency(frequency),
+                    freq_error as f64 / 1_000_000.0
+                ));
+            }
+            max_error = max_error.max(freq_error);
+        }
+        Ok(max_error)
+    }
+
+    /// Check that every chip frequency can be set by some PLL divider from `table` with error
+    /// not exceeding `tolerance` (in Hz)
+    pub fn validate(&self, table: &bm1387::PllTable, tolerance: usize) -> error::Result<()> {
+        self.pll_error(table, self.chip.len(), tolerance)
+            .map_err(ErrorKind::PLL)?;
+        Ok(())
+    }
+
     fn pretty_frequency(freq: usize) -> String {
         format!("{:.01} MHz", (freq as f32) / 1_000_000.0)
     }
@@ -1016,22 +2174,32 @@ impl fmt::Display for FrequencySettings {
 #[derive(Debug)]
 pub struct StoppedChain {
     pub manager: Arc<Manager>,
+    /// Ownership of `manager`, it's released when the chain is dropped
+    ownership: ownership::Guard,
 }
 
-impl Drop for StoppedChain {
-    fn drop(&mut self) {
-        // remove ownership in case we are dropped
-        self.manager
-            .owned_by
-            .lock()
-            .expect("BUG: lock failed")
-            .take();
+/// Wait for `delay` unless halt is signalled sooner via `halted_receiver`
+///
+/// Returns `true` if the wait has been interrupted by halt.
+async fn delay_unless_halted(delay: Duration, mut halted_receiver: watch::Receiver<bool>) -> bool {
+    let wait_for_halt = async move {
+        while let Some(halted) = halted_receiver.recv().await {
+            if halted {
+                return;
+            }
+        }
+        // halt sender is gone, there's nobody to halt us
+        futures::future::pending::<()>().await;
+    };
+    match futures::future::select(Box::pin(delay_for(delay)), Box::pin(wait_for_halt)).await {
+        futures::future::Either::Left(_) => false,
+        futures::future::Either::Right(_) => true,
     }
 }
 
 impl StoppedChain {
-    pub fn from_manager(manager: Arc<Manager>) -> Self {
-        StoppedChain { manager }
+    pub fn from_manager(manager: Arc<Manager>, ownership: ownership::Guard) -> Self {
+        StoppedChain { manager, ownership }
     }
 
     pub async fn start(
@@ -1041,21 +2209,42 @@ impl StoppedChain {
         asic_difficulty: usize,
     ) -> Result<RunningChain, (Self, error::Error)> {
         // if miner initialization fails, retry
-        let mut tries_left = ENUM_RETRY_COUNT;
+        let retry_policy = self.manager.chain_config.enum_retry_policy;
+        let short_chain_policy = self.manager.chain_config.short_chain_policy;
+        let mut tries_left = retry_policy.retry_count;
 
         loop {
+            if self.manager.is_halted() {
+                let e = ErrorKind::Hashboard(
+                    self.manager.hashboard_idx,
+                    "halted while starting".into(),
+                );
+                return Err((self, e.into()));
+            }
+            if self.manager.is_quarantined() {
+                let e = ErrorKind::Hashboard(
+                    self.manager.hashboard_idx,
+                    "quarantined after repeated enumeration failures".into(),
+                );
+                return Err((self, e.into()));
+            }
+            if !self.manager.is_enabled().await {
+                let e = ErrorKind::Hashboard(self.manager.hashboard_idx, "disabled".into());
+                return Err((self, e.into()));
+            }
+
             info!(
                 "Registering hashboard {} with monitor",
                 self.manager.hashboard_idx
             );
 
             // Start this hashchain
-            // If we've already exhausted half of our tries, then stop worrying about having
-            // less chips than expected (63).
+            // Short chain policy decides whether to stop worrying about having less chips than
+            // expected (63).
             match self
                 .manager
                 .attempt_start_chain(
-                    tries_left <= ENUM_RETRY_COUNT / 2,
+                    short_chain_policy.accept(retry_policy.retry_count - tries_left),
                     initial_frequency,
                     initial_voltage,
                     asic_difficulty,
@@ -1064,11 +2253,22 @@ impl StoppedChain {
             {
                 // start successful
                 Ok(_) => {
+                    let inner = self.manager.inner.lock().await;
+                    // hashchain may have been stopped by halt right after it started
+                    if inner.hash_chain.is_none() {
+                        drop(inner);
+                        let e = ErrorKind::Hashboard(
+                            self.manager.hashboard_idx,
+                            "halted while starting".into(),
+                        );
+                        return Err((self, e.into()));
+                    }
                     // we've started the hashchain
                     // create a `Running` tape and be gone
                     return Ok(RunningChain::from_manager(
                         self.manager.clone(),
-                        self.manager.inner.lock().await,
+                        inner,
+                        self.ownership,
                     ));
                 }
                 // start failed
@@ -1080,16 +2280,41 @@ impl StoppedChain {
                         error!("No tries left");
                         return Err((self, e.into()));
                     } else {
+                        let delay = retry_policy.delay(retry_policy.retry_count - tries_left);
                         tries_left -= 1;
-                        // TODO: wait with locks unlocked()! Otherwise no-one can halt the miner
-                        // This is not possible with current lock design, but fix this ASAP!
-                        delay_for(ENUM_RETRY_DELAY).await;
-                        info!("Retrying chain {} start...", self.manager.hashboard_idx);
+                        // `attempt_start_chain` releases `inner` when it returns, wait without
+                        // any lock held so that halt can get through
+                        if delay_unless_halted(delay, self.manager.halted_receiver.clone()).await {
+                            info!(
+                                "Chain {} start interrupted by halt",
+                                self.manager.hashboard_idx
+                            );
+                            return Err((self, e.into()));
+                        }
+                        info!(
+                            "Retrying chain {} start (after {}s)...",
+                            self.manager.hashboard_idx,
+                            delay.as_secs()
+                        );
                     }
                 }
             }
         }
     }
+
+    /// Run one-shot diagnostic of the hashchain without mining on it
+    ///
+    /// The hashchain is reset and enumerated, sensors, hashrate registers and baud rates
+    /// are read once and then it's halted again. Neither monitor nor work pipeline is involved.
+    pub async fn self_test(self) -> error::Result<SelfTestReport> {
+        if self.manager.is_halted() {
+            Err(ErrorKind::Hashboard(
+                self.manager.hashboard_idx,
+                "halted".into(),
+            ))?
+        }
+        self.manager.self_test_chain().await
+    }
 }
 
 #[derive(Debug)]
@@ -1097,38 +2322,43 @@ pub struct RunningChain {
     pub manager: Arc<Manager>,
     pub start_id: usize,
     pub asic_difficulty: usize,
-}
-
-impl Drop for RunningChain {
-    fn drop(&mut self) {
-        // remove ownership in case we are dropped
-        self.manager
-            .owned_by
-            .lock()
-            .expect("BUG: lock failed")
-            .take();
-    }
+    /// When the hashchain was started
+    pub running_since: Instant,
+    /// Ownership of `manager`, it's released when the chain is dropped
+    ownership: ownership::Guard,
 }
 
 impl RunningChain {
-    pub fn from_manager(manager: Arc<Manager>, inner: MutexGuard<ManagerInner>) -> Self {
+    pub fn from_manager(
+        manager: Arc<Manager>,
+        inner: MutexGuard<ManagerInner>,
+        ownership: ownership::Guard,
+    ) -> Self {
         let hash_chain = inner
             .hash_chain
             .as_ref()
             .expect("BUG: hashchain is not running");
         RunningChain {
             manager: manager.clone(),
-            asic_difficulty: hash_chain.asic_difficulty,
+            asic_difficulty: hash_chain.get_asic_difficulty(),
             start_id: inner.start_count,
+            running_since: inner
+                .running_since
+                .expect("BUG: hashchain start time is missing"),
+            ownership,
         }
     }
 
+    /// How long has the hashchain been running
+    pub fn uptime(&self) -> Duration {
+        self.running_since.elapsed()
+    }
+
     pub async fn stop(self) -> StoppedChain {
         self.manager.stop_chain(false).await;
 
-        StoppedChain {
-            manager: self.manager.clone(),
-        }
+        // pass the ownership along so that it's not released in between
+        StoppedChain::from_manager(self.manager, self.ownership)
     }
 
     /// TODO: for the love of god use macros or something
@@ -1163,7 +2393,21 @@ impl RunningChain {
             .await
     }
 
+    /// Re-enumerate chips without restarting the chain. Chips that reappeared get frequency
+    /// from chain configuration.
+    pub async fn reenumerate(&self) -> error::Result<usize> {
+        let inner = self.manager.inner.lock().await;
+        inner
+            .hash_chain
+            .as_ref()
+            .expect("BUG: hashchain is not running")
+            .reenumerate(&self.manager.chain_config.frequency)
+            .await
+    }
+
+    /// Set working voltage, requests outside of configured bounds are rejected
     pub async fn set_voltage(&self, voltage: power::Voltage) -> error::Result<()> {
+        self.manager.chain_config.voltage_bounds.check(voltage)?;
         let inner = self.manager.inner.lock().await;
         inner
             .hash_chain
@@ -1174,6 +2418,19 @@ impl RunningChain {
             .await
     }
 
+    /// Change ASIC difficulty, see `HashChain::set_asic_difficulty`
+    ///
+    /// Note: `asic_difficulty` of this instance keeps the value it was created with
+    pub async fn set_asic_difficulty(&self, difficulty: usize) -> error::Result<()> {
+        let inner = self.manager.inner.lock().await;
+        inner
+            .hash_chain
+            .as_ref()
+            .expect("BUG: hashchain is not running")
+            .set_asic_difficulty(difficulty)
+            .await
+    }
+
     pub async fn reset_counter(&self) {
         self.manager
             .inner
@@ -1198,7 +2455,105 @@ impl RunningChain {
             .await
     }
 
-    pub async fn current_temperature(&self) -> Option<sensor::Temperature> {
+    /// Hashrate estimated from valid solutions since the last `reset_counter` (see
+    /// `counters::HashChain::estimated_hashrate`)
+    pub async fn estimated_hashrate(&self) -> f64 {
+        let snapshot = self.snapshot_counter().await;
+        snapshot.estimated_hashrate(snapshot.duration())
+    }
+
+    /// Baud rates actually set on the chips and in the FPGA IP core
+    pub async fn baud_rates(&self) -> (usize, usize) {
+        self.manager
+            .inner
+            .lock()
+            .await
+            .hash_chain
+            .as_ref()
+            .expect("BUG: hashchain is not running")
+            .get_baud_rates()
+    }
+
+    /// Temperature sensors found on hashchain (`None` if they haven't been probed yet or none
+    /// was found)
+    pub async fn sensor_info(&self) -> Option<String> {
+        self.manager
+            .inner
+            .lock()
+            .await
+            .hash_chain
+            .as_ref()
+            .expect("BUG: hashchain is not running")
+            .get_sensor_info()
+    }
+
+    /// Worst difference between requested and achieved chip frequency (in Hz)
+    pub async fn pll_freq_error(&self) -> usize {
+        self.manager
+            .inner
+            .lock()
+            .await
+            .hash_chain
+            .as_ref()
+            .expect("BUG: hashchain is not running")
+            .get_pll_freq_error()
+    }
+
+    /// Work TX FIFO level statistics
+    pub async fn work_tx_occupancy(&self) -> WorkTxOccupancy {
+        self.manager
+            .inner
+            .lock()
+            .await
+            .hash_chain
+            .as_ref()
+            .expect("BUG: hashchain is not running")
+            .get_work_tx_occupancy()
+    }
+
+    /// Error ratio of each core computed from counter snapshot (see
+    /// `counters::HashChain::core_error_rates`)
+    pub async fn core_error_rates(&self) -> Vec<(bm1387::CoreAddress, f64)> {
+        self.snapshot_counter().await.core_error_rates()
+    }
+
+    pub async fn raw_solutions(&self) -> Vec<io::Solution> {
+        self.manager
+            .inner
+            .lock()
+            .await
+            .hash_chain
+            .as_ref()
+            .expect("not running")
+            .get_raw_solutions()
+            .await
+    }
+
+    pub async fn chip_map(&self) -> Vec<ChipEnumStatus> {
+        self.manager
+            .inner
+            .lock()
+            .await
+            .hash_chain
+            .as_ref()
+            .expect("not running")
+            .get_chip_map()
+            .await
+    }
+
+    pub async fn chip_hashrates(&self) -> Vec<u64> {
+        self.manager
+            .inner
+            .lock()
+            .await
+            .hash_chain
+            .as_ref()
+            .expect("not running")
+            .get_chip_hashrates()
+            .await
+    }
+
+    pub async fn current_temperature(&self) -> Option<Vec<sensor::Temperature>> {
         self.manager
             .inner
             .lock()
@@ -1210,16 +2565,19 @@ impl RunningChain {
     }
 
     /// Check from `Monitor` status message if miner is hot enough
-    /// Also: this will break if there are no temperature sensors
-    fn preheat_ok(status: monitor::Status) -> bool {
-        const PREHEAT_TEMP_EPSILON: f32 = 2.0;
+    /// Preheat is not applicable (and it's OK) when hashboards have no temperature sensors.
+    ///
+    /// * `temp_epsilon` - temperature this much below target is considered hot enough
+    fn preheat_ok(status: monitor::Status, temp_epsilon: f32) -> bool {
         let target_temp;
         // check if we are in PID mode, otherwise return `true`
-        match status.config.fan_config {
+        match status.config.fan_config() {
             // Can't preheat if we are not controlling fans
             None => return true,
             Some(fan_config) => match fan_config.mode {
-                monitor::FanControlMode::TargetTemperature(t) => target_temp = t,
+                monitor::FanControlMode::TargetTemperature { target_temp: t, .. } => {
+                    target_temp = t
+                }
                 _ => return true,
             },
         }
@@ -1230,11 +2588,16 @@ impl RunningChain {
         // we are in PID mode, check if temperature is OK
         match status.input_temperature {
             monitor::ChainTemperature::Ok(t) => {
-                if t >= target_temp || target_temp - t < PREHEAT_TEMP_EPSILON {
+                if t >= target_temp || target_temp - t < temp_epsilon {
                     info!("Preheat: temperature {} is hot enough", t);
                     return true;
                 }
             }
+            // temperature will stay unknown forever, there's nothing to wait for
+            monitor::ChainTemperature::Unknown if status.no_sensors => {
+                info!("Preheat: no temperature sensors, skipping preheat");
+                return true;
+            }
             _ => (),
         }
         return false;
@@ -1242,28 +2605,44 @@ impl RunningChain {
 
     /// Wait for hashboard to reach PID-defined temperature (or higher)
     /// If monitor isn't in PID mode then this is effectively no-op.
-    /// Wait at most predefined number of seconds to avoid any kind of dead-locks.
+    /// Wait at most `ResolvedChainConfig::preheat_timeout` to avoid any kind of dead-locks.
+    /// Preheat can be disabled completely in configuration.
     ///
     /// Note: we have to lock it on the inside, because otherwise we would hold lock on hashchain
     /// manager and prevent shutdown from happening.
-    pub async fn wait_for_preheat(&self) {
-        const MAX_PREHEAT_DELAY: u64 = 180;
+    ///
+    /// Returns `true` if the wait has been interrupted by halt.
+    pub async fn wait_for_preheat(&self) -> bool {
+        let chain_config = &self.manager.chain_config;
+        if !chain_config.preheat {
+            info!("Preheat: disabled in configuration, skipping preheat");
+            return false;
+        }
 
-        let mut status_receiver = self.manager.status_receiver.clone();
+        let mut status_stream = self.manager.status_stream.clone();
+        let temp_epsilon = chain_config.preheat_temp_epsilon;
         // wait for status from monitor
-        let started = Instant::now();
-        // TODO: wrap `status_receiver` into some kind of API
-        while let Some(status) = status_receiver.next().await {
-            // take just non-empty status messages
-            if let Some(status) = status {
-                if Self::preheat_ok(status) {
+        let wait_for_temp = async move {
+            while let Some(status) = status_stream.next().await {
+                if Self::preheat_ok(status, temp_epsilon) {
                     break;
                 }
             }
-            // in case we are waiting for too long, just skip preheat
-            if Instant::now().duration_since(started).as_secs() >= MAX_PREHEAT_DELAY {
+        };
+        // in case we are waiting for too long, just skip preheat
+        let wait_for_timeout = delay_unless_halted(
+            chain_config.preheat_timeout,
+            self.manager.halted_receiver.clone(),
+        );
+        match futures::future::select(Box::pin(wait_for_temp), Box::pin(wait_for_timeout)).await {
+            futures::future::Either::Left(_) => false,
+            futures::future::Either::Right((true, _)) => {
+                info!("Preheat: interrupted by halt");
+                true
+            }
+            futures::future::Either::Right((false, _)) => {
                 info!("Preheat: waiting too long to heat-up, skipping preheat");
-                return;
+                false
             }
         }
     }
@@ -1287,6 +2666,18 @@ pub struct ManagerInner {
     pub hash_chain: Option<Arc<HashChain>>,
     /// Each (attempted) hashchain start increments this counter by 1
     pub start_count: usize,
+    /// When the currently running hashchain was started
+    pub running_since: Option<Instant>,
+    /// Hashchain has not been administratively disabled and may be started
+    pub enabled: bool,
+}
+
+impl ManagerInner {
+    /// How long has the current hashchain been running (`None` when it's stopped)
+    pub fn uptime(&self) -> Option<Duration> {
+        self.running_since
+            .map(|running_since| running_since.elapsed())
+    }
 }
 
 /// Hashchain manager that can start and stop instances of hashchain
@@ -1299,15 +2690,27 @@ pub struct Manager {
     pub hashboard_idx: usize,
     work_generator: work::Generator,
     solution_sender: work::SolutionSender,
+    /// Shared switch for pausing solution submission
+    pub solution_pause: Arc<SolutionPause>,
     plug_pin: PlugPin,
     reset_pin: ResetPin,
     voltage_ctrl_backend: Arc<power::I2cBackend>,
     midstate_count: MidstateCount,
     /// channel to report to the monitor
     monitor_tx: mpsc::UnboundedSender<monitor::Message>,
-    /// TODO: wrap this type in a structure (in Monitor)
-    pub status_receiver: watch::Receiver<Option<monitor::Status>>,
-    owned_by: StdMutex<Option<&'static str>>,
+    /// Status updates from monitor
+    pub status_stream: monitor::StatusStream,
+    /// Who has acquired this hashchain (see `Manager::acquire`)
+    ownership: ownership::Lock,
+    /// Number of consecutive failed hashchain initializations
+    enumeration_failures: AtomicUsize,
+    /// Hashchain failed too many times and it's kept powered off
+    quarantined: AtomicBool,
+    /// Set to `true` when the manager is halted (so that pending start is aborted)
+    halted_sender: watch::Sender<bool>,
+    halted_receiver: watch::Receiver<bool>,
+    /// Termination context of this hashchain (it remembers why it was halted)
+    chain_halt_sender: Arc<halt::Sender>,
     pub inner: Mutex<ManagerInner>,
     pub chain_config: config::ResolvedChainConfig,
 }
@@ -1319,22 +2722,21 @@ impl Manager {
         owner_name: &'static str,
     ) -> Result<ChainStatus, &'static str> {
         // acquire ownership of the hashchain
-        {
-            let mut owned_by = self.owned_by.lock().expect("BUG: failed to lock mutex");
-            if let Some(already_owned_by) = *owned_by {
-                return Err(already_owned_by);
-            }
-            owned_by.replace(owner_name);
-        }
+        let ownership = self.ownership.try_acquire(owner_name)?;
         // Create a `Chain` instance. If it's dropped, the ownership reverts back to `Manager`
         let inner = self.inner.lock().await;
         Ok(if inner.hash_chain.is_some() {
-            ChainStatus::Running(RunningChain::from_manager(self.clone(), inner))
+            ChainStatus::Running(RunningChain::from_manager(self.clone(), inner, ownership))
         } else {
-            ChainStatus::Stopped(StoppedChain::from_manager(self.clone()))
+            ChainStatus::Stopped(StoppedChain::from_manager(self.clone(), ownership))
         })
     }
 
+    /// Return name of the current owner of this hashchain (if any)
+    pub fn owned_by(&self) -> Option<&'static str> {
+        self.ownership.owned_by()
+    }
+
     /// Initialize and start mining on hashchain
     /// TODO: this function is private and should be called only from `Stopped`
     async fn attempt_start_chain(
@@ -1367,9 +2769,23 @@ impl Manager {
             self.hashboard_idx,
             self.midstate_count,
             asic_difficulty,
+            self.chain_config.core_address_layout,
+            self.chain_config.sensor_chip_address,
             self.monitor_tx.clone(),
         )
         .expect("BUG: hashchain instantiation failed");
+        hash_chain.set_raw_solution_history(self.chain_config.raw_solution_history);
+        hash_chain.set_chip_revision_policy(self.chain_config.chip_revision_policy);
+        hash_chain.set_heart_beat_period(self.chain_config.heart_beat_period);
+        hash_chain.set_init_work_multiplier(self.chain_config.init_work_multiplier);
+        hash_chain.set_voltage_ramp(self.chain_config.voltage_ramp);
+        hash_chain.set_fpga_clock_frequency(self.chain_config.fpga_clock_frequency);
+        hash_chain.set_init_delay(self.chain_config.init_delay);
+        hash_chain.set_expected_chip_count(self.chain_config.expected_chip_count);
+        hash_chain.set_sensor_config(self.chain_config.sensor_config);
+        hash_chain.set_temp_update_interval(self.chain_config.temp_update_interval);
+        hash_chain.set_pll_freq_error_tolerance(self.chain_config.pll_freq_error_tolerance);
+        hash_chain.set_work_delay_fudge(self.chain_config.work_delay_fudge);
 
         // initialize it
         let work_registry = match hash_chain
@@ -1377,6 +2793,19 @@ impl Manager {
             .await
         {
             Err(e) => {
+                let failures = self.enumeration_failures.fetch_add(1, Ordering::Relaxed) + 1;
+                let max_failures = self.chain_config.max_enumeration_failures;
+                if max_failures > 0 && failures >= max_failures {
+                    // do not power up a board that may be damaged over and over again
+                    error!(
+                        "Chain {}: {} consecutive enumeration failures, powering it off",
+                        self.hashboard_idx, failures
+                    );
+                    hash_chain.power_off().await;
+                    self.quarantined.store(true, Ordering::Relaxed);
+                } else {
+                    hash_chain.shutdown().await;
+                }
                 // halt is required to stop voltage heart-beat task
                 hash_chain.halt_sender.clone().send_halt().await;
                 // deregister us
@@ -1388,6 +2817,7 @@ impl Manager {
             }
             Ok(a) => a,
         };
+        self.enumeration_failures.store(0, Ordering::Relaxed);
 
         // spawn worker tasks for hash chain and start mining
         let hash_chain = Arc::new(hash_chain);
@@ -1396,16 +2826,54 @@ impl Manager {
             .start(
                 self.work_generator.clone(),
                 self.solution_sender.clone(),
+                self.solution_pause.clone(),
                 work_registry,
             )
             .await;
 
         // remember we started
         inner.hash_chain.replace(hash_chain);
+        inner.running_since.replace(Instant::now());
 
         Ok(())
     }
 
+    /// Create hashchain just for the purpose of `StoppedChain::self_test`
+    async fn self_test_chain(&self) -> error::Result<SelfTestReport> {
+        // hold inner so that nobody starts the hashchain meanwhile
+        let inner = self.inner.lock().await;
+        assert!(inner.hash_chain.is_none());
+
+        let mut hash_chain = HashChain::new(
+            self.reset_pin.clone(),
+            self.plug_pin.clone(),
+            self.voltage_ctrl_backend.clone(),
+            self.hashboard_idx,
+            self.midstate_count,
+            config::DEFAULT_ASIC_DIFFICULTY,
+            self.chain_config.core_address_layout,
+            self.chain_config.sensor_chip_address,
+            self.monitor_tx.clone(),
+        )?;
+        // chips with unexpected revision are flagged in the report instead
+        hash_chain.set_chip_revision_policy(config::ChipRevisionPolicy::Warn);
+        hash_chain.set_heart_beat_period(self.chain_config.heart_beat_period);
+        hash_chain.set_fpga_clock_frequency(self.chain_config.fpga_clock_frequency);
+        hash_chain.set_init_delay(self.chain_config.init_delay);
+        hash_chain.set_expected_chip_count(self.chain_config.expected_chip_count);
+        hash_chain.set_sensor_config(self.chain_config.sensor_config);
+        hash_chain.set_pll_freq_error_tolerance(self.chain_config.pll_freq_error_tolerance);
+
+        let report = hash_chain.self_test(&self.chain_config.frequency).await;
+
+        // power the hashboard off and stop voltage heart-beat task
+        hash_chain.shutdown().await;
+        hash_chain.halt_sender.clone().send_halt().await;
+        drop(inner);
+
+        report
+    }
+
     /// TODO: this function is private and should be called only from `RunningChain`
     async fn stop_chain(&self, its_ok_if_its_missing: bool) {
         // lock inner to guarantee atomicity of hashchain stop
@@ -1413,13 +2881,15 @@ impl Manager {
 
         // TODO: maybe we should throw an error instead
         let hash_chain = inner.hash_chain.take();
+        inner.running_since.take();
         if hash_chain.is_none() && its_ok_if_its_missing {
             return;
         }
         let hash_chain = hash_chain.expect("BUG: hashchain is missing");
 
-        // stop everything
-        hash_chain.halt_sender.clone().send_halt().await;
+        // stop feeding chips with work and let the work in flight drain, then cut the voltage
+        // and stop everything
+        hash_chain.stop().await;
 
         // tell monitor we are done
         self.monitor_tx
@@ -1428,8 +2898,53 @@ impl Manager {
     }
 
     async fn termination_handler(self: Arc<Self>) {
+        // abort start of hashchain that may be in progress
+        self.halted_sender
+            .broadcast(true)
+            .expect("BUG: halted receiver missing");
+        self.stop_chain(true).await;
+    }
+
+    /// Manager has been halted, hashchain must not be started anymore
+    pub fn is_halted(&self) -> bool {
+        *self.halted_receiver.borrow()
+    }
+
+    /// Why was this hashchain (or the whole miner) halted the last time
+    pub async fn last_halt_reason(&self) -> Option<String> {
+        self.chain_halt_sender.last_halt_reason().await
+    }
+
+    /// Hashchain failed to enumerate too many times and is kept powered off
+    pub fn is_quarantined(&self) -> bool {
+        self.quarantined.load(Ordering::Relaxed)
+    }
+
+    /// Allow quarantined hashchain to be started again (e.g. after the board was replaced)
+    pub fn release_quarantine(&self) {
+        self.enumeration_failures.store(0, Ordering::Relaxed);
+        self.quarantined.store(false, Ordering::Relaxed);
+    }
+
+    /// Hashchain may be started (it hasn't been disabled by operator)
+    pub async fn is_enabled(&self) -> bool {
+        self.inner.lock().await.enabled
+    }
+
+    /// Stop the hashchain (if running) and keep it stopped until `enable` is called
+    pub async fn disable(&self) {
+        self.inner.lock().await.enabled = false;
+        info!("Chain {}: disabled", self.hashboard_idx);
         self.stop_chain(true).await;
     }
+
+    /// Allow disabled hashchain to be started again
+    ///
+    /// The hashchain is not started by this method, it's up to the caller to do so.
+    pub async fn enable(&self) {
+        self.inner.lock().await.enabled = true;
+        info!("Chain {}: enabled", self.hashboard_idx);
+    }
 }
 
 #[async_trait]
@@ -1522,22 +3037,72 @@ impl Backend {
     }
 
     /// Enumerate present hashboards by querying the plug pin
-    pub fn detect_hashboards(gpio_mgr: &gpio::ControlPinManager) -> error::Result<Vec<usize>> {
+    ///
+    /// Only `slots` are probed. Slots with plug pin that cannot be opened or read are skipped,
+    /// so that one flaky slot doesn't prevent the rest of the miner from running.
+    pub fn detect_hashboards(gpio_mgr: &gpio::ControlPinManager, slots: &[usize]) -> Vec<usize> {
         let mut detected = vec![];
-        // TODO: configure this range somewhere
-        for hashboard_idx in 1..=8 {
-            let plug_pin = PlugPin::open(gpio_mgr, hashboard_idx)?;
-            if plug_pin.hashboard_present()? {
-                detected.push(hashboard_idx);
+        for &hashboard_idx in slots {
+            let present = PlugPin::open(gpio_mgr, hashboard_idx)
+                .and_then(|plug_pin| plug_pin.hashboard_present());
+            match present {
+                Ok(true) => detected.push(hashboard_idx),
+                Ok(false) => {}
+                Err(e) => warn!("Skipping hashboard slot {}: {}", hashboard_idx, e),
+            }
+        }
+        detected
+    }
+
+    /// Check that IP core of each hashboard supports configured number of midstates
+    ///
+    /// Hashchain with midstate count unsupported by the FPGA bitstream would be started just fine
+    /// but it wouldn't produce valid solutions.
+    fn check_midstate_count(
+        backend_config: &config::Backend,
+        hashboards: &[usize],
+    ) -> error::Result<()> {
+        for &hashboard_idx in hashboards {
+            let midstate_count = backend_config
+                .resolve_chain_config(hashboard_idx)?
+                .midstate_count;
+            let supported_counts =
+                io::Core::new(hashboard_idx, midstate_count)?.supported_midstate_counts();
+            if !supported_counts.contains(&midstate_count.to_count()) {
+                Err(ErrorKind::Hashboard(
+                    hashboard_idx,
+                    format!(
+                        "midstate count {} is not supported by s9-io (supported: {:?})",
+                        midstate_count.to_count(),
+                        supported_counts
+                    ),
+                ))?
             }
         }
-        Ok(detected)
+        Ok(())
     }
 
     /// Miner termination handler called when app is shutdown.
-    /// Just propagate the shutdown to all hashchain managers
-    async fn termination_handler(halt_sender: Arc<halt::Sender>) {
-        halt_sender.send_halt().await;
+    /// Just propagate the shutdown (along with its reason) to all hashchain managers
+    async fn termination_handler(halt_sender: Arc<halt::Sender>, parent: Arc<halt::Sender>) {
+        match parent.last_halt_reason().await {
+            Some(reason) => halt_sender.send_halt_with_reason(reason).await,
+            None => halt_sender.send_halt().await,
+        }
+    }
+
+    /// Miner termination handler, in addition to `termination_handler` it closes I2C controller
+    /// of voltage controllers once all hashchains are stopped. The controller is shared by all
+    /// hashchains, so it cannot be closed when just one of them is halted.
+    async fn miner_termination_handler(
+        halt_sender: Arc<halt::Sender>,
+        parent: Arc<halt::Sender>,
+        voltage_ctrl_backend: Arc<power::I2cBackend>,
+    ) {
+        Self::termination_handler(halt_sender, parent).await;
+        if let Err(e) = voltage_ctrl_backend.close().await {
+            error!("Failed to close voltage controller I2C: {}", e);
+        }
     }
 
     /// Start miner
@@ -1549,19 +3114,26 @@ impl Backend {
         backend_config: config::Backend,
         app_halt_receiver: halt::Receiver,
         app_halt_sender: Arc<halt::Sender>,
-    ) -> (Vec<Arc<Manager>>, Arc<monitor::Monitor>) {
+        solution_pause: Arc<SolutionPause>,
+    ) -> error::Result<(Vec<Arc<Manager>>, Arc<monitor::Monitor>)> {
         // Create hooks
         let hooks = match backend_config.hooks.as_ref() {
             Some(hooks) => hooks.clone(),
             None => Arc::new(hooks::NoHooks),
         };
 
+        let voltage_ctrl_backend = Arc::new(power::I2cBackend::new(0));
+
         // Create new termination context and link it to the main (app) termination context
         let (halt_sender, halt_receiver) = halt::make_pair(HALT_TIMEOUT);
         app_halt_receiver
             .register_client("miner termination".into())
             .await
-            .spawn_halt_handler(Self::termination_handler(halt_sender.clone()));
+            .spawn_halt_handler(Self::miner_termination_handler(
+                halt_sender.clone(),
+                app_halt_sender.clone(),
+                voltage_ctrl_backend.clone(),
+            ));
         hooks
             .halt_created(
                 halt_sender.clone(),
@@ -1574,15 +3146,19 @@ impl Backend {
         // Let it shutdown the main context as well
         let monitor_config = backend_config.resolve_monitor_config();
         info!("Resolved monitor backend_config: {:?}", monitor_config);
+        monitor_config
+            .timeouts
+            .validate()
+            .map_err(ErrorKind::General)?;
         let monitor = monitor::Monitor::new_and_start(
             monitor_config,
+            hooks.power_meter(),
             app_halt_sender.clone(),
             app_halt_receiver.clone(),
         )
         .await;
         hooks.monitor_started(monitor.clone()).await;
 
-        let voltage_ctrl_backend = Arc::new(power::I2cBackend::new(0));
         let mut managers = Vec::new();
         info!(
             "Initializing miner, enabled_chains={:?}, midstate_count={}",
@@ -1591,12 +3167,26 @@ impl Backend {
         );
         // build all hash chain managers and register ourselves with frontend
         for hashboard_idx in enabled_chains {
+            // Each hashchain has its own termination context linked to the miner one, so that
+            // monitor can halt just this hashchain
+            let (chain_halt_sender, chain_halt_receiver) = halt::make_pair(HALT_TIMEOUT);
+            halt_receiver
+                .register_client("hashchain termination".into())
+                .await
+                .spawn_halt_handler(Self::termination_handler(
+                    chain_halt_sender.clone(),
+                    halt_sender.clone(),
+                ));
+
             // register monitor for this haschain
-            let monitor_tx = monitor.register_hashchain(hashboard_idx).await;
+            let monitor_tx = monitor
+                .register_hashchain(hashboard_idx, chain_halt_sender.clone())
+                .await;
             // make pins
-            let chain_config = backend_config.resolve_chain_config(hashboard_idx);
+            let chain_config = backend_config.resolve_chain_config(hashboard_idx)?;
 
-            let status_receiver = monitor.status_receiver.clone();
+            let status_stream = monitor.subscribe();
+            let (halted_sender, halted_receiver) = watch::channel(false);
 
             // build hashchain_node for statistics and static parameters
             let manager = work_hub
@@ -1615,36 +3205,43 @@ impl Backend {
                         midstate_count: chain_config.midstate_count,
                         work_solver_stats: Default::default(),
                         solution_sender,
+                        solution_pause: solution_pause.clone(),
                         work_generator,
                         monitor_tx,
-                        status_receiver,
-                        owned_by: StdMutex::new(None),
+                        status_stream,
+                        ownership: ownership::Lock::new(),
+                        enumeration_failures: AtomicUsize::new(0),
+                        quarantined: AtomicBool::new(false),
+                        halted_sender,
+                        halted_receiver,
+                        chain_halt_sender,
                         inner: Mutex::new(ManagerInner {
                             hash_chain: None,
                             start_count: 0,
+                            running_since: None,
+                            enabled: true,
                         }),
                         chain_config,
                     }
                 })
                 .await;
+
+            // Register handler to stop hashchain when miner (or just this hashchain) is stopped
+            chain_halt_receiver
+                .register_client("hashchain".into())
+                .await
+                .spawn_halt_handler(Manager::termination_handler(manager.clone()));
             managers.push(manager);
         }
 
         // start everything
         for manager in managers.iter() {
-            let halt_receiver = halt_receiver.clone();
             let manager = manager.clone();
 
             let initial_frequency = manager.chain_config.frequency.clone();
             let initial_voltage = manager.chain_config.voltage;
             let hooks = hooks.clone();
 
-            // Register handler to stop hashchain when miner is stopped
-            halt_receiver
-                .register_client("hashchain".into())
-                .await
-                .spawn_halt_handler(Manager::termination_handler(manager.clone()));
-
             // Suppress haschain start if chain is either not enabled or haschain hook doesn't
             // want us to start it (default `NoHooks` has all chains enabled).
             if hooks.can_start_chain(manager.clone()).await {
@@ -1664,8 +3261,11 @@ impl Backend {
                 });
             }
         }
+        // Let monitor lower hashchain frequency when power is above limit
+        monitor.register_managers(managers.clone()).await;
+
         hooks.miner_started().await;
-        (managers, monitor)
+        Ok((managers, monitor))
     }
 }
 
@@ -1686,6 +3286,7 @@ impl hal::Backend for Backend {
         work_hub: work::SolverBuilder<Self>,
     ) -> bosminer::Result<hal::FrontendConfig> {
         let hooks = backend_config.hooks.clone();
+        let hook_termination_signals = backend_config.hook_termination_signals.unwrap_or(true);
         // Prepare data for pool configuration after successful start of backend
         let client_manager = backend_config
             .client_manager
@@ -1697,15 +3298,28 @@ impl hal::Backend for Backend {
         let backend = work_hub.to_node().clone();
         let gpio_mgr = gpio::ControlPinManager::new();
         let (app_halt_sender, app_halt_receiver) = halt::make_pair(HALT_TIMEOUT);
+        let solution_pause = Arc::new(SolutionPause::new());
+        let hashboard_slots = backend_config.resolve_hashboard_slots();
+        let hashboards = Self::detect_hashboards(&gpio_mgr, &hashboard_slots);
+        if hashboards.is_empty() {
+            // keep running anyway so that the miner can be diagnosed via API
+            error!(
+                "No hashboards detected in slots {:?}, miner will not be hashing!",
+                hashboard_slots
+            );
+        }
+        Self::check_midstate_count(&backend_config, &hashboards).map_err(|e| e.to_string())?;
         let (managers, monitor) = Self::start_miner(
             &gpio_mgr,
-            Self::detect_hashboards(&gpio_mgr).expect("failed detecting hashboards"),
+            hashboards,
             work_hub,
             backend_config,
             app_halt_receiver,
             app_halt_sender.clone(),
+            solution_pause.clone(),
         )
-        .await;
+        .await
+        .map_err(|e| e.to_string())?;
 
         // On miner exit, halt the whole program
         app_halt_sender
@@ -1715,7 +3329,9 @@ impl hal::Backend for Backend {
             })
             .await;
         // Hook `Ctrl-C`, `SIGTERM` and other termination methods
-        app_halt_sender.hook_termination_signals();
+        if hook_termination_signals {
+            app_halt_sender.hook_termination_signals();
+        }
 
         // Load initial pool configuration
         client_manager
@@ -1731,7 +3347,12 @@ impl hal::Backend for Backend {
         }
 
         Ok(hal::FrontendConfig {
-            cgminer_custom_commands: cgminer::create_custom_commands(backend, managers, monitor),
+            cgminer_custom_commands: cgminer::create_custom_commands(
+                backend,
+                managers,
+                monitor,
+                solution_pause,
+            ),
         })
     }
 
@@ -1756,6 +3377,16 @@ impl fmt::Display for Backend {
     }
 }
 
+/// Check whether `actual` baud rate differs from `requested` more than `BAUD_RATE_WARN_PERC`
+fn baud_rate_off_target(requested: usize, actual: usize) -> bool {
+    let diff = if actual > requested {
+        actual - requested
+    } else {
+        requested - actual
+    };
+    diff * 100 > BAUD_RATE_WARN_PERC * requested
+}
+
 /// Helper method that calculates baud rate clock divisor value for the specified baud rate.
 ///
 /// The calculation follows the same scheme for the hashing chips as well as for the FPGA IP core
@@ -1791,45 +3422,3 @@ fn calc_baud_clock_div(
     }
     Ok((baud_div, actual_baud_rate))
 }
-
-/// Helper method to calculate time to finish one piece of work
-///
-/// * `n_midstates` - number of midstates
-/// * `pll_frequency` - frequency of chip in Hz
-/// Return a number of seconds.
-///
-/// The formula for work_delay is:
-///
-///   work_delay = space_size_of_one_work / computation_speed; [sec, hashes, hashes_per_sec]
-///
-/// In our case it would be
-///
-///   work_delay = n_midstates * 2^32 / (freq * num_chips * cores_per_chip)
-///
-/// Unfortunately the space is not divided evenly, some nonces get never computed.
-/// The current conjecture is that nonce space is divided by chip/core address,
-/// ie. chip number 0x1a iterates all nonces 0x1axxxxxx. That's 6 bits of chip_address
-/// and 7 bits of core_address. Putting it all together:
-///
-///   work_delay = n_midstates * num_chips * cores_per_chip * 2^(32 - 7 - 6) / (freq * num_chips * cores_per_chip)
-///
-/// Simplify:
-///
-///   work_delay = n_midstates * 2^19 / freq
-///
-/// Last but not least, we apply fudge factor of 0.9 and send work 11% faster to offset
-/// delays when sending out/generating work/chips not getting proper work...:
-///
-///   work_delay = 0.9 * n_midstates * 2^19 / freq
-fn calculate_work_delay_for_pll(n_midstates: usize, pll_frequency: usize) -> f64 {
-    let space_size_per_core: u64 = 1 << 19;
-    0.9 * (n_midstates as u64 * space_size_per_core) as f64 / pll_frequency as f64
-}
-
-/// Helper method to convert seconds to FPGA ticks suitable to be written
-/// to `WORK_TIME` FPGA register.
-///
-/// Returns number of ticks.
-fn secs_to_fpga_ticks(secs: f64) -> u32 {
-    (secs * io::F_CLK_SPEED_HZ as f64) as u32
-}
@@ -80,6 +80,12 @@ async fn main() {
                 .required(false)
                 .takes_value(true),
         )
+        .arg(
+            clap::Arg::with_name("check-config")
+                .long("check-config")
+                .help("Validate configuration file and exit (hardware is not touched)")
+                .required(false),
+        )
         .subcommand(
             clap::SubCommand::with_name("config")
                 .about("Configuration backend API")
@@ -133,6 +139,23 @@ async fn main() {
         return;
     }
 
+    // Only validate configuration without starting the miner
+    if matches.is_present("check-config") {
+        match config::validate_config_file(config_path) {
+            Ok(_) => info!("Configuration file \"{}\" is valid", config_path),
+            Err(errors) => {
+                error!("Configuration file \"{}\" is invalid", config_path);
+                for e in errors {
+                    error!("Reason: {}", e);
+                }
+                // flush the log before exiting with error status
+                drop(_log_guard);
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+
     let mut backend_config: config::Backend = match config::FormatWrapper::parse(config_path) {
         Err(config::FormatWrapperError::IncompatibleVersion(version, Some(v))) => {
             warn!(
@@ -156,7 +179,9 @@ async fn main() {
             .expect("BUG: missing 'user' argument");
         let user_info = ClientUserInfo::parse(user_info);
 
-        match ClientDescriptor::create(url, &user_info, true) {
+        match ClientDescriptor::create(url, &user_info, true)
+            .and_then(|descriptor| descriptor.check_insecure(false))
+        {
             Err(e) => {
                 error!("Cannot set pool from command line: {}", e.to_string());
                 return;
@@ -170,6 +195,8 @@ async fn main() {
                 url: url.to_string(),
                 user: user_info.user.to_string(),
                 password: user_info.password.map(|v| v.to_string()),
+                allow_insecure: None,
+                connection: None,
             }]),
         };
 
@@ -193,13 +220,10 @@ async fn main() {
         return;
     }
 
-    // Set just 1 midstate if user requested disabling asicboost
+    // Set just 1 midstate if user requested disabling asicboost (even on hash chains that
+    // override it)
     if matches.is_present("disable-asic-boost") {
-        backend_config
-            .hash_chain_global
-            .get_or_insert_with(|| Default::default())
-            .asic_boost
-            .replace(false);
+        backend_config.disable_asic_boost.replace(true);
     }
     if let Some(value) = matches.value_of("frequency") {
         let frequency = match value.parse::<f64>() {
@@ -25,44 +25,65 @@
 
 use ii_logging::macros::*;
 
+use crate::config;
 use crate::fan;
 use crate::halt;
+use crate::power;
 use crate::sensor::{self, Measurement};
+use crate::{FrequencySettings, Manager};
 
+use std::collections::VecDeque;
+use std::fmt;
+use std::pin::Pin;
 use std::sync::Arc;
-use std::time::{Duration, Instant};
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant, SystemTime};
+
+use serde::{Deserialize, Serialize};
 
 use futures::channel::mpsc;
 use futures::lock::Mutex;
-use futures::stream::StreamExt;
+use futures::stream::{Stream, StreamExt};
 use ii_async_compat::futures;
 use ii_async_compat::tokio;
 use tokio::sync::watch;
 use tokio::time::delay_for;
 
-/// If miner start takes longer than this, mark it as `Broken`
-const START_TIMEOUT: Duration = Duration::from_secs(180);
-/// If miner doesn't send temperature update within this time, mark it as dead.
-/// This timeout doubles as hashchain watchdog timeout.
-/// TODO: Synchronize timeout with temperature monitor task
-const RUN_UPDATE_TIMEOUT: Duration = Duration::from_secs(10);
-/// How often check timeouts and adjust PID
-const TICK_LENGTH: Duration = Duration::from_secs(5);
-/// How long does it take until miner warm up? We won't let it tu turn fans off until then...
-const WARM_UP_PERIOD: Duration = Duration::from_secs(90);
+/// Default values of `Timeouts`
+pub const DEFAULT_START_TIMEOUT: Duration = Duration::from_secs(180);
+pub const DEFAULT_RUN_UPDATE_TIMEOUT: Duration = Duration::from_secs(10);
+pub const DEFAULT_TICK_LENGTH: Duration = Duration::from_secs(5);
+pub const DEFAULT_WARM_UP_PERIOD: Duration = Duration::from_secs(90);
+pub const DEFAULT_MISSING_FANS_GRACE: Duration = Duration::from_secs(15);
+pub const DEFAULT_TEMP_UPDATE_INTERVAL: Duration = Duration::from_secs(5);
+/// Fan cooldown on shutdown has to fit into halt timeout of the monitor
+pub const MAX_SHUTDOWN_COOLDOWN: Duration = Duration::from_secs(20);
+/// Number of samples kept in temperature history (1 hour with default tick length)
+const TEMPERATURE_HISTORY_LEN: usize = 720;
+/// Difference between S9 chip temperature and board (local sensor) temperature
+const S9_LOCAL_TEMP_OFFSET: f32 = 15.0;
+/// Number of hashchain failures within `CHAIN_FAILURE_WINDOW` after which the whole miner is
+/// shut down even if failed chains are isolated (crash-loop protection)
+const MAX_CHAIN_FAILURES: usize = 3;
+const CHAIN_FAILURE_WINDOW: Duration = Duration::from_secs(600);
+/// Chip frequency decrement applied when power is above `PowerControlConfig::max_watts`
+const POWER_LIMIT_FREQ_STEP_HZ: usize = 25_000_000;
+/// Monitor does not lower chip frequency under this floor (it shuts down miner instead)
+const POWER_LIMIT_MIN_FREQ_HZ: usize = 200_000_000;
 
 /// A message from hashchain
 ///
 /// Here are some rules that HashChains registered with monitors have to obey:
 ///
 /// - state change must be strictly `[Off -> On -> Running*]*`
-/// - duration between `On` and first `Running` must be less than START_TIMEOUT
+/// - duration between `On` and first `Running` must be less than `Timeouts::start`
 /// - duration between `Running` measurement and the next one must be less than
-///   RUN_UPDATE_INTERVAL (ideally set periodic update to half of this interval)
+///   `Timeouts::run_update` (ideally set periodic update to half of this interval)
 #[derive(Debug, Clone)]
 pub enum Message {
     On,
-    Running(sensor::Temperature),
+    /// Heartbeat with one reading per temperature sensor (empty if hashboard has no sensor)
+    Running(Vec<sensor::Temperature>),
     Off,
 }
 
@@ -85,19 +106,54 @@ impl ChainTemperature {
     /// remote sensors fail while mining and instead of signalizing error they return non-sensical
     /// numbers.
     /// TODO: Is returning "Unknown" when sensor fails OK?
-    fn from_s9_sensor(temp: sensor::Temperature) -> Self {
-        match temp.remote {
+    fn from_s9_sensor(temp: sensor::Temperature, sensor_config: &TempSensorConfig) -> Self {
+        // fake chip temperature from local (PCB) temperature
+        let local = match temp.local {
+            Measurement::Ok(t) => Some(t + S9_LOCAL_TEMP_OFFSET),
+            _ => None,
+        };
+        match (temp.remote, local) {
             // remote is chip temperature
-            Measurement::Ok(t) => Self::Ok(t),
-            _ => {
-                // fake chip temperature from local (PCB) temperature
-                match temp.local {
-                    Measurement::Ok(t) => Self::Ok(t + 15.0),
-                    _ => Self::Unknown,
+            (Measurement::Ok(remote), Some(local)) => {
+                if (remote - local).abs() <= sensor_config.disagreement_threshold {
+                    return Self::Ok(remote);
                 }
+                let temp = match sensor_config.preference {
+                    TempSensorPreference::Remote => remote,
+                    TempSensorPreference::Local => local,
+                };
+                info!(
+                    "Monitor: chip temperature {} disagrees with board temperature {} \
+                     (incl. offset), using {:?} sensor",
+                    remote, local, sensor_config.preference
+                );
+                Self::Ok(temp)
+            }
+            (Measurement::Ok(remote), None) => Self::Ok(remote),
+            (remote, Some(local)) => {
+                info!(
+                    "Monitor: chip temperature not available ({:?}), using board temperature {} \
+                     (incl. offset)",
+                    remote, local
+                );
+                Self::Ok(local)
             }
+            (_, None) => Self::Unknown,
         }
     }
+
+    /// Summarize readings of all hashboard sensors: the hottest one is taken (readings
+    /// without temperature are skipped)
+    fn from_s9_sensors(temps: &[sensor::Temperature], sensor_config: &TempSensorConfig) -> Self {
+        temps
+            .iter()
+            .map(|temp| Self::from_s9_sensor(temp.clone(), sensor_config))
+            .fold(Self::Unknown, |summary, temp| match (summary, temp) {
+                (Self::Ok(a), Self::Ok(b)) => Self::Ok(a.max(b)),
+                (Self::Ok(a), _) | (_, Self::Ok(a)) => Self::Ok(a),
+                _ => summary,
+            })
+    }
 }
 
 /// State of hashchain as seen from Monitor point of view
@@ -109,7 +165,7 @@ enum ChainState {
     Running {
         started: Instant,
         last_heartbeat: Instant,
-        temperature: sensor::Temperature,
+        temperature: Vec<sensor::Temperature>,
     },
     Off,
     Broken(&'static str),
@@ -150,17 +206,17 @@ impl ChainState {
     }
 
     /// Do a timer tick: check all timeouts and do appropriate state transitions.
-    /// If miner is starting, check it starts in `START_TIMEOUT`, if its running, check
+    /// If miner is starting, check it starts in `timeouts.start`, if its running, check
     /// it's sending "heartbeats" often enought.
-    fn tick(&mut self, now: Instant) {
+    fn tick(&mut self, now: Instant, timeouts: &Timeouts) {
         match *self {
             ChainState::On(started) => {
-                if now.duration_since(started) >= START_TIMEOUT {
+                if now.duration_since(started) >= timeouts.start {
                     *self = ChainState::Broken("took too long to start");
                 }
             }
             ChainState::Running { last_heartbeat, .. } => {
-                if now.duration_since(last_heartbeat) >= RUN_UPDATE_TIMEOUT {
+                if now.duration_since(last_heartbeat) >= timeouts.run_update {
                     *self = ChainState::Broken("failed to set update in time");
                 }
             }
@@ -171,23 +227,25 @@ impl ChainState {
     /// Return hashchain temperature as seen from our point of view. For example,
     /// `Broken` miner doesn't have a valid temperature reading even though it sent
     /// some numbers a while ago.
-    fn get_temperature(&self) -> ChainTemperature {
+    fn get_temperature(&self, sensor_config: &TempSensorConfig) -> ChainTemperature {
         match self {
             ChainState::On(_) => ChainTemperature::Unknown,
             ChainState::Off => ChainTemperature::Unknown,
             ChainState::Broken(_) => ChainTemperature::Failed,
             ChainState::Running { temperature, .. } => {
-                ChainTemperature::from_s9_sensor(temperature.clone())
+                ChainTemperature::from_s9_sensors(temperature, sensor_config)
             }
         }
     }
 
     /// Is hashchain warming up?
-    fn is_warming_up(&self, now: Instant) -> bool {
+    fn is_warming_up(&self, now: Instant, timeouts: &Timeouts) -> bool {
         match self {
             // chain state stays in "warming up" state until it sends heartbeat
             ChainState::On(_) => true,
-            ChainState::Running { started, .. } => now.duration_since(*started) <= WARM_UP_PERIOD,
+            ChainState::Running { started, .. } => {
+                now.duration_since(*started) <= timeouts.warm_up_period
+            }
             _ => false,
         }
     }
@@ -197,22 +255,42 @@ impl ChainState {
 struct Chain {
     state: ChainState,
     hashboard_idx: usize,
+    /// Last power reading from `power::PowerMeter` (reset when hashchain is turned off)
+    power: Option<ChainPower>,
+    /// Halt context of this hashchain only (used when failed chains are isolated)
+    halt_sender: Arc<halt::Sender>,
+    /// Hashchain doesn't keep fans elevated during its warm-up period (see
+    /// `Monitor::set_warm_up_opt_out`)
+    warm_up_opt_out: bool,
 }
 
 impl Chain {
-    fn new(hashboard_idx: usize) -> Self {
+    fn new(hashboard_idx: usize, halt_sender: Arc<halt::Sender>) -> Self {
         Self {
             state: ChainState::Off,
             hashboard_idx,
+            power: None,
+            halt_sender,
+            warm_up_opt_out: false,
         }
     }
+
+    /// Is hashchain warming up (unless it opted out of warm-up)?
+    fn is_warming_up(&self, now: Instant, timeouts: &Timeouts) -> bool {
+        !self.warm_up_opt_out && self.state.is_warming_up(now, timeouts)
+    }
 }
 
 /// What method of controlling fans is configured
 #[derive(Debug, Clone)]
 pub enum FanControlMode {
     FixedSpeed(fan::Speed),
-    TargetTemperature(f32),
+    /// PID drives the fans towards target temperature. It cannot work without temperature
+    /// control, so temperature limits are part of this mode.
+    TargetTemperature {
+        target_temp: f32,
+        temp_config: TempControlConfig,
+    },
 }
 
 /// Fan configuration
@@ -222,6 +300,47 @@ pub struct FanControlConfig {
     /// Minimal number of fans - miner will refuse to work until at least
     /// this number of fans is spinning.
     pub min_fans: usize,
+    /// Fan speed used instead of PID while temperature is below COLD
+    pub cold_fan_speed: fan::Speed,
+    /// Limit change of fan speed (in PWM percent) in one tick so that fans ramp up/down
+    /// gradually. `None` means the speed is set right away.
+    pub max_speed_step_per_tick: Option<u8>,
+    /// Number of consecutive ticks `min_fans` has to be violated for to be taken seriously
+    /// (a single glitchy tach reading of 0 shouldn't shut the miner down)
+    pub min_fans_ticks: usize,
+}
+
+impl FanControlConfig {
+    /// Move fan speed from `current` towards `target` by at most `max_speed_step_per_tick`
+    fn ramp_speed(&self, current: Option<fan::Speed>, target: fan::Speed) -> fan::Speed {
+        match (current, self.max_speed_step_per_tick) {
+            (Some(current), Some(max_step)) => {
+                let current = current.to_pwm();
+                let target = target.to_pwm();
+                let max_step = max_step as usize;
+                if target > current {
+                    fan::Speed::new(target.min(current + max_step))
+                } else {
+                    fan::Speed::new(target.max(current.saturating_sub(max_step)))
+                }
+            }
+            _ => target,
+        }
+    }
+
+    /// Update count of consecutive ticks with less than `min_fans` running
+    fn count_missing_fans_ticks(&self, ticks: usize, num_fans_running: usize) -> usize {
+        if num_fans_running < self.min_fans {
+            ticks + 1
+        } else {
+            0
+        }
+    }
+
+    /// Fans have been missing for enough consecutive ticks (see `min_fans_ticks`)
+    fn missing_fans_confirmed(&self, ticks: usize) -> bool {
+        ticks >= self.min_fans_ticks
+    }
 }
 
 /// Temperature limit configuration
@@ -229,17 +348,226 @@ pub struct FanControlConfig {
 pub struct TempControlConfig {
     pub dangerous_temp: f32,
     pub hot_temp: f32,
+    /// Once HOT is entered, temperature has to drop this much below `hot_temp` to leave it
+    pub hot_temp_hysteresis: f32,
+    /// Below this temperature PID is not used (it tends to overshoot on cold start)
+    pub cold_temp: f32,
+}
+
+/// Which sensor to trust when chip (remote) and board (local) temperatures disagree
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum TempSensorPreference {
+    Remote,
+    Local,
+}
+
+/// Temperature sensor configuration
+#[derive(Debug, Clone)]
+pub struct TempSensorConfig {
+    pub preference: TempSensorPreference,
+    /// Readings disagree when chip temperature and board temperature (incl. offset) differ
+    /// by more than this
+    pub disagreement_threshold: f32,
+}
+
+/// Monitor timeouts and periods (configured in seconds)
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct Timeouts {
+    /// If miner start takes longer than this, mark it as `Broken`
+    #[serde(with = "config::support::duration_secs")]
+    pub start: Duration,
+    /// If miner doesn't send temperature update within this time, mark it as dead.
+    /// This timeout doubles as hashchain watchdog timeout.
+    #[serde(with = "config::support::duration_secs")]
+    pub run_update: Duration,
+    /// How often hashchains send temperature update (see `Message::Running`), it must be
+    /// at most half of `run_update` (see `Timeouts::validate`)
+    #[serde(with = "config::support::duration_secs")]
+    pub temp_update_interval: Duration,
+    /// How often check timeouts and adjust PID
+    #[serde(with = "config::support::duration_secs")]
+    pub tick_length: Duration,
+    /// How long does it take until miner warm up? We won't let it tu turn fans off until then...
+    #[serde(with = "config::support::duration_secs")]
+    pub warm_up_period: Duration,
+    /// How long can the number of running fans stay below `FanControlConfig::min_fans` before
+    /// miner is shut down (fans take some time to spin up after start or config change)
+    #[serde(with = "config::support::duration_secs")]
+    pub missing_fans_grace: Duration,
+}
+
+impl Default for Timeouts {
+    fn default() -> Self {
+        Self {
+            start: DEFAULT_START_TIMEOUT,
+            run_update: DEFAULT_RUN_UPDATE_TIMEOUT,
+            tick_length: DEFAULT_TICK_LENGTH,
+            warm_up_period: DEFAULT_WARM_UP_PERIOD,
+            missing_fans_grace: DEFAULT_MISSING_FANS_GRACE,
+            temp_update_interval: DEFAULT_TEMP_UPDATE_INTERVAL,
+        }
+    }
+}
+
+impl Timeouts {
+    /// Check that hashchains can keep up with `run_update` so that they don't get marked
+    /// as `Broken` just because of delayed temperature update
+    pub fn validate(&self) -> Result<(), String> {
+        if self.tick_length == Duration::from_secs(0)
+            || self.temp_update_interval == Duration::from_secs(0)
+        {
+            return Err("tick length and temperature update interval must not be zero".into());
+        }
+        if self.temp_update_interval * 2 > self.run_update {
+            return Err(format!(
+                "temperature update interval {:?} has to be at most half of run update timeout {:?}",
+                self.temp_update_interval, self.run_update
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// What to do with fans when miner is shut down normally (not due to failure)
+///
+/// It's configured as table with `mode` key, e.g. `{ mode = "cooldown", speed = 50,
+/// duration = 10 }` (duration is in seconds).
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum ShutdownFanMode {
+    /// Stop fans right away
+    Stop,
+    /// Leave fans running at the last speed
+    KeepLastSpeed,
+    /// Run fans at `speed` for `duration` (at most `MAX_SHUTDOWN_COOLDOWN`) to cool down
+    /// hot boards, then stop them
+    Cooldown {
+        speed: fan::Speed,
+        #[serde(with = "config::support::duration_secs")]
+        duration: Duration,
+    },
+}
+
+/// Power limit configuration
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct PowerControlConfig {
+    /// Maximal total power (in watts) drawn by all hashboards
+    pub max_watts: f32,
 }
 
 /// Overall configuration
 /// "Disabled" is represented as `None`
 #[derive(Debug, Clone)]
 pub struct Config {
-    pub fan_config: Option<FanControlConfig>,
-    pub temp_config: Option<TempControlConfig>,
+    fan_config: Option<FanControlConfig>,
+    /// Temperature control used when fans are not in `FanControlMode::TargetTemperature`
+    /// (which carries its own). It's always `None` in that mode so that there's a single place
+    /// the configuration is kept in. Use `Config::temp_config()` to get the effective one.
+    temp_config: Option<TempControlConfig>,
+    pub sensor_config: TempSensorConfig,
+    pub timeouts: Timeouts,
     /// If true, then do not let fans bellow predefined limit while miner is warming up.
-    /// TODO: this is not particularly nice, it should be done per-chain and run-time.
+    /// Miner is warming up if any of its hashchains is (S9 fans are shared by all hashchains,
+    /// so there's no per-chain fan zone). Hashchains can opt out of warm-up at run-time, see
+    /// `Monitor::set_warm_up_opt_out`.
     pub fans_on_while_warming_up: bool,
+    /// If true, then broken hashchain or hashchain with dangerous temperature is halted
+    /// alone and the rest of miner keeps running. Otherwise the whole miner is shut down.
+    pub isolate_failed_chains: bool,
+    /// Fan behavior on normal shutdown
+    pub shutdown_fan_mode: ShutdownFanMode,
+    pub power_config: Option<PowerControlConfig>,
+}
+
+impl Config {
+    /// Construct configuration with given temperature and fan control. The rest of settings
+    /// is at their defaults (fans on while warming up, no chain isolation, fans stopped on
+    /// shutdown and no power limit).
+    ///
+    /// * `temp_config` - temperature control, it has to be `None` if `fan_config` is in
+    ///   `FanControlMode::TargetTemperature` mode (which carries its own)
+    pub fn new(
+        temp_config: Option<TempControlConfig>,
+        fan_config: Option<FanControlConfig>,
+        sensor_config: TempSensorConfig,
+        timeouts: Timeouts,
+    ) -> Self {
+        if let Some(FanControlMode::TargetTemperature { .. }) =
+            fan_config.as_ref().map(|fan_config| &fan_config.mode)
+        {
+            assert!(
+                temp_config.is_none(),
+                "BUG: temperature control is part of target temperature fan mode"
+            );
+        }
+        Self {
+            fan_config,
+            temp_config,
+            sensor_config,
+            timeouts,
+            fans_on_while_warming_up: true,
+            isolate_failed_chains: false,
+            shutdown_fan_mode: ShutdownFanMode::Stop,
+            power_config: None,
+        }
+    }
+
+    /// Return fan control configuration
+    pub fn fan_config(&self) -> Option<&FanControlConfig> {
+        self.fan_config.as_ref()
+    }
+
+    /// Stop controlling fans, temperature control is kept (even the one that has been part of
+    /// `FanControlMode::TargetTemperature`)
+    pub fn disable_fan_control(&mut self) {
+        self.temp_config = self.temp_config().cloned();
+        self.fan_config = None;
+    }
+
+    /// Return temperature control configuration that is in effect
+    pub fn temp_config(&self) -> Option<&TempControlConfig> {
+        match self.fan_config.as_ref().map(|fan_config| &fan_config.mode) {
+            Some(FanControlMode::TargetTemperature { temp_config, .. }) => Some(temp_config),
+            _ => self.temp_config.as_ref(),
+        }
+    }
+}
+
+/// Temperature band the miner is in. It's tracked between ticks to implement hysteresis
+/// on the HOT threshold.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TemperatureBand {
+    Normal,
+    /// Temperature went above HOT and hasn't dropped below `hot_temp - hot_temp_hysteresis`
+    Hot,
+}
+
+impl TemperatureBand {
+    /// Determine band for new temperature measurement given the current band.
+    /// Band doesn't change when temperature is not known.
+    fn update(self, temp_config: Option<&TempControlConfig>, temp: ChainTemperature) -> Self {
+        let temp_config = match temp_config {
+            Some(temp_config) => temp_config,
+            None => return Self::Normal,
+        };
+        match temp {
+            ChainTemperature::Ok(input_temp) => {
+                if input_temp >= temp_config.hot_temp {
+                    Self::Hot
+                } else if self == Self::Hot
+                    && input_temp >= temp_config.hot_temp - temp_config.hot_temp_hysteresis
+                {
+                    Self::Hot
+                } else {
+                    Self::Normal
+                }
+            }
+            ChainTemperature::Failed | ChainTemperature::Unknown => self,
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -248,6 +576,17 @@ pub struct ControlDecisionExplained {
     pub reason: &'static str,
 }
 
+impl ControlDecisionExplained {
+    /// Whether the decision has been made because there are not enough fans running
+    fn caused_by_missing_fans(&self) -> bool {
+        match self.decision {
+            ControlDecision::WaitForFans => true,
+            ControlDecision::Shutdown => self.reason == ControlDecision::REASON_NOT_ENOUGH_FANS,
+            _ => false,
+        }
+    }
+}
+
 /// Output of the decision process
 #[derive(Debug, Clone, PartialEq)]
 pub enum ControlDecision {
@@ -257,18 +596,27 @@ pub enum ControlDecision {
     UsePid { target_temp: f32, input_temp: f32 },
     /// Use fixed speed
     UseFixedSpeed(fan::Speed),
+    /// Not enough fans are running, but it's still within `Timeouts::missing_fans_grace` -
+    /// run fans at full speed to help them spin up
+    WaitForFans,
     /// Do nothing (only valid when fan control is disabled)
     Nothing,
 }
 
 impl ControlDecision {
-    /// Decision rules if both fan control and temp control are enabled
+    const REASON_NOT_ENOUGH_FANS: &'static str = "not enough fans";
+
+    /// Decision rules if fan control is enabled
+    ///
+    /// * `temp_control_enabled` - whether temperature control is enabled (it always is in
+    ///   `FanControlMode::TargetTemperature`)
     fn decide_fan_control(
         fan_config: &FanControlConfig,
-        temp_config: &TempControlConfig,
+        temp_control_enabled: bool,
         temp: ChainTemperature,
+        band: TemperatureBand,
     ) -> ControlDecisionExplained {
-        if temp == ChainTemperature::Unknown {
+        if temp_control_enabled && temp == ChainTemperature::Unknown {
             return ControlDecisionExplained {
                 decision: Self::UseFixedSpeed(fan::Speed::FULL_SPEED),
                 reason: "unknown temperature",
@@ -281,17 +629,26 @@ impl ControlDecision {
                     reason: "user defined fan speed",
                 };
             }
-            FanControlMode::TargetTemperature(target_temp) => match temp {
+            FanControlMode::TargetTemperature {
+                target_temp,
+                temp_config,
+            } => match temp {
                 ChainTemperature::Failed | ChainTemperature::Unknown => {
                     panic!("BUG: should've been caught earlier at the top of `decide()` function")
                 }
                 ChainTemperature::Ok(input_temp) => {
-                    if input_temp >= temp_config.hot_temp {
+                    if input_temp >= temp_config.hot_temp || band == TemperatureBand::Hot {
                         return ControlDecisionExplained {
                             decision: Self::UseFixedSpeed(fan::Speed::FULL_SPEED),
                             reason: "temperature above HOT",
                         };
                     }
+                    if input_temp < temp_config.cold_temp {
+                        return ControlDecisionExplained {
+                            decision: Self::UseFixedSpeed(fan_config.cold_fan_speed),
+                            reason: "temperature below COLD",
+                        };
+                    }
                     return ControlDecisionExplained {
                         decision: Self::UsePid {
                             target_temp: *target_temp,
@@ -304,36 +661,39 @@ impl ControlDecision {
         }
     }
 
-    /// Decision rules if fan control is enabled and temp control disabled
-    fn decide_fan_control_notemp(fan_config: &FanControlConfig) -> ControlDecisionExplained {
-        match fan_config.mode {
-            FanControlMode::FixedSpeed(pwm) => {
-                return ControlDecisionExplained {
-                    decision: Self::UseFixedSpeed(pwm),
-                    reason: "user defined fan speed",
-                };
-            }
-            FanControlMode::TargetTemperature(_) => {
-                // I don't know how to avoid this variant using type system alone
-                // Let's make it non-fatal
-                return ControlDecisionExplained {
-                    decision: Self::UseFixedSpeed(fan::Speed::FULL_SPEED),
-                    reason: "wrong configration - temp control off",
-                };
-            }
-        }
-    }
-
-    /// Decide what to do depending on temperature/fan feedback.
+    /// Decide what to do depending on temperature/fan feedback (missing fans shut the miner
+    /// down immediately, see `decide_with_grace`).
     /// This function has been factored out of the main control code to facilitate testing.
+    ///
+    /// * `band` - current temperature band (see `TemperatureBand::update`)
     fn decide(
         config: &Config,
         num_fans_running: usize,
         temp: ChainTemperature,
+        band: TemperatureBand,
+    ) -> ControlDecisionExplained {
+        Self::decide_with_grace(config, num_fans_running, temp, band, true, true)
+    }
+
+    /// Same as `decide`, but missing fans result in shutdown only if they have been missing
+    /// for too long.
+    ///
+    /// * `missing_fans_grace_expired` - fans have been missing for longer than
+    ///   `Timeouts::missing_fans_grace` (evaluated by caller)
+    /// * `missing_fans_confirmed` - fans have been missing for at least
+    ///   `FanControlConfig::min_fans_ticks` consecutive ticks, otherwise fan feedback is
+    ///   considered a glitch and ignored (evaluated by caller)
+    fn decide_with_grace(
+        config: &Config,
+        num_fans_running: usize,
+        temp: ChainTemperature,
+        band: TemperatureBand,
+        missing_fans_grace_expired: bool,
+        missing_fans_confirmed: bool,
     ) -> ControlDecisionExplained {
         // This section is labeled `TEMP_DANGER` in the diagram
         // Check for dangerous temperature or dead sensors
-        if let Some(temp_config) = config.temp_config.as_ref() {
+        if let Some(temp_config) = config.temp_config() {
             match temp {
                 ChainTemperature::Failed => {
                     return ControlDecisionExplained {
@@ -354,11 +714,8 @@ impl ControlDecision {
         }
         // Check the health of fans and decide their speed
         if let Some(fan_config) = config.fan_config.as_ref() {
-            let decision_explained = if let Some(temp_config) = config.temp_config.as_ref() {
-                Self::decide_fan_control(fan_config, temp_config, temp)
-            } else {
-                Self::decide_fan_control_notemp(fan_config)
-            };
+            let decision_explained =
+                Self::decide_fan_control(fan_config, config.temp_config().is_some(), temp, band);
             // This section is labeled `FAN_DANGER` in the diagram
             //
             // Check `min_fans` are spinning _unless_ we have been explicitly configured to
@@ -368,10 +725,16 @@ impl ControlDecision {
             // the configuration changes at runtime to non-stopped fans, the delay of fans
             // taking some time to spin up will cause this check to fire off!
             if decision_explained.decision != Self::UseFixedSpeed(fan::Speed::STOPPED) {
-                if num_fans_running < fan_config.min_fans {
+                if num_fans_running < fan_config.min_fans && missing_fans_confirmed {
+                    if !missing_fans_grace_expired {
+                        return ControlDecisionExplained {
+                            decision: Self::WaitForFans,
+                            reason: "not enough fans (grace period)",
+                        };
+                    }
                     return ControlDecisionExplained {
                         decision: Self::Shutdown,
-                        reason: "not enough fans",
+                        reason: Self::REASON_NOT_ENOUGH_FANS,
                     };
                 }
             }
@@ -386,6 +749,64 @@ impl ControlDecision {
     }
 }
 
+/// Output of the power limit decision process
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PowerDecision {
+    /// Power is within limit (or it's not measured at all)
+    Nothing,
+    /// Lower frequency of all running hashchains
+    ReduceFrequency,
+    /// Frequency is already at floor - shutdown miner
+    Shutdown,
+}
+
+impl PowerDecision {
+    /// Decide what to do depending on power drawn by hashchains. Hashchains without power
+    /// measurement do not count towards the total.
+    /// This function has been factored out of the main control code to facilitate testing.
+    ///
+    /// * `at_floor` - all running hashchains are already at `POWER_LIMIT_MIN_FREQ_HZ`
+    fn decide(
+        power_config: Option<&PowerControlConfig>,
+        chain_power: &[ChainPower],
+        at_floor: bool,
+    ) -> Self {
+        let power_config = match power_config {
+            Some(power_config) => power_config,
+            None => return Self::Nothing,
+        };
+        match Self::total_watts(chain_power) {
+            Some(watts) if watts > power_config.max_watts => {
+                if at_floor {
+                    Self::Shutdown
+                } else {
+                    Self::ReduceFrequency
+                }
+            }
+            _ => Self::Nothing,
+        }
+    }
+
+    /// Return readings taken after `since` (all of them if it's `None`). Readings taken before
+    /// the last frequency change do not reflect it and acting on them would lower frequency
+    /// again.
+    fn fresh_readings(chain_power: &[ChainPower], since: Option<Instant>) -> Vec<ChainPower> {
+        chain_power
+            .iter()
+            .filter(|power| since.map_or(true, |since| power.time > since))
+            .cloned()
+            .collect()
+    }
+
+    /// Sum power of all hashchains that measured it (`None` if there's none)
+    fn total_watts(chain_power: &[ChainPower]) -> Option<f32> {
+        chain_power
+            .iter()
+            .filter_map(|power| power.reading.watts())
+            .fold(None, |total, watts| Some(total.unwrap_or(0.0) + watts))
+    }
+}
+
 /// This structure abstracts the process of "making one aggregate temperature out of
 /// all hashchain temperatures".
 /// The resulting temperature is used as an input variable for PID control.
@@ -429,6 +850,154 @@ impl TemperatureAccumulator {
     }
 }
 
+/// Why monitor declared miner shutdown
+#[derive(Debug, Clone, PartialEq)]
+pub enum ShutdownReason {
+    /// Hashchain failed to start or stopped sending updates
+    BrokenChain {
+        hashboard_idx: usize,
+        reason: &'static str,
+    },
+    /// Hashchain reported temperature above DANGEROUS (only when failed chains are isolated)
+    DangerousChainTemperature {
+        hashboard_idx: usize,
+        temperature: f32,
+    },
+    /// Temperature/fan control decided to shut down (reason is taken from
+    /// `ControlDecisionExplained`)
+    ControlDecision(&'static str),
+    /// Power stayed above limit even with hashchains at minimal frequency
+    PowerAboveLimit { watts: f32, max_watts: f32 },
+}
+
+impl fmt::Display for ShutdownReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::BrokenChain {
+                hashboard_idx,
+                reason,
+            } => write!(f, "Chain {} is broken: {}", hashboard_idx, reason),
+            Self::DangerousChainTemperature {
+                hashboard_idx,
+                temperature,
+            } => write!(
+                f,
+                "Chain {} temperature {} above DANGEROUS",
+                hashboard_idx, temperature
+            ),
+            Self::ControlDecision(reason) => write!(f, "{}", reason),
+            Self::PowerAboveLimit { watts, max_watts } => write!(
+                f,
+                "power {} W above limit {} W at minimal frequency",
+                watts, max_watts
+            ),
+        }
+    }
+}
+
+impl ShutdownReason {
+    /// Return index of hashboard that caused the failure (if it's hashchain specific)
+    pub fn hashboard_idx(&self) -> Option<usize> {
+        match self {
+            Self::BrokenChain { hashboard_idx, .. }
+            | Self::DangerousChainTemperature { hashboard_idx, .. } => Some(*hashboard_idx),
+            Self::ControlDecision(_) | Self::PowerAboveLimit { .. } => None,
+        }
+    }
+}
+
+/// What to do about a failure detected by monitor
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ShutdownAction {
+    /// Halt the whole miner
+    ShutdownMiner,
+    /// Halt only the failed hashchain, the rest of miner keeps running
+    HaltChain,
+    /// Miner shutdown has already been declared, do not halt anything again
+    Throttle,
+}
+
+/// Policy deciding how monitor reacts to failures based on failure type and recent history
+#[derive(Debug, Clone)]
+pub struct ShutdownPolicy {
+    /// Times of recent hashchain failures (oldest first)
+    chain_failures: VecDeque<Instant>,
+    /// When the miner shutdown has been declared
+    miner_shutdown: Option<Instant>,
+}
+
+impl ShutdownPolicy {
+    pub fn new() -> Self {
+        Self {
+            chain_failures: VecDeque::new(),
+            miner_shutdown: None,
+        }
+    }
+
+    /// Decide what to do about failure
+    ///
+    /// * `num_chains` - number of hashchains monitored at the moment
+    pub fn decide(
+        &mut self,
+        config: &Config,
+        now: Instant,
+        reason: &ShutdownReason,
+        num_chains: usize,
+    ) -> ShutdownAction {
+        if self.miner_shutdown.is_some() {
+            return ShutdownAction::Throttle;
+        }
+        let action = match reason.hashboard_idx() {
+            // there's nothing left to keep running when the last chain fails
+            Some(_) if config.isolate_failed_chains && num_chains > 1 => {
+                while let Some(time) = self.chain_failures.front() {
+                    if now.duration_since(*time) < CHAIN_FAILURE_WINDOW {
+                        break;
+                    }
+                    self.chain_failures.pop_front();
+                }
+                self.chain_failures.push_back(now);
+                if self.chain_failures.len() > MAX_CHAIN_FAILURES {
+                    ShutdownAction::ShutdownMiner
+                } else {
+                    ShutdownAction::HaltChain
+                }
+            }
+            _ => ShutdownAction::ShutdownMiner,
+        };
+        if action == ShutdownAction::ShutdownMiner {
+            self.miner_shutdown = Some(now);
+        }
+        action
+    }
+}
+
+/// Record of the last shutdown kept for post-mortem reporting
+#[derive(Debug, Clone)]
+pub struct ShutdownRecord {
+    pub reason: ShutdownReason,
+    /// Wall-clock time of the shutdown
+    pub time: SystemTime,
+}
+
+impl ShutdownRecord {
+    /// Time of the shutdown in seconds since Unix epoch
+    pub fn unix_time(&self) -> u64 {
+        self.time
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or(0)
+    }
+}
+
+/// One sample of temperature history taken at the end of monitor tick
+#[derive(Debug, Clone)]
+pub struct TemperatureSample {
+    pub time: Instant,
+    pub temperature: ChainTemperature,
+    pub fan_speed: Option<fan::Speed>,
+}
+
 /// Status of `Monitor` for others to observe
 #[derive(Debug, Clone)]
 pub struct Status {
@@ -436,8 +1005,64 @@ pub struct Status {
     pub fan_feedback: fan::Feedback,
     pub fan_speed: Option<fan::Speed>,
     pub input_temperature: ChainTemperature,
+    pub temperature_band: TemperatureBand,
     pub temperature_accumulator: TemperatureAccumulator,
     pub decision_explained: ControlDecisionExplained,
+    /// Hashchains that were halted by monitor while the rest of miner keeps running
+    pub isolated_chains: Vec<ShutdownRecord>,
+    /// Action taken on the last failure (if any)
+    pub last_shutdown_action: Option<ShutdownAction>,
+    /// Power readings of hashchains that reported them
+    pub chain_power: Vec<ChainPower>,
+    /// None of the running hashchains has temperature sensor (`false` when there's no
+    /// hashchain running yet)
+    pub no_sensors: bool,
+}
+
+/// Stream of `Status` updates broadcast by `Monitor` (see `Monitor::subscribe`)
+///
+/// The first item is the status that is current at the time of subscription (if monitor has
+/// reported any yet), then one item per each update. Updates that happen in between polls are
+/// coalesced into the latest one.
+#[derive(Clone)]
+pub struct StatusStream {
+    status_receiver: watch::Receiver<Option<Status>>,
+}
+
+impl StatusStream {
+    fn new(status_receiver: watch::Receiver<Option<Status>>) -> Self {
+        Self { status_receiver }
+    }
+
+    /// Return the last status without waiting (`None` if monitor hasn't reported any yet)
+    pub fn current(&self) -> Option<Status> {
+        self.status_receiver.borrow().clone()
+    }
+}
+
+impl Stream for StatusStream {
+    type Item = Status;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        loop {
+            match self.status_receiver.poll_next_unpin(cx) {
+                // skip initial value of the channel
+                Poll::Ready(Some(None)) => continue,
+                Poll::Ready(Some(Some(status))) => return Poll::Ready(Some(status)),
+                Poll::Ready(None) => return Poll::Ready(None),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
+
+/// Power reading of one hashchain
+#[derive(Debug, Clone)]
+pub struct ChainPower {
+    pub hashboard_idx: usize,
+    pub reading: power::PowerReading,
+    /// When the reading has been taken
+    pub time: Instant,
 }
 
 /// Monitor - it holds states of all Chains and everything related to fan control
@@ -446,22 +1071,45 @@ pub struct MonitorInner {
     chains: Vec<Arc<Mutex<Chain>>>,
     /// temp/fan control configuration
     config: Config,
-    /// Fan controller - can set RPM or read feedback
-    fan_control: fan::Control,
+    /// Fan controller - can set RPM or read feedback (`None` when it failed to initialize,
+    /// fans are not controlled then and only dangerous temperature is watched)
+    fan_control: Option<fan::Control>,
     /// Last fan speed that was set
     current_fan_speed: Option<fan::Speed>,
     /// PID that controls fan with hashchain temperature as input
     pid: fan::pid::TempControl,
+    /// Temperature band from previous tick (for HOT hysteresis)
+    temperature_band: TemperatureBand,
     /// Flag whether miner is in failure state - temperature critical, hashboards not responding,
     /// fans gone missing...
     failure_state: bool,
+    /// Why and when miner was last shut down by monitor
+    last_shutdown: Option<ShutdownRecord>,
+    /// Hashchains halted by monitor (see `Config::isolate_failed_chains`)
+    isolated_chains: Vec<ShutdownRecord>,
+    /// Decides whether failure shuts down the whole miner or just the hashchain
+    shutdown_policy: ShutdownPolicy,
+    /// Action taken on the last failure
+    last_shutdown_action: Option<ShutdownAction>,
+    /// Last `TEMPERATURE_HISTORY_LEN` temperature samples (oldest first)
+    temperature_history: VecDeque<TemperatureSample>,
+    /// Hashchain managers to act on when power is above limit
+    managers: Vec<Arc<Manager>>,
+    /// Hashchains are being acted on because power is above limit (see `power_limit_task`)
+    power_limit_pending: bool,
+    /// When frequency has been last changed because of power limit
+    power_limit_changed: Option<Instant>,
+    /// Since when there are not enough fans running (`None` if there are enough)
+    missing_fans_since: Option<Instant>,
+    /// Number of consecutive ticks with not enough fans running
+    missing_fans_ticks: usize,
 }
 
 /// Wrapper around `MonitorInner` with immutable fields
 pub struct Monitor {
     /// Broadcast channel to send/receive monitor status
     status_sender: watch::Sender<Option<Status>>,
-    pub status_receiver: watch::Receiver<Option<Status>>,
+    status_receiver: watch::Receiver<Option<Status>>,
 
     /// Context to shutdown when miner enters critical state
     miner_shutdown: Arc<halt::Sender>,
@@ -473,22 +1121,48 @@ pub struct Monitor {
 impl Monitor {
     /// Construct a new monitor and start it
     ///
+    /// * `power_meter` - source of hashboard power readings (if there's any)
     /// * `miner_shutdown` - halt sender to shutdown the whole miner in case of a failure
     /// * `halt_receiver` - termination context in which to start the monitor
     pub async fn new_and_start(
-        config: Config,
+        mut config: Config,
+        power_meter: Option<Arc<dyn power::PowerMeter>>,
         miner_shutdown: Arc<halt::Sender>,
         halt_receiver: halt::Receiver,
     ) -> Arc<Self> {
         let (status_sender, status_receiver) = watch::channel(None);
 
+        // Fans may be controlled externally, do not give up on temperature watchdog
+        let fan_control = match fan::Control::new() {
+            Ok(fan_control) => Some(fan_control),
+            Err(e) => {
+                error!(
+                    "Monitor: failed initializing fan controller, running without fan control: {}",
+                    e
+                );
+                config.disable_fan_control();
+                None
+            }
+        };
+
         let inner = MonitorInner {
             chains: Vec::new(),
             config,
-            fan_control: fan::Control::new().expect("failed initializing fan controller"),
+            fan_control,
             pid: fan::pid::TempControl::new(),
+            temperature_band: TemperatureBand::Normal,
             failure_state: false,
             current_fan_speed: None,
+            last_shutdown: None,
+            isolated_chains: Vec::new(),
+            shutdown_policy: ShutdownPolicy::new(),
+            last_shutdown_action: None,
+            temperature_history: VecDeque::with_capacity(TEMPERATURE_HISTORY_LEN),
+            managers: Vec::new(),
+            power_limit_pending: false,
+            power_limit_changed: None,
+            missing_fans_since: None,
+            missing_fans_ticks: 0,
         };
 
         let monitor = Arc::new(Monitor {
@@ -498,92 +1172,348 @@ impl Monitor {
             inner: Mutex::new(inner),
         });
 
+        // Clients are halted in order of registration: tasks that set fans have to be stopped
+        // before termination handler sets fans for shutdown (e.g. cooldown)
         halt_receiver
-            .register_client("monitor termination".into())
+            .register_client("monitor".into())
             .await
-            .spawn_halt_handler(Self::termination_handler(monitor.clone()));
+            .spawn(Self::tick_task(monitor.clone()));
+
+        if let Some(power_meter) = power_meter {
+            halt_receiver
+                .register_client("monitor power meter".into())
+                .await
+                .spawn(Self::power_task(monitor.clone(), power_meter));
+        }
 
         halt_receiver
-            .register_client("monitor".into())
+            .register_client("monitor termination".into())
             .await
-            .spawn(Self::tick_task(monitor.clone()));
+            .spawn_halt_handler(Self::termination_handler(monitor.clone()));
 
         monitor
     }
 
     /// Handler that is run when monitor is signalized with shutdown.
-    /// Just stops the fans (depending on whether it's in failure state).
+    /// Just stops the fans (depending on whether it's in failure state and on
+    /// `Config::shutdown_fan_mode`).
     async fn termination_handler(self: Arc<Self>) {
         let mut inner = self.inner.lock().await;
         // Decide whether to leave fans on (depending on whether we are in failure state or not)
         if inner.failure_state {
             self.set_fan_speed(&mut inner, fan::Speed::FULL_SPEED);
-        } else {
-            self.set_fan_speed(&mut inner, fan::Speed::STOPPED);
+            return;
+        }
+        match inner.config.shutdown_fan_mode {
+            ShutdownFanMode::Stop => self.set_fan_speed(&mut inner, fan::Speed::STOPPED),
+            ShutdownFanMode::KeepLastSpeed => {}
+            ShutdownFanMode::Cooldown { speed, duration } => {
+                let duration = duration.min(MAX_SHUTDOWN_COOLDOWN);
+                info!("Monitor: cooling down fans for {:?}", duration);
+                self.set_fan_speed(&mut inner, speed);
+                // do not block others while waiting
+                drop(inner);
+                delay_for(duration).await;
+                let mut inner = self.inner.lock().await;
+                self.set_fan_speed(&mut inner, fan::Speed::STOPPED);
+            }
+        }
+    }
+
+    /// Handle failure according to shutdown policy
+    ///
+    /// * `chain_idx` - index of the failed chain in `chains` (if failure is chain specific)
+    ///
+    /// Returns `false` if the monitor tick must not continue (miner is shutting down).
+    async fn handle_failure(
+        &self,
+        inner: &mut MonitorInner,
+        chain_idx: Option<usize>,
+        reason: ShutdownReason,
+    ) -> bool {
+        let num_chains = inner.chains.len();
+        let policy = &mut inner.shutdown_policy;
+        let action = policy.decide(&inner.config, Instant::now(), &reason, num_chains);
+        inner.last_shutdown_action = Some(action);
+        match (action, chain_idx) {
+            (ShutdownAction::HaltChain, Some(idx)) => {
+                self.isolate_chain(inner, idx, reason).await;
+                true
+            }
+            (ShutdownAction::Throttle, _) => {
+                info!("Monitor: miner already shutting down, ignoring: {}", reason);
+                false
+            }
+            _ => {
+                self.shutdown(inner, reason).await;
+                false
+            }
         }
     }
 
     /// Shutdown miner
-    async fn shutdown(&self, inner: &mut MonitorInner, reason: String) {
+    async fn shutdown(&self, inner: &mut MonitorInner, reason: ShutdownReason) {
         error!("Monitor task declared miner shutdown: {}", reason);
         inner.failure_state = true;
-        self.miner_shutdown.clone().send_halt().await;
+        let halt_reason = reason.to_string();
+        inner.last_shutdown = Some(ShutdownRecord {
+            reason,
+            time: SystemTime::now(),
+        });
+        self.miner_shutdown
+            .clone()
+            .send_halt_with_reason(halt_reason)
+            .await;
+    }
+
+    /// Halt only one hashchain and stop monitoring it, the rest of miner keeps running
+    async fn isolate_chain(&self, inner: &mut MonitorInner, idx: usize, reason: ShutdownReason) {
+        error!("Monitor task declared hashchain shutdown: {}", reason);
+        let chain = inner.chains.remove(idx);
+        let halt_sender = chain.lock().await.halt_sender.clone();
+        let halt_reason = reason.to_string();
+        inner.isolated_chains.push(ShutdownRecord {
+            reason,
+            time: SystemTime::now(),
+        });
+        // do not block monitor while the hashchain is being stopped
+        tokio::spawn(halt_sender.send_halt_with_reason(halt_reason));
     }
 
     /// Set fan speed
     fn set_fan_speed(&self, inner: &mut MonitorInner, fan_speed: fan::Speed) {
-        info!("Monitor: setting fan to {:?}", fan_speed);
-        inner.fan_control.set_speed(fan_speed);
-        inner.current_fan_speed = Some(fan_speed);
+        match inner.fan_control.as_ref() {
+            Some(fan_control) => {
+                info!("Monitor: setting fan to {:?}", fan_speed);
+                fan_control.set_speed(fan_speed);
+                inner.current_fan_speed = Some(fan_speed);
+            }
+            // missing fan controller has been reported when monitor started
+            None => trace!(
+                "Monitor: no fan controller, not setting fan to {:?}",
+                fan_speed
+            ),
+        }
     }
 
-    /// One tick of temperature/fan controller
-    ///
-    /// TODO: Run this tick every time new temperature is submitted to lower temp controller
-    ///   latency.
-    async fn do_tick(&self) {
-        // decide hashchain state and collect temperatures
-        let mut inner = self.inner.lock().await;
-        let mut temperature_accumulator = TemperatureAccumulator::new();
-        let mut miner_warming_up = false;
-        for chain in inner.chains.iter() {
-            let mut chain = chain.lock().await;
-            chain.state.tick(Instant::now());
+    /// Set fan speed, but limit its change according to `max_speed_step_per_tick`
+    fn ramp_fan_speed(&self, inner: &mut MonitorInner, fan_speed: fan::Speed) {
+        let fan_speed = match inner.config.fan_config.as_ref() {
+            Some(fan_config) => fan_config.ramp_speed(inner.current_fan_speed, fan_speed),
+            None => fan_speed,
+        };
+        self.set_fan_speed(inner, fan_speed);
+    }
 
-            if let ChainState::Broken(reason) = chain.state {
-                // TODO: here comes "Shutdown"
-                let reason = format!("Chain {} is broken: {}", chain.hashboard_idx, reason);
-                // drop `chain` here to drop iterator which holds immutable reference
-                // to `monitor`
-                drop(chain);
-                self.shutdown(&mut inner, reason).await;
-                return;
+    /// Check whether all running hashchains are at `POWER_LIMIT_MIN_FREQ_HZ`
+    async fn power_at_floor(managers: &[Arc<Manager>]) -> bool {
+        for manager in managers.iter() {
+            let hash_chain = manager.inner.lock().await.hash_chain.clone();
+            if let Some(hash_chain) = hash_chain {
+                if hash_chain.get_frequency().await.max() > POWER_LIMIT_MIN_FREQ_HZ {
+                    return false;
+                }
             }
-            info!("chain {}: {:?}", chain.hashboard_idx, chain.state);
-            temperature_accumulator.add_chain_temp(chain.state.get_temperature());
-            miner_warming_up |= chain.state.is_warming_up(Instant::now());
         }
-        let input_temperature = temperature_accumulator.calc_result();
+        true
+    }
 
-        // Read fans
-        let fan_feedback = inner.fan_control.read_feedback();
-        let num_fans_running = fan_feedback.num_fans_running();
+    /// Lower frequency of all running hashchains by `POWER_LIMIT_FREQ_STEP_HZ` (but not under
+    /// `POWER_LIMIT_MIN_FREQ_HZ`)
+    async fn reduce_frequency(managers: &[Arc<Manager>]) {
+        for manager in managers.iter() {
+            let chain = match manager.clone().acquire("monitor").await {
+                Ok(crate::ChainStatus::Running(chain)) => chain,
+                Ok(crate::ChainStatus::Stopped(_)) => continue,
+                Err(owner) => {
+                    warn!(
+                        "Monitor: cannot reduce frequency of chain {}: owned by {}",
+                        manager.hashboard_idx, owner
+                    );
+                    continue;
+                }
+            };
+            let frequency = chain.get_frequency().await;
+            let reduced = FrequencySettings {
+                chip: frequency
+                    .chip
+                    .iter()
+                    .map(|f| {
+                        f.saturating_sub(POWER_LIMIT_FREQ_STEP_HZ)
+                            .max(POWER_LIMIT_MIN_FREQ_HZ)
+                            .min(*f)
+                    })
+                    .collect(),
+            };
+            info!(
+                "Monitor: reducing frequency of chain {} from {} to {} MHz (power limit)",
+                manager.hashboard_idx,
+                frequency.avg() as f64 / 1_000_000.0,
+                reduced.avg() as f64 / 1_000_000.0,
+            );
+            if let Err(e) = chain.set_frequency(&reduced).await {
+                error!(
+                    "Monitor: failed to reduce frequency of chain {}: {}",
+                    manager.hashboard_idx, e
+                );
+            }
+        }
+    }
+
+    /// Act on power above limit: lower frequency of running hashchains or shut the miner down
+    /// when they are already at the floor (see `PowerDecision::decide`)
+    ///
+    /// It runs outside of monitor tick because it has to wait for hashchain locks.
+    async fn power_limit_task(
+        self: Arc<Self>,
+        managers: Vec<Arc<Manager>>,
+        power_config: PowerControlConfig,
+        chain_power: Vec<ChainPower>,
+    ) {
+        let at_floor = Self::power_at_floor(&managers).await;
+        let power_decision = PowerDecision::decide(Some(&power_config), &chain_power, at_floor);
+        if power_decision == PowerDecision::ReduceFrequency {
+            Self::reduce_frequency(&managers).await;
+        }
+
+        let mut inner = self.inner.lock().await;
+        if power_decision == PowerDecision::Shutdown {
+            let reason = ShutdownReason::PowerAboveLimit {
+                watts: PowerDecision::total_watts(&chain_power).unwrap_or_default(),
+                max_watts: power_config.max_watts,
+            };
+            self.handle_failure(&mut inner, None, reason).await;
+        }
+        inner.power_limit_changed = Some(Instant::now());
+        inner.power_limit_pending = false;
+    }
+
+    /// One tick of temperature/fan controller
+    ///
+    /// TODO: Run this tick every time new temperature is submitted to lower temp controller
+    ///   latency.
+    async fn do_tick(self: Arc<Self>) {
+        // decide hashchain state and collect temperatures
+        let mut inner = self.inner.lock().await;
+        let mut temperature_accumulator = TemperatureAccumulator::new();
+        let mut miner_warming_up = false;
+        let mut failed_chains = Vec::new();
+        let mut chain_power = Vec::new();
+        let mut num_running = 0;
+        let mut sensor_present = false;
+        for (idx, chain) in inner.chains.iter().enumerate() {
+            let mut chain = chain.lock().await;
+            chain.state.tick(Instant::now(), &inner.config.timeouts);
+            if let Some(power) = chain.power.as_ref() {
+                chain_power.push(power.clone());
+            }
+
+            if let ChainState::Broken(reason) = chain.state {
+                failed_chains.push((
+                    idx,
+                    ShutdownReason::BrokenChain {
+                        hashboard_idx: chain.hashboard_idx,
+                        reason,
+                    },
+                ));
+                continue;
+            }
+            info!("chain {}: {:?}", chain.hashboard_idx, chain.state);
+            if let ChainState::Running { temperature, .. } = &chain.state {
+                num_running += 1;
+                sensor_present |= temperature.iter().any(|temp| !temp.is_sensor_absent());
+            }
+            let chain_temperature = chain.state.get_temperature(&inner.config.sensor_config);
+            if let (true, Some(temp_config), ChainTemperature::Ok(temperature)) = (
+                inner.config.isolate_failed_chains,
+                inner.config.temp_config(),
+                chain_temperature,
+            ) {
+                // shut down only the chain that reported dangerous temperature
+                if temperature >= temp_config.dangerous_temp {
+                    failed_chains.push((
+                        idx,
+                        ShutdownReason::DangerousChainTemperature {
+                            hashboard_idx: chain.hashboard_idx,
+                            temperature,
+                        },
+                    ));
+                    continue;
+                }
+            }
+            temperature_accumulator.add_chain_temp(chain_temperature);
+            miner_warming_up |= chain.is_warming_up(Instant::now(), &inner.config.timeouts);
+        }
+        // remove chains from the back so that indices stay valid
+        while let Some((idx, reason)) = failed_chains.pop() {
+            if !self.handle_failure(&mut inner, Some(idx), reason).await {
+                return;
+            }
+        }
+        let input_temperature = temperature_accumulator.calc_result();
+
+        // Read fans
+        let fan_feedback = inner
+            .fan_control
+            .as_ref()
+            .map(|fan_control| fan_control.read_feedback())
+            .unwrap_or_default();
+        let num_fans_running = fan_feedback.num_fans_running();
         info!(
             "Monitor: fan={:?} num_fans={} acc.temp.={:?}",
             fan_feedback, num_fans_running, input_temperature,
         );
 
         // all right, temperature has been aggregated, decide what to do
-        let decision_explained =
-            ControlDecision::decide(&inner.config, num_fans_running, input_temperature);
+        let temperature_band = inner
+            .temperature_band
+            .update(inner.config.temp_config(), input_temperature);
+        inner.temperature_band = temperature_band;
+        let now = Instant::now();
+        let missing_fans_grace_expired = inner
+            .missing_fans_since
+            .map(|since| now.duration_since(since) >= inner.config.timeouts.missing_fans_grace)
+            .unwrap_or(false);
+        let (missing_fans_ticks, missing_fans_confirmed) = match inner.config.fan_config.as_ref() {
+            Some(fan_config) => {
+                let ticks =
+                    fan_config.count_missing_fans_ticks(inner.missing_fans_ticks, num_fans_running);
+                (ticks, fan_config.missing_fans_confirmed(ticks))
+            }
+            None => (0, false),
+        };
+        inner.missing_fans_ticks = missing_fans_ticks;
+        let decision_explained = ControlDecision::decide_with_grace(
+            &inner.config,
+            num_fans_running,
+            input_temperature,
+            temperature_band,
+            missing_fans_grace_expired,
+            missing_fans_confirmed,
+        );
         info!("Monitor: {:?}", decision_explained);
+        // start grace period on first tick with missing fans, end it when they are back
+        inner.missing_fans_since = if decision_explained.caused_by_missing_fans() {
+            inner.missing_fans_since.or(Some(now))
+        } else {
+            None
+        };
         match decision_explained.decision {
             ControlDecision::Shutdown => {
-                self.shutdown(&mut inner, decision_explained.reason.into())
-                    .await;
+                self.handle_failure(
+                    &mut inner,
+                    None,
+                    ShutdownReason::ControlDecision(decision_explained.reason),
+                )
+                .await;
             }
             ControlDecision::UseFixedSpeed(fan_speed) => {
-                self.set_fan_speed(&mut inner, fan_speed);
+                if input_temperature == ChainTemperature::Unknown {
+                    // we are blind, do not wait for fans to ramp up
+                    self.set_fan_speed(&mut inner, fan_speed);
+                } else {
+                    self.ramp_fan_speed(&mut inner, fan_speed);
+                }
             }
             ControlDecision::UsePid {
                 target_temp,
@@ -600,31 +1530,110 @@ impl Monitor {
                     "Monitor: input={} target={} output={:?}",
                     input_temp, target_temp, speed
                 );
-                self.set_fan_speed(&mut inner, speed);
+                self.ramp_fan_speed(&mut inner, speed);
+            }
+            ControlDecision::WaitForFans => {
+                self.set_fan_speed(&mut inner, fan::Speed::FULL_SPEED);
             }
             ControlDecision::Nothing => {}
         }
 
+        // Keep total power under limit. Only readings taken after the last frequency change
+        // are considered and one change is done at a time.
+        if let (Some(power_config), false) =
+            (inner.config.power_config.clone(), inner.power_limit_pending)
+        {
+            let fresh_power =
+                PowerDecision::fresh_readings(&chain_power, inner.power_limit_changed);
+            if PowerDecision::decide(Some(&power_config), &fresh_power, false)
+                != PowerDecision::Nothing
+            {
+                debug!(
+                    "Monitor: power {:?} W above limit {} W",
+                    PowerDecision::total_watts(&fresh_power),
+                    power_config.max_watts
+                );
+                inner.power_limit_pending = true;
+                tokio::spawn(self.clone().power_limit_task(
+                    inner.managers.clone(),
+                    power_config,
+                    fresh_power,
+                ));
+            }
+        }
+
         // Broadcast `Status`
         let monitor_status = Status {
             fan_feedback,
             fan_speed: inner.current_fan_speed,
             input_temperature,
+            temperature_band,
             temperature_accumulator,
             decision_explained,
+            isolated_chains: inner.isolated_chains.clone(),
+            last_shutdown_action: inner.last_shutdown_action,
+            chain_power,
+            no_sensors: num_running > 0 && !sensor_present,
             config: inner.config.clone(),
         };
         self.status_sender
             .broadcast(Some(monitor_status))
             .expect("broadcast failed");
+
+        // Remember temperature for later diagnosis
+        if inner.temperature_history.len() >= TEMPERATURE_HISTORY_LEN {
+            inner.temperature_history.pop_front();
+        }
+        let fan_speed = inner.current_fan_speed;
+        inner.temperature_history.push_back(TemperatureSample {
+            time: Instant::now(),
+            temperature: input_temperature,
+            fan_speed,
+        });
     }
 
     /// Task performing temp control
     async fn tick_task(self: Arc<Self>) {
         loop {
-            self.do_tick().await;
+            self.clone().do_tick().await;
+            let tick_length = self.inner.lock().await.config.timeouts.tick_length;
             // TODO: find some of kind "run every x secs" function
-            delay_for(TICK_LENGTH).await;
+            delay_for(tick_length).await;
+        }
+    }
+
+    /// Task reading power of running hashchains from `power_meter` (once per tick)
+    async fn power_task(self: Arc<Self>, power_meter: Arc<dyn power::PowerMeter>) {
+        loop {
+            let (chains, tick_length) = {
+                let inner = self.inner.lock().await;
+                (inner.chains.clone(), inner.config.timeouts.tick_length)
+            };
+            for chain in chains {
+                let hashboard_idx = {
+                    let chain = chain.lock().await;
+                    match chain.state {
+                        ChainState::Running { .. } => chain.hashboard_idx,
+                        _ => continue,
+                    }
+                };
+                // do not hold chain lock while reading power
+                match power_meter.read_power(hashboard_idx).await {
+                    Ok(reading) => {
+                        let mut chain = chain.lock().await;
+                        // hashchain may have been turned off in the meantime
+                        if let ChainState::Running { .. } = chain.state {
+                            chain.power = Some(ChainPower {
+                                hashboard_idx,
+                                reading,
+                                time: Instant::now(),
+                            });
+                        }
+                    }
+                    Err(e) => warn!("Monitor: chain {}: power read failed: {}", hashboard_idx, e),
+                }
+            }
+            delay_for(tick_length).await;
         }
     }
 
@@ -632,15 +1641,24 @@ impl Monitor {
     async fn recv_task(chain: Arc<Mutex<Chain>>, mut rx: mpsc::UnboundedReceiver<Message>) {
         while let Some(message) = rx.next().await {
             let mut chain = chain.lock().await;
+            if let Message::Off = message {
+                chain.power = None;
+            }
             chain.state.transition(Instant::now(), message);
         }
     }
 
     /// Registers hashchain within monitor
     /// The `hashboard_idx` parameter is for debugging purposes
-    pub async fn register_hashchain(&self, hashboard_idx: usize) -> mpsc::UnboundedSender<Message> {
+    ///
+    /// * `halt_sender` - halt context of this hashchain only (to isolate it when it fails)
+    pub async fn register_hashchain(
+        &self,
+        hashboard_idx: usize,
+        halt_sender: Arc<halt::Sender>,
+    ) -> mpsc::UnboundedSender<Message> {
         let (tx, rx) = mpsc::unbounded();
-        let chain = Arc::new(Mutex::new(Chain::new(hashboard_idx)));
+        let chain = Arc::new(Mutex::new(Chain::new(hashboard_idx, halt_sender)));
         {
             let mut inner = self.inner.lock().await;
             inner.chains.push(chain.clone());
@@ -649,6 +1667,82 @@ impl Monitor {
         tx
     }
 
+    /// Register hashchain managers so that monitor can lower their frequency when power is
+    /// above limit
+    pub async fn register_managers(&self, managers: Vec<Arc<Manager>>) {
+        self.inner.lock().await.managers = managers;
+    }
+
+    /// Let hashchain opt out of warm-up (or opt back in), e.g. a board that has been running
+    /// before restart and is already warm doesn't have to keep fans elevated for the others.
+    ///
+    /// Returns `false` if there's no such hashchain registered.
+    pub async fn set_warm_up_opt_out(&self, hashboard_idx: usize, opt_out: bool) -> bool {
+        let inner = self.inner.lock().await;
+        for chain in inner.chains.iter() {
+            let mut chain = chain.lock().await;
+            if chain.hashboard_idx == hashboard_idx {
+                info!(
+                    "Monitor: chain {} warm-up opt-out set to {}",
+                    hashboard_idx, opt_out
+                );
+                chain.warm_up_opt_out = opt_out;
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Dry run: decide what monitor would do with `num_fans` fans running at input temperature
+    /// `temp` under current configuration. Fans are not touched and nothing is shut down.
+    /// Missing fans are evaluated as if their grace period has already expired.
+    pub async fn simulate_decision(
+        &self,
+        num_fans: usize,
+        temp: ChainTemperature,
+    ) -> ControlDecisionExplained {
+        let inner = self.inner.lock().await;
+        let band = inner
+            .temperature_band
+            .update(inner.config.temp_config(), temp);
+        ControlDecision::decide(&inner.config, num_fans, temp, band)
+    }
+
+    /// Subscribe to monitor status updates
+    pub fn subscribe(&self) -> StatusStream {
+        StatusStream::new(self.status_receiver.clone())
+    }
+
+    /// Return reason and time of the last shutdown declared by monitor (if any)
+    pub async fn last_shutdown(&self) -> Option<ShutdownRecord> {
+        self.inner.lock().await.last_shutdown.clone()
+    }
+
+    /// Return temperature history (oldest first)
+    pub async fn temperature_history(&self) -> Vec<TemperatureSample> {
+        self.inner
+            .lock()
+            .await
+            .temperature_history
+            .iter()
+            .cloned()
+            .collect()
+    }
+
+    /// Retune fan PID at runtime
+    ///
+    /// Note: gains are negative, because the PID works in reverse direction (the lower the PWM,
+    /// the higher the temperature).
+    pub async fn set_pid_params(&self, kp: f64, ki: f64, kd: f64) {
+        info!("Monitor: setting PID params kp={} ki={} kd={}", kp, ki, kd);
+        self.inner.lock().await.pid.set_pid_params(kp, ki, kd);
+    }
+
+    /// Return current `(kp, ki, kd)` of fan PID
+    pub async fn get_pid_params(&self) -> (f64, f64, f64) {
+        self.inner.lock().await.pid.pid_params()
+    }
+
     pub async fn with_configuration<F, R>(&self, f: F) -> R
     where
         F: FnOnce(&mut Config) -> R,
@@ -682,11 +1776,15 @@ mod test {
     /// Test that faking S9 chip temperature from board temperature works
     #[test]
     fn test_monitor_s9_chip_temp() {
+        let sensor_config = TempSensorConfig {
+            preference: TempSensorPreference::Remote,
+            disagreement_threshold: 20.0,
+        };
         let temp = sensor::Temperature {
             local: sensor::Measurement::Ok(10.0),
             remote: sensor::Measurement::Ok(22.0),
         };
-        match ChainTemperature::from_s9_sensor(temp) {
+        match ChainTemperature::from_s9_sensor(temp, &sensor_config) {
             ChainTemperature::Ok(t) => assert_relative_eq!(t, 22.0),
             _ => panic!("missing temperature"),
         };
@@ -694,7 +1792,7 @@ mod test {
             local: sensor::Measurement::Ok(10.0),
             remote: sensor::Measurement::OpenCircuit,
         };
-        match ChainTemperature::from_s9_sensor(temp) {
+        match ChainTemperature::from_s9_sensor(temp, &sensor_config) {
             ChainTemperature::Ok(t) => assert_relative_eq!(t, 25.0),
             _ => panic!("missing temperature"),
         };
@@ -703,7 +1801,71 @@ mod test {
             remote: sensor::Measurement::OpenCircuit,
         };
         assert_eq!(
-            ChainTemperature::from_s9_sensor(temp),
+            ChainTemperature::from_s9_sensor(temp, &sensor_config),
+            ChainTemperature::Unknown
+        );
+
+        // noisy remote sensor is overridden only when local sensor is preferred
+        let temp = sensor::Temperature {
+            local: sensor::Measurement::Ok(60.0),
+            remote: sensor::Measurement::Ok(120.0),
+        };
+        assert_eq!(
+            ChainTemperature::from_s9_sensor(temp.clone(), &sensor_config),
+            ChainTemperature::Ok(120.0)
+        );
+        let local_sensor_config = TempSensorConfig {
+            preference: TempSensorPreference::Local,
+            ..sensor_config.clone()
+        };
+        assert_eq!(
+            ChainTemperature::from_s9_sensor(temp, &local_sensor_config),
+            ChainTemperature::Ok(75.0)
+        );
+        let temp = sensor::Temperature {
+            local: sensor::Measurement::Ok(60.0),
+            remote: sensor::Measurement::Ok(80.0),
+        };
+        assert_eq!(
+            ChainTemperature::from_s9_sensor(temp, &local_sensor_config),
+            ChainTemperature::Ok(80.0)
+        );
+    }
+
+    /// Test that the hottest of multiple sensors is taken and dead sensors are skipped
+    #[test]
+    fn test_monitor_s9_multiple_sensors() {
+        let sensor_config = TempSensorConfig {
+            preference: TempSensorPreference::Remote,
+            disagreement_threshold: 20.0,
+        };
+        let reading = |local, remote| sensor::Temperature {
+            local: sensor::Measurement::Ok(local),
+            remote: sensor::Measurement::Ok(remote),
+        };
+        assert_eq!(
+            ChainTemperature::from_s9_sensors(&[], &sensor_config),
+            ChainTemperature::Unknown
+        );
+        assert_eq!(
+            ChainTemperature::from_s9_sensors(
+                &[reading(50.0, 70.0), reading(55.0, 72.0)],
+                &sensor_config
+            ),
+            ChainTemperature::Ok(72.0)
+        );
+        assert_eq!(
+            ChainTemperature::from_s9_sensors(
+                &[sensor::INVALID_TEMPERATURE_READING, reading(50.0, 70.0)],
+                &sensor_config
+            ),
+            ChainTemperature::Ok(70.0)
+        );
+        assert_eq!(
+            ChainTemperature::from_s9_sensors(
+                &[sensor::INVALID_TEMPERATURE_READING],
+                &sensor_config
+            ),
             ChainTemperature::Unknown
         );
     }
@@ -716,10 +1878,10 @@ mod test {
     /// Test that miner transitions states as expected
     #[test]
     fn test_monitor_state_transition() {
-        let temp = sensor::Temperature {
+        let temp = vec![sensor::Temperature {
             local: sensor::Measurement::Ok(10.0),
             remote: sensor::Measurement::Ok(22.0),
-        };
+        }];
         let now = Instant::now();
         let later = now + Duration::from_secs(1);
         let running_state = ChainState::Running {
@@ -773,39 +1935,54 @@ mod test {
     /// Test "warm up" period
     #[test]
     fn test_monitor_warm_up() {
-        let temp = sensor::Temperature {
+        let temp = vec![sensor::Temperature {
             local: sensor::Measurement::Ok(10.0),
             remote: sensor::Measurement::Ok(22.0),
-        };
+        }];
         let now = Instant::now();
         let later = now + Duration::from_secs(20);
         let warmed_time = now + Duration::from_secs(200);
+        let timeouts = Timeouts::default();
         let running_state = ChainState::Running {
             started: now,
             last_heartbeat: now,
             temperature: temp.clone(),
         };
 
-        assert_eq!(ChainState::Off.is_warming_up(now), false);
-        assert_eq!(ChainState::On(now).is_warming_up(now), true);
-        assert_eq!(ChainState::On(now).is_warming_up(warmed_time), true);
-        assert_eq!(running_state.clone().is_warming_up(now), true);
-        assert_eq!(running_state.clone().is_warming_up(later), true);
-        assert_eq!(running_state.clone().is_warming_up(warmed_time), false);
+        assert_eq!(ChainState::Off.is_warming_up(now, &timeouts), false);
+        assert_eq!(ChainState::On(now).is_warming_up(now, &timeouts), true);
+        assert_eq!(
+            ChainState::On(now).is_warming_up(warmed_time, &timeouts),
+            true
+        );
+        assert_eq!(running_state.clone().is_warming_up(now, &timeouts), true);
+        assert_eq!(running_state.clone().is_warming_up(later, &timeouts), true);
+        assert_eq!(
+            running_state.clone().is_warming_up(warmed_time, &timeouts),
+            false
+        );
+
+        // hashchain that opted out doesn't count as warming up
+        let (halt_sender, _halt_receiver) = halt::make_pair(Duration::from_secs(1));
+        let mut chain = Chain::new(0, halt_sender);
+        chain.state = running_state;
+        assert_eq!(chain.is_warming_up(later, &timeouts), true);
+        chain.warm_up_opt_out = true;
+        assert_eq!(chain.is_warming_up(later, &timeouts), false);
     }
 
     fn tick(mut state: ChainState, later: Instant) -> ChainState {
-        state.tick(later);
+        state.tick(later, &Timeouts::default());
         state
     }
 
     /// Test timeouts
     #[test]
     fn test_monitor_timeouts() {
-        let temp = sensor::Temperature {
+        let temp = vec![sensor::Temperature {
             local: sensor::Measurement::Ok(10.0),
             remote: sensor::Measurement::Ok(22.0),
-        };
+        }];
         let now = Instant::now();
         let long = now + Duration::from_secs(10_000);
         let short = now + Duration::from_secs(2);
@@ -837,6 +2014,73 @@ mod test {
             tick(running_state.clone(), now + Duration::from_secs(20)),
             ChainState::Broken(_)
         );
+
+        // slow-starting chains can be given more time
+        let slow_start = Timeouts {
+            start: Duration::from_secs(20_000),
+            ..Timeouts::default()
+        };
+        let mut state = ChainState::On(now);
+        state.tick(long, &slow_start);
+        assert_variant!(state, ChainState::On(_));
+    }
+
+    /// Test that temperature update interval is checked against run update timeout
+    #[test]
+    fn test_timeouts_validate() {
+        assert!(Timeouts::default().validate().is_ok());
+        let exact = Timeouts {
+            run_update: Duration::from_secs(10),
+            temp_update_interval: Duration::from_secs(5),
+            ..Timeouts::default()
+        };
+        assert!(exact.validate().is_ok());
+        let too_slow = Timeouts {
+            run_update: Duration::from_secs(10),
+            temp_update_interval: Duration::from_secs(6),
+            ..Timeouts::default()
+        };
+        assert!(too_slow.validate().is_err());
+    }
+
+    /// Test that fan speed ramps only when configured
+    #[test]
+    fn test_fan_speed_ramp() {
+        let mut fan_config = FanControlConfig {
+            mode: FanControlMode::FixedSpeed(fan::Speed::FULL_SPEED),
+            min_fans: 1,
+            cold_fan_speed: fan::Speed::new(30),
+            max_speed_step_per_tick: None,
+            min_fans_ticks: 2,
+        };
+        let current = Some(fan::Speed::new(50));
+        assert_eq!(
+            fan_config.ramp_speed(current, fan::Speed::FULL_SPEED),
+            fan::Speed::FULL_SPEED
+        );
+
+        fan_config.max_speed_step_per_tick = Some(10);
+        assert_eq!(
+            fan_config.ramp_speed(current, fan::Speed::FULL_SPEED),
+            fan::Speed::new(60)
+        );
+        assert_eq!(
+            fan_config.ramp_speed(current, fan::Speed::STOPPED),
+            fan::Speed::new(40)
+        );
+        assert_eq!(
+            fan_config.ramp_speed(current, fan::Speed::new(55)),
+            fan::Speed::new(55)
+        );
+        assert_eq!(
+            fan_config.ramp_speed(Some(fan::Speed::new(5)), fan::Speed::STOPPED),
+            fan::Speed::STOPPED
+        );
+        // nothing to ramp from
+        assert_eq!(
+            fan_config.ramp_speed(None, fan::Speed::FULL_SPEED),
+            fan::Speed::FULL_SPEED
+        );
     }
 
     fn test_acc(temp1: ChainTemperature, temp2: ChainTemperature) -> ChainTemperature {
@@ -897,153 +2141,735 @@ mod test {
         let dang_temp = ChainTemperature::Ok(150.0);
         let hot_temp = ChainTemperature::Ok(95.0);
         let low_temp = ChainTemperature::Ok(50.0);
+        let cold_temp = ChainTemperature::Ok(10.0);
+        let normal = TemperatureBand::Normal;
+        let sensor_config = TempSensorConfig {
+            preference: TempSensorPreference::Remote,
+            disagreement_threshold: 20.0,
+        };
         let temp_config = TempControlConfig {
             dangerous_temp: 100.0,
             hot_temp: 80.0,
+            hot_temp_hysteresis: 5.0,
+            cold_temp: 20.0,
         };
         let fan_speed = fan::Speed::new(50);
+        let cold_fan_speed = fan::Speed::new(30);
         let fan_config = FanControlConfig {
             mode: FanControlMode::FixedSpeed(fan_speed),
             min_fans: 2,
+            cold_fan_speed,
+            max_speed_step_per_tick: None,
+            min_fans_ticks: 2,
         };
         let fans_off = fan::Speed::STOPPED;
         let fans_off_config = Config {
             fans_on_while_warming_up: true,
+            isolate_failed_chains: false,
+            shutdown_fan_mode: ShutdownFanMode::Stop,
+            power_config: None,
+            sensor_config: sensor_config.clone(),
+            timeouts: Timeouts::default(),
             fan_config: Some(FanControlConfig {
                 mode: FanControlMode::FixedSpeed(fans_off),
                 min_fans: 2,
+                cold_fan_speed,
+                max_speed_step_per_tick: None,
+                min_fans_ticks: 2,
             }),
             temp_config: None,
         };
         let all_off_config = Config {
             fans_on_while_warming_up: true,
+            isolate_failed_chains: false,
+            shutdown_fan_mode: ShutdownFanMode::Stop,
+            power_config: None,
+            sensor_config: sensor_config.clone(),
+            timeouts: Timeouts::default(),
             fan_config: None,
             temp_config: None,
         };
         let fans_on_config = Config {
             fans_on_while_warming_up: true,
+            isolate_failed_chains: false,
+            shutdown_fan_mode: ShutdownFanMode::Stop,
+            power_config: None,
+            sensor_config: sensor_config.clone(),
+            timeouts: Timeouts::default(),
             fan_config: Some(fan_config.clone()),
             temp_config: None,
         };
         let temp_on_config = Config {
             fans_on_while_warming_up: true,
+            isolate_failed_chains: false,
+            shutdown_fan_mode: ShutdownFanMode::Stop,
+            power_config: None,
+            sensor_config: sensor_config.clone(),
+            timeouts: Timeouts::default(),
             fan_config: None,
             temp_config: Some(temp_config.clone()),
         };
         let both_on_config = Config {
             fans_on_while_warming_up: true,
+            isolate_failed_chains: false,
+            shutdown_fan_mode: ShutdownFanMode::Stop,
+            power_config: None,
+            sensor_config: sensor_config.clone(),
+            timeouts: Timeouts::default(),
             fan_config: Some(fan_config.clone()),
             temp_config: Some(temp_config.clone()),
         };
         let both_on_pid_config = Config {
             fans_on_while_warming_up: true,
+            isolate_failed_chains: false,
+            shutdown_fan_mode: ShutdownFanMode::Stop,
+            power_config: None,
+            sensor_config: sensor_config.clone(),
+            timeouts: Timeouts::default(),
             fan_config: Some(FanControlConfig {
-                mode: FanControlMode::TargetTemperature(75.0),
+                mode: FanControlMode::TargetTemperature {
+                    target_temp: 75.0,
+                    temp_config: temp_config.clone(),
+                },
                 min_fans: 2,
+                cold_fan_speed,
+                max_speed_step_per_tick: None,
+                min_fans_ticks: 2,
             }),
-            temp_config: Some(temp_config.clone()),
+            temp_config: None,
         };
 
         assert_variant!(
-            ControlDecision::decide(&all_off_config, 0, dang_temp.clone()).decision,
+            ControlDecision::decide(&all_off_config, 0, dang_temp.clone(), normal).decision,
             ControlDecision::Nothing
         );
         assert_variant!(
-            ControlDecision::decide(&all_off_config, 0, ChainTemperature::Failed).decision,
+            ControlDecision::decide(&all_off_config, 0, ChainTemperature::Failed, normal).decision,
             ControlDecision::Nothing
         );
 
         assert_eq!(
-            ControlDecision::decide(&fans_on_config, 2, dang_temp.clone()).decision,
+            ControlDecision::decide(&fans_on_config, 2, dang_temp.clone(), normal).decision,
             ControlDecision::UseFixedSpeed(fan_speed)
         );
         assert_eq!(
-            ControlDecision::decide(&fans_on_config, 0, dang_temp.clone()).decision,
+            ControlDecision::decide(&fans_on_config, 0, dang_temp.clone(), normal).decision,
+            ControlDecision::Shutdown
+        );
+        assert_eq!(
+            ControlDecision::decide(&fans_on_config, 1, dang_temp.clone(), normal).decision,
             ControlDecision::Shutdown
         );
+        // missing fans are tolerated during grace period
         assert_eq!(
-            ControlDecision::decide(&fans_on_config, 1, dang_temp.clone()).decision,
+            ControlDecision::decide_with_grace(&fans_on_config, 1, low_temp, normal, false, true)
+                .decision,
+            ControlDecision::WaitForFans
+        );
+        assert_eq!(
+            ControlDecision::decide_with_grace(&fans_on_config, 1, low_temp, normal, true, true)
+                .decision,
             ControlDecision::Shutdown
         );
         assert_eq!(
-            ControlDecision::decide(&fans_on_config, 2, ChainTemperature::Failed).decision,
+            ControlDecision::decide_with_grace(&fans_on_config, 2, low_temp, normal, false, true)
+                .decision,
+            ControlDecision::UseFixedSpeed(fan_speed)
+        );
+        // single glitchy fan feedback is ignored, repeated one shuts the miner down
+        let fan_config = fans_on_config.fan_config.as_ref().unwrap();
+        let mut ticks = 0;
+        for (num_fans_running, expected) in [
+            (2, ControlDecision::UseFixedSpeed(fan_speed)),
+            (0, ControlDecision::UseFixedSpeed(fan_speed)),
+            (2, ControlDecision::UseFixedSpeed(fan_speed)),
+            (0, ControlDecision::UseFixedSpeed(fan_speed)),
+            (0, ControlDecision::Shutdown),
+        ]
+        .iter()
+        {
+            ticks = fan_config.count_missing_fans_ticks(ticks, *num_fans_running);
+            assert_eq!(
+                ControlDecision::decide_with_grace(
+                    &fans_on_config,
+                    *num_fans_running,
+                    low_temp,
+                    normal,
+                    true,
+                    fan_config.missing_fans_confirmed(ticks),
+                )
+                .decision,
+                *expected
+            );
+        }
+        assert_eq!(
+            ControlDecision::decide(&fans_on_config, 2, ChainTemperature::Failed, normal).decision,
             ControlDecision::UseFixedSpeed(fan_speed)
         );
 
         // fans set to 0 -> do not check if fans are running
         assert_eq!(
-            ControlDecision::decide(&fans_off_config, 0, dang_temp.clone()).decision,
+            ControlDecision::decide(&fans_off_config, 0, dang_temp.clone(), normal).decision,
             ControlDecision::UseFixedSpeed(fans_off)
         );
 
         assert_eq!(
-            ControlDecision::decide(&temp_on_config, 0, ChainTemperature::Failed).decision,
+            ControlDecision::decide(&temp_on_config, 0, ChainTemperature::Failed, normal).decision,
             ControlDecision::Shutdown
         );
         assert_variant!(
-            ControlDecision::decide(&temp_on_config, 0, ChainTemperature::Unknown).decision,
+            ControlDecision::decide(&temp_on_config, 0, ChainTemperature::Unknown, normal).decision,
             ControlDecision::Nothing
         );
         assert_eq!(
-            ControlDecision::decide(&temp_on_config, 0, dang_temp).decision,
+            ControlDecision::decide(&temp_on_config, 0, dang_temp, normal).decision,
             ControlDecision::Shutdown
         );
         assert_variant!(
-            ControlDecision::decide(&temp_on_config, 0, hot_temp).decision,
+            ControlDecision::decide(&temp_on_config, 0, hot_temp, normal).decision,
             ControlDecision::Nothing
         );
 
         assert_eq!(
-            ControlDecision::decide(&both_on_config, 0, low_temp).decision,
+            ControlDecision::decide(&both_on_config, 0, low_temp, normal).decision,
             ControlDecision::Shutdown
         );
         assert_eq!(
-            ControlDecision::decide(&both_on_config, 2, dang_temp).decision,
+            ControlDecision::decide(&both_on_config, 2, dang_temp, normal).decision,
             ControlDecision::Shutdown
         );
         assert_eq!(
-            ControlDecision::decide(&both_on_config, 2, ChainTemperature::Failed).decision,
+            ControlDecision::decide(&both_on_config, 2, ChainTemperature::Failed, normal).decision,
             ControlDecision::Shutdown
         );
         assert_eq!(
-            ControlDecision::decide(&both_on_config, 2, ChainTemperature::Unknown).decision,
+            ControlDecision::decide(&both_on_config, 2, ChainTemperature::Unknown, normal).decision,
             ControlDecision::UseFixedSpeed(fan::Speed::FULL_SPEED)
         );
         assert_eq!(
-            ControlDecision::decide(&both_on_config, 2, hot_temp).decision,
+            ControlDecision::decide(&both_on_config, 2, hot_temp, normal).decision,
+            ControlDecision::UseFixedSpeed(fan_speed)
+        );
+        assert_eq!(
+            ControlDecision::decide(&both_on_config, 2, low_temp, normal).decision,
             ControlDecision::UseFixedSpeed(fan_speed)
         );
         assert_eq!(
-            ControlDecision::decide(&both_on_config, 2, low_temp).decision,
+            ControlDecision::decide(&both_on_config, 2, cold_temp, normal).decision,
             ControlDecision::UseFixedSpeed(fan_speed)
         );
 
         assert_eq!(
-            ControlDecision::decide(&both_on_pid_config, 0, low_temp).decision,
+            ControlDecision::decide(&both_on_pid_config, 0, low_temp, normal).decision,
             ControlDecision::Shutdown
         );
         assert_eq!(
-            ControlDecision::decide(&both_on_pid_config, 2, dang_temp).decision,
+            ControlDecision::decide(&both_on_pid_config, 2, dang_temp, normal).decision,
             ControlDecision::Shutdown
         );
         assert_eq!(
-            ControlDecision::decide(&both_on_pid_config, 2, ChainTemperature::Failed).decision,
+            ControlDecision::decide(&both_on_pid_config, 2, ChainTemperature::Failed, normal)
+                .decision,
             ControlDecision::Shutdown
         );
         assert_eq!(
-            ControlDecision::decide(&both_on_pid_config, 2, ChainTemperature::Unknown).decision,
+            ControlDecision::decide(&both_on_pid_config, 2, ChainTemperature::Unknown, normal)
+                .decision,
             ControlDecision::UseFixedSpeed(fan::Speed::FULL_SPEED)
         );
         assert_eq!(
-            ControlDecision::decide(&both_on_pid_config, 2, hot_temp).decision,
+            ControlDecision::decide(&both_on_pid_config, 2, hot_temp, normal).decision,
             ControlDecision::UseFixedSpeed(fan::Speed::FULL_SPEED)
         );
         assert_eq!(
-            ControlDecision::decide(&both_on_pid_config, 2, low_temp).decision,
+            ControlDecision::decide(&both_on_pid_config, 2, low_temp, normal).decision,
             ControlDecision::UsePid {
                 target_temp: 75.0,
                 input_temp: 50.0
             }
         );
+        assert_eq!(
+            ControlDecision::decide(&both_on_pid_config, 2, cold_temp, normal).decision,
+            ControlDecision::UseFixedSpeed(cold_fan_speed)
+        );
+        assert_eq!(
+            ControlDecision::decide(&both_on_pid_config, 0, cold_temp, normal).decision,
+            ControlDecision::Shutdown
+        );
+
+        // HOT band is left only after temperature drops below `hot_temp - hot_temp_hysteresis`
+        let hot_hysteresis_temp = ChainTemperature::Ok(77.0);
+        let band = TemperatureBand::Normal.update(Some(&temp_config), hot_temp);
+        assert_eq!(band, TemperatureBand::Hot);
+        let band = band.update(Some(&temp_config), hot_hysteresis_temp);
+        assert_eq!(band, TemperatureBand::Hot);
+        assert_eq!(
+            band.update(Some(&temp_config), ChainTemperature::Unknown),
+            TemperatureBand::Hot
+        );
+        assert_eq!(
+            ControlDecision::decide(&both_on_pid_config, 2, hot_hysteresis_temp, band).decision,
+            ControlDecision::UseFixedSpeed(fan::Speed::FULL_SPEED)
+        );
+        assert_eq!(
+            TemperatureBand::Normal.update(Some(&temp_config), hot_hysteresis_temp),
+            TemperatureBand::Normal
+        );
+        let band = band.update(Some(&temp_config), low_temp);
+        assert_eq!(band, TemperatureBand::Normal);
+        assert_eq!(band.update(None, hot_temp), TemperatureBand::Normal);
+    }
+
+    /// Test that only decisions caused by missing fans start grace period of missing fans
+    #[test]
+    fn test_decision_caused_by_missing_fans() {
+        let config = Config::new(
+            Some(TempControlConfig {
+                dangerous_temp: 100.0,
+                hot_temp: 80.0,
+                hot_temp_hysteresis: 5.0,
+                cold_temp: 20.0,
+            }),
+            Some(FanControlConfig {
+                mode: FanControlMode::FixedSpeed(fan::Speed::new(50)),
+                min_fans: 2,
+                cold_fan_speed: fan::Speed::new(30),
+                max_speed_step_per_tick: None,
+                min_fans_ticks: 2,
+            }),
+            TempSensorConfig {
+                preference: TempSensorPreference::Remote,
+                disagreement_threshold: 20.0,
+            },
+            Timeouts::default(),
+        );
+        let normal = TemperatureBand::Normal;
+        let low_temp = ChainTemperature::Ok(50.0);
+
+        // dangerous temperature takes precedence over missing fans
+        let explained = ControlDecision::decide(&config, 0, ChainTemperature::Ok(150.0), normal);
+        assert_eq!(explained.decision, ControlDecision::Shutdown);
+        assert!(!explained.caused_by_missing_fans());
+        let explained = ControlDecision::decide(&config, 0, ChainTemperature::Failed, normal);
+        assert_eq!(explained.decision, ControlDecision::Shutdown);
+        assert!(!explained.caused_by_missing_fans());
+
+        let explained = ControlDecision::decide(&config, 0, low_temp, normal);
+        assert_eq!(explained.decision, ControlDecision::Shutdown);
+        assert!(explained.caused_by_missing_fans());
+        let explained =
+            ControlDecision::decide_with_grace(&config, 0, low_temp, normal, false, true);
+        assert_eq!(explained.decision, ControlDecision::WaitForFans);
+        assert!(explained.caused_by_missing_fans());
+
+        let explained = ControlDecision::decide(&config, 2, low_temp, normal);
+        assert!(!explained.caused_by_missing_fans());
+    }
+
+    /// Monitor running without fan controller keeps watching temperature configured for
+    /// 'auto' mode
+    #[test]
+    fn test_disable_fan_control() {
+        let normal = TemperatureBand::Normal;
+        let temp_config = TempControlConfig {
+            dangerous_temp: 100.0,
+            hot_temp: 80.0,
+            hot_temp_hysteresis: 5.0,
+            cold_temp: 20.0,
+        };
+        let fan_config = FanControlConfig {
+            mode: FanControlMode::TargetTemperature {
+                target_temp: 75.0,
+                temp_config,
+            },
+            min_fans: 2,
+            cold_fan_speed: fan::Speed::new(30),
+            max_speed_step_per_tick: None,
+            min_fans_ticks: 2,
+        };
+        let mut config = Config::new(
+            None,
+            Some(fan_config),
+            TempSensorConfig {
+                preference: TempSensorPreference::Remote,
+                disagreement_threshold: 20.0,
+            },
+            Timeouts::default(),
+        );
+
+        config.disable_fan_control();
+        assert!(config.fan_config().is_none());
+        assert_eq!(config.temp_config().map(|v| v.dangerous_temp), Some(100.0));
+        assert_eq!(
+            ControlDecision::decide(&config, 0, ChainTemperature::Ok(150.0), normal).decision,
+            ControlDecision::Shutdown
+        );
+        assert_eq!(
+            ControlDecision::decide(&config, 0, ChainTemperature::Failed, normal).decision,
+            ControlDecision::Shutdown
+        );
+        assert_eq!(
+            ControlDecision::decide(&config, 0, ChainTemperature::Ok(50.0), normal).decision,
+            ControlDecision::Nothing
+        );
+    }
+
+    /// Test that tasks setting fans are halted before termination handler sets fans for shutdown
+    #[tokio::test]
+    async fn test_termination_after_tick() {
+        let (halt_sender, halt_receiver) = halt::make_pair(Duration::from_secs(1));
+        let mut config = Config::new(
+            None,
+            None,
+            TempSensorConfig {
+                preference: TempSensorPreference::Remote,
+                disagreement_threshold: 20.0,
+            },
+            Timeouts::default(),
+        );
+        config.shutdown_fan_mode = ShutdownFanMode::Cooldown {
+            speed: fan::Speed::new(50),
+            duration: Duration::from_millis(10),
+        };
+        let _monitor =
+            Monitor::new_and_start(config, None, halt_sender.clone(), halt_receiver).await;
+
+        let clients = halt_sender.registered_clients().await;
+        let position = |name: &str| {
+            clients
+                .iter()
+                .position(|client| client == name)
+                .expect("BUG: missing halt client")
+        };
+        assert!(position("monitor") < position("monitor termination"));
+        halt_sender.send_halt().await;
+    }
+
+    /// Test that reason and time of miner shutdown are kept for reporting
+    #[tokio::test]
+    async fn test_last_shutdown() {
+        // monitor tasks are registered elsewhere so that the shutdown doesn't halt them
+        let (miner_shutdown, _miner_halt_receiver) = halt::make_pair(Duration::from_secs(1));
+        let (halt_sender, halt_receiver) = halt::make_pair(Duration::from_secs(1));
+        let config = Config::new(
+            None,
+            None,
+            TempSensorConfig {
+                preference: TempSensorPreference::Remote,
+                disagreement_threshold: 20.0,
+            },
+            Timeouts::default(),
+        );
+        let monitor =
+            Monitor::new_and_start(config, None, miner_shutdown.clone(), halt_receiver).await;
+        assert!(monitor.last_shutdown().await.is_none());
+
+        let before = SystemTime::now();
+        {
+            let mut inner = monitor.inner.lock().await;
+            monitor
+                .shutdown(&mut inner, ShutdownReason::ControlDecision("test"))
+                .await;
+        }
+        let record = monitor
+            .last_shutdown()
+            .await
+            .expect("BUG: shutdown not recorded");
+        assert_eq!(record.reason, ShutdownReason::ControlDecision("test"));
+        assert!(record.time >= before);
+        assert!(record.unix_time() > 0);
+        assert_eq!(
+            miner_shutdown.last_halt_reason().await,
+            Some("test".to_string())
+        );
+        halt_sender.send_halt().await;
+    }
+
+    /// Test that fan PID can be retuned on running monitor
+    #[tokio::test]
+    async fn test_pid_params() {
+        let (halt_sender, halt_receiver) = halt::make_pair(Duration::from_secs(1));
+        let config = Config::new(
+            None,
+            None,
+            TempSensorConfig {
+                preference: TempSensorPreference::Remote,
+                disagreement_threshold: 20.0,
+            },
+            Timeouts::default(),
+        );
+        let monitor =
+            Monitor::new_and_start(config, None, halt_sender.clone(), halt_receiver).await;
+
+        monitor.set_pid_params(-4.0, -0.02, -0.1).await;
+        assert_eq!(monitor.get_pid_params().await, (-4.0, -0.02, -0.1));
+        halt_sender.send_halt().await;
+    }
+
+    /// Test that simulated decision follows current configuration and doesn't change monitor state
+    #[tokio::test]
+    async fn test_simulate_decision() {
+        // monitor tasks are registered elsewhere so that missing fans don't halt them
+        let (miner_shutdown, _miner_halt_receiver) = halt::make_pair(Duration::from_secs(1));
+        let (halt_sender, halt_receiver) = halt::make_pair(Duration::from_secs(1));
+        let fan_speed = fan::Speed::new(50);
+        let config = Config::new(
+            Some(TempControlConfig {
+                dangerous_temp: 100.0,
+                hot_temp: 80.0,
+                hot_temp_hysteresis: 5.0,
+                cold_temp: 20.0,
+            }),
+            Some(FanControlConfig {
+                mode: FanControlMode::FixedSpeed(fan_speed),
+                min_fans: 2,
+                cold_fan_speed: fan::Speed::new(30),
+                max_speed_step_per_tick: None,
+                min_fans_ticks: 2,
+            }),
+            TempSensorConfig {
+                preference: TempSensorPreference::Remote,
+                disagreement_threshold: 20.0,
+            },
+            Timeouts::default(),
+        );
+        let monitor = Monitor::new_and_start(config, None, miner_shutdown, halt_receiver).await;
+
+        assert_eq!(
+            monitor
+                .simulate_decision(2, ChainTemperature::Ok(50.0))
+                .await
+                .decision,
+            ControlDecision::UseFixedSpeed(fan_speed)
+        );
+        assert_eq!(
+            monitor
+                .simulate_decision(2, ChainTemperature::Ok(150.0))
+                .await
+                .decision,
+            ControlDecision::Shutdown
+        );
+        assert_eq!(
+            monitor
+                .simulate_decision(0, ChainTemperature::Ok(50.0))
+                .await
+                .decision,
+            ControlDecision::Shutdown
+        );
+
+        // hot temperature is not remembered
+        monitor
+            .simulate_decision(2, ChainTemperature::Ok(95.0))
+            .await;
+        assert_eq!(
+            monitor.inner.lock().await.temperature_band,
+            TemperatureBand::Normal
+        );
+        halt_sender.send_halt().await;
+    }
+
+    /// Test that status stream skips the empty status that monitor starts with
+    #[tokio::test]
+    async fn test_status_stream() {
+        let status = |input_temperature| Status {
+            config: Config::new(
+                None,
+                None,
+                TempSensorConfig {
+                    preference: TempSensorPreference::Remote,
+                    disagreement_threshold: 20.0,
+                },
+                Timeouts::default(),
+            ),
+            fan_feedback: fan::Feedback { rpm: vec![] },
+            fan_speed: None,
+            input_temperature,
+            temperature_band: TemperatureBand::Normal,
+            temperature_accumulator: TemperatureAccumulator {
+                chain_temperatures: vec![input_temperature],
+            },
+            decision_explained: ControlDecisionExplained {
+                decision: ControlDecision::Nothing,
+                reason: "test",
+            },
+            isolated_chains: vec![],
+            last_shutdown_action: None,
+            chain_power: vec![],
+            no_sensors: false,
+        };
+        let (status_sender, status_receiver) = watch::channel(None);
+        let mut status_stream = StatusStream::new(status_receiver);
+
+        // monitor hasn't reported anything yet
+        assert!(status_stream.current().is_none());
+        assert!(futures::poll!(status_stream.next()).is_pending());
+
+        status_sender
+            .broadcast(Some(status(ChainTemperature::Ok(50.0))))
+            .expect("BUG: status receiver missing");
+        assert_eq!(
+            status_stream
+                .current()
+                .map(|status| status.input_temperature),
+            Some(ChainTemperature::Ok(50.0))
+        );
+        assert_eq!(
+            status_stream
+                .next()
+                .await
+                .map(|status| status.input_temperature),
+            Some(ChainTemperature::Ok(50.0))
+        );
+        assert!(futures::poll!(status_stream.next()).is_pending());
+
+        // stream ends along with monitor
+        drop(status_sender);
+        assert!(status_stream.next().await.is_none());
+    }
+
+    #[test]
+    fn test_power_decide() {
+        let now = Instant::now();
+        let measured = |hashboard_idx, watts| ChainPower {
+            hashboard_idx,
+            reading: power::PowerReading::Measured {
+                voltage: 10.0,
+                current: watts / 10.0,
+            },
+            time: now,
+        };
+        let unsupported = ChainPower {
+            hashboard_idx: 3,
+            reading: power::PowerReading::Unsupported,
+            time: now,
+        };
+        let power_config = PowerControlConfig { max_watts: 1000.0 };
+
+        // no limit
+        let over = vec![measured(1, 600.0), measured(2, 600.0)];
+        assert_eq!(
+            PowerDecision::decide(None, &over, false),
+            PowerDecision::Nothing
+        );
+        // total of all chains is compared against limit
+        assert_eq!(
+            PowerDecision::decide(Some(&power_config), &over, false),
+            PowerDecision::ReduceFrequency
+        );
+        assert_eq!(
+            PowerDecision::decide(Some(&power_config), &over, true),
+            PowerDecision::Shutdown
+        );
+        let under = vec![measured(1, 400.0), measured(2, 400.0), unsupported.clone()];
+        assert_eq!(
+            PowerDecision::decide(Some(&power_config), &under, true),
+            PowerDecision::Nothing
+        );
+        // nothing measured
+        assert_eq!(
+            PowerDecision::decide(Some(&power_config), &[unsupported], true),
+            PowerDecision::Nothing
+        );
+        assert_eq!(
+            PowerDecision::decide(Some(&power_config), &[], true),
+            PowerDecision::Nothing
+        );
+
+        // readings taken before frequency change are not acted on again
+        let changed = now + Duration::from_secs(1);
+        assert_eq!(PowerDecision::fresh_readings(&over, None).len(), 2);
+        let fresh_power = PowerDecision::fresh_readings(&over, Some(changed));
+        assert!(fresh_power.is_empty());
+        assert_eq!(
+            PowerDecision::decide(Some(&power_config), &fresh_power, false),
+            PowerDecision::Nothing
+        );
+        let later = ChainPower {
+            time: changed + Duration::from_secs(1),
+            ..measured(1, 1200.0)
+        };
+        let fresh_power = PowerDecision::fresh_readings(&[over[0].clone(), later], Some(changed));
+        assert_eq!(fresh_power.len(), 1);
+        assert_eq!(fresh_power[0].hashboard_idx, 1);
+        assert_eq!(
+            PowerDecision::decide(Some(&power_config), &fresh_power, false),
+            PowerDecision::ReduceFrequency
+        );
+    }
+
+    #[test]
+    fn test_shutdown_policy() {
+        let mut config = Config {
+            fans_on_while_warming_up: true,
+            isolate_failed_chains: false,
+            shutdown_fan_mode: ShutdownFanMode::Stop,
+            power_config: None,
+            sensor_config: TempSensorConfig {
+                preference: TempSensorPreference::Remote,
+                disagreement_threshold: 20.0,
+            },
+            timeouts: Timeouts::default(),
+            fan_config: None,
+            temp_config: None,
+        };
+        let broken = |hashboard_idx| ShutdownReason::BrokenChain {
+            hashboard_idx,
+            reason: "test",
+        };
+        let control = ShutdownReason::ControlDecision("test");
+        let now = Instant::now();
+
+        // without isolation everything shuts down the miner, but just once
+        let mut policy = ShutdownPolicy::new();
+        assert_eq!(
+            policy.decide(&config, now, &broken(6), 3),
+            ShutdownAction::ShutdownMiner
+        );
+        assert_eq!(
+            policy.decide(&config, now, &control, 3),
+            ShutdownAction::Throttle
+        );
+
+        // isolate chains until the last one or until they fail too often
+        config.isolate_failed_chains = true;
+        let mut policy = ShutdownPolicy::new();
+        assert_eq!(
+            policy.decide(&config, now, &broken(6), 3),
+            ShutdownAction::HaltChain
+        );
+        assert_eq!(
+            policy.decide(&config, now, &broken(7), 2),
+            ShutdownAction::HaltChain
+        );
+        assert_eq!(
+            policy.decide(&config, now, &broken(8), 1),
+            ShutdownAction::ShutdownMiner
+        );
+        let mut policy = ShutdownPolicy::new();
+        for i in 0..MAX_CHAIN_FAILURES {
+            let later = now + CHAIN_FAILURE_WINDOW * i as u32;
+            assert_eq!(
+                policy.decide(&config, later, &broken(6), 3),
+                ShutdownAction::HaltChain
+            );
+        }
+        let later = now + CHAIN_FAILURE_WINDOW * MAX_CHAIN_FAILURES as u32;
+        for _ in 0..MAX_CHAIN_FAILURES {
+            assert_eq!(
+                policy.decide(&config, later, &broken(6), 3),
+                ShutdownAction::HaltChain
+            );
+        }
+        assert_eq!(
+            policy.decide(&config, later, &broken(6), 3),
+            ShutdownAction::ShutdownMiner
+        );
+
+        // control decision always shuts down the miner
+        let mut policy = ShutdownPolicy::new();
+        assert_eq!(
+            policy.decide(&config, now, &control, 3),
+            ShutdownAction::ShutdownMiner
+        );
     }
 }
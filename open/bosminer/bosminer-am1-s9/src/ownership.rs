@@ -0,0 +1,143 @@
+// Copyright (C) 2019  Braiins Systems s.r.o.
+//
+// This file is part of Braiins Open-Source Initiative (BOSI).
+//
+// BOSI is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Common Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Common Public License for more details.
+//
+// You should have received a copy of the GNU Common Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+//
+// Please, keep in mind that we may also license BOSI or any part thereof
+// under a proprietary license. For more information on the terms and conditions
+// of such proprietary license or if you have any other questions, please
+// contact us at opensource@braiins.com.
+
+//! Exclusive ownership of hashchain `Manager`. Whoever wants to start/stop the hashchain has to
+//! acquire it first. The ownership is represented by `Guard` which is passed along between
+//! `StoppedChain` and `RunningChain` and the ownership is released when the guard is dropped.
+
+use std::sync::{Arc, Mutex as StdMutex};
+
+/// Lock holding the name of the current owner
+#[derive(Debug, Clone)]
+pub struct Lock {
+    owned_by: Arc<StdMutex<Option<&'static str>>>,
+}
+
+impl Lock {
+    pub fn new() -> Self {
+        Self {
+            owned_by: Arc::new(StdMutex::new(None)),
+        }
+    }
+
+    /// Acquire ownership for `owner_name`
+    /// Returns name of the current owner if it's already owned.
+    pub fn try_acquire(&self, owner_name: &'static str) -> Result<Guard, &'static str> {
+        let mut owned_by = self.owned_by.lock().expect("BUG: failed to lock mutex");
+        if let Some(already_owned_by) = *owned_by {
+            return Err(already_owned_by);
+        }
+        owned_by.replace(owner_name);
+        Ok(Guard {
+            lock: self.clone(),
+            owner_name,
+        })
+    }
+
+    /// Return name of the current owner (if any)
+    pub fn owned_by(&self) -> Option<&'static str> {
+        *self.owned_by.lock().expect("BUG: failed to lock mutex")
+    }
+}
+
+/// Proof of ownership, the ownership is released when it's dropped
+#[derive(Debug)]
+pub struct Guard {
+    lock: Lock,
+    owner_name: &'static str,
+}
+
+impl Guard {
+    pub fn owner_name(&self) -> &'static str {
+        self.owner_name
+    }
+}
+
+impl Drop for Guard {
+    fn drop(&mut self) {
+        self.lock
+            .owned_by
+            .lock()
+            .expect("BUG: failed to lock mutex")
+            .take();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn test_acquire_release() {
+        let lock = Lock::new();
+        assert_eq!(lock.owned_by(), None);
+
+        let guard = lock.try_acquire("main").expect("BUG: acquire failed");
+        assert_eq!(guard.owner_name(), "main");
+        assert_eq!(lock.owned_by(), Some("main"));
+
+        // double acquire fails and reports the current owner
+        assert_eq!(lock.try_acquire("main").unwrap_err(), "main");
+        assert_eq!(lock.try_acquire("tuner").unwrap_err(), "main");
+        assert_eq!(lock.owned_by(), Some("main"));
+
+        // drop releases ownership
+        drop(guard);
+        assert_eq!(lock.owned_by(), None);
+        let guard = lock.try_acquire("tuner").expect("BUG: acquire failed");
+        assert_eq!(lock.owned_by(), Some("tuner"));
+        drop(guard);
+        assert_eq!(lock.owned_by(), None);
+    }
+
+    #[test]
+    fn test_concurrent_acquire() {
+        let lock = Lock::new();
+        // number of threads holding the guard at the same time
+        let holders = Arc::new(AtomicUsize::new(0));
+        let threads: Vec<_> = (0..8)
+            .map(|_| {
+                let lock = lock.clone();
+                let holders = holders.clone();
+                std::thread::spawn(move || {
+                    let mut acquired = 0;
+                    for _ in 0..1000 {
+                        if let Ok(guard) = lock.try_acquire("thread") {
+                            assert_eq!(holders.fetch_add(1, Ordering::SeqCst), 0);
+                            acquired += 1;
+                            holders.fetch_sub(1, Ordering::SeqCst);
+                            drop(guard);
+                        }
+                    }
+                    acquired
+                })
+            })
+            .collect();
+        let acquired: usize = threads
+            .into_iter()
+            .map(|thread| thread.join().expect("BUG: thread panicked"))
+            .sum();
+        assert!(acquired > 0);
+        assert_eq!(lock.owned_by(), None);
+    }
+}
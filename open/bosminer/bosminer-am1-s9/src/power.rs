@@ -26,13 +26,16 @@ use ii_logging::macros::*;
 
 // TODO remove thread specific code
 use std::convert::TryInto;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
 
 use crate::async_i2c::AsyncI2cDev;
+pub use crate::async_i2c::{ActiveBackend, AsyncI2c};
 use crate::error::{self, ErrorKind};
 use crate::halt;
 
+use async_trait::async_trait;
 use futures::lock::Mutex;
 use ii_async_compat::futures;
 use ii_async_compat::tokio;
@@ -40,12 +43,22 @@ use tokio::time::delay_for;
 
 use once_cell::sync::Lazy;
 
+use serde::{Deserialize, Serialize};
+
 /// Default initial voltage
 pub static OPEN_CORE_VOLTAGE: Lazy<Voltage> =
     Lazy::new(|| Voltage::from_volts(9.4).expect("BUG: opencore voltage is invalid"));
 
+/// Absolute hardware limit of hashboard voltage, nothing (not even open-core voltage) is set
+/// above it
+pub static HARDWARE_MAX_VOLTAGE: Lazy<Voltage> =
+    Lazy::new(|| Voltage::from_volts(9.4).expect("BUG: hardware voltage limit is invalid"));
+
 /// Voltage controller requires periodic heart beat messages to be sent
-const VOLTAGE_CTRL_HEART_BEAT_PERIOD: Duration = Duration::from_millis(1000);
+pub const VOLTAGE_CTRL_HEART_BEAT_PERIOD: Duration = Duration::from_millis(1000);
+
+/// Heart beat is sent at least this many times per watchdog timeout of the voltage controller
+const HEART_BEATS_PER_WATCHDOG_TIMEOUT: u32 = 2;
 
 const PIC_BASE_ADDRESS: u8 = 0x50;
 
@@ -89,6 +102,27 @@ const RD_TEMP_OFFSET_VALUE: u8 = 0x23;
 /// The PIC firmware in the voltage controller is expected to provide/return this version
 pub const EXPECTED_VOLTAGE_CTRL_VERSION: u8 = 0x03;
 
+/// Return watchdog timeout of voltage controller firmware `version` (if it's known)
+///
+/// Voltage controller cuts off power supply when no heart beat arrives within this timeout.
+pub fn watchdog_timeout(version: u8) -> Option<Duration> {
+    match version {
+        0x03 => Some(Duration::from_secs(10)),
+        _ => None,
+    }
+}
+
+/// Pick heart beat period for voltage controller firmware `version`
+///
+/// Requested period is shortened if it wouldn't satisfy the watchdog of the firmware.
+pub fn resolve_heart_beat_period(requested: Option<Duration>, version: u8) -> Duration {
+    let period = requested.unwrap_or(VOLTAGE_CTRL_HEART_BEAT_PERIOD);
+    match watchdog_timeout(version) {
+        Some(timeout) => period.min(timeout / HEART_BEATS_PER_WATCHDOG_TIMEOUT),
+        None => period,
+    }
+}
+
 /// Path to voltage controller PIC program
 pub const PIC_PROGRAM_PATH: &'static str = "/lib/antminer/hash_s8_app.txt";
 
@@ -146,6 +180,112 @@ impl Voltage {
     pub fn as_pic_value(&self) -> u8 {
         self.0
     }
+
+    /// Convert voltage difference to number of PIC steps (at least one so that a ramp always
+    /// makes progress)
+    fn volts_to_pic_step(step: f32) -> u8 {
+        (step.abs() * Self::VOLT_CONV_COEF_2)
+            .round()
+            .max(1.0)
+            .min(u8::MAX as f32) as u8
+    }
+
+    /// Return voltage that is at most `max_step` PIC steps closer to `target`
+    fn step_towards(&self, target: Self, max_step: u8) -> Self {
+        if self.0 < target.0 {
+            Self(self.0.saturating_add(max_step).min(target.0))
+        } else {
+            Self(self.0.saturating_sub(max_step).max(target.0))
+        }
+    }
+}
+
+/// Hashboard power draw as measured by `PowerMeter`
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum PowerReading {
+    /// Power meter is not able to measure power of the hashboard
+    Unsupported,
+    /// Input voltage (in volts) and current (in amperes)
+    Measured { voltage: f32, current: f32 },
+}
+
+impl PowerReading {
+    /// Power in watts (`None` when it's not measured)
+    pub fn watts(&self) -> Option<f32> {
+        match *self {
+            Self::Unsupported => None,
+            Self::Measured { voltage, current } => Some(voltage * current),
+        }
+    }
+}
+
+/// Source of hashboard power readings
+///
+/// S9 voltage controller (PIC) cannot measure power, so the readings have to come from
+/// external hardware (e.g. metered PDU) which is provided by application embedding the
+/// backend (see `hooks::Hooks::power_meter`).
+#[async_trait]
+pub trait PowerMeter: Send + Sync {
+    /// Read input voltage and current of hashboard `hashboard_idx`
+    async fn read_power(&self, hashboard_idx: usize) -> error::Result<PowerReading>;
+}
+
+/// Parameters of gradual voltage change (see `Control::set_voltage_ramped`)
+#[derive(Serialize, Deserialize, Copy, Clone, Debug, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct VoltageRamp {
+    /// Maximum voltage change in one step (in volts)
+    pub step: f32,
+    /// Delay between successive steps (configured in seconds)
+    #[serde(with = "crate::config::support::duration_secs")]
+    pub delay: Duration,
+}
+
+impl VoltageRamp {
+    /// Worst-case time it takes to change voltage by `volts`
+    pub fn duration(&self, volts: f32) -> Duration {
+        let steps = (volts.abs() / self.step).ceil() as u32;
+        self.delay * steps.saturating_sub(1)
+    }
+}
+
+/// Range of voltages allowed on a working hashchain
+///
+/// Open-core voltage used during initialization is not subject to these bounds, it's only
+/// capped by `HARDWARE_MAX_VOLTAGE`.
+#[derive(Copy, Clone, PartialEq)]
+pub struct VoltageBounds {
+    pub min: Voltage,
+    pub max: Voltage,
+}
+
+impl VoltageBounds {
+    pub fn new(min: Voltage, max: Voltage) -> error::Result<Self> {
+        if min > max {
+            Err(ErrorKind::Power(format!(
+                "minimal voltage {} is higher than maximal voltage {}",
+                min, max
+            )))?
+        }
+        if max > *HARDWARE_MAX_VOLTAGE {
+            Err(ErrorKind::Power(format!(
+                "maximal voltage {} exceeds hardware limit {}",
+                max, *HARDWARE_MAX_VOLTAGE
+            )))?
+        }
+        Ok(Self { min, max })
+    }
+
+    /// Reject `voltage` that is out of bounds
+    pub fn check(&self, voltage: Voltage) -> error::Result<()> {
+        if voltage < self.min || voltage > self.max {
+            Err(ErrorKind::Power(format!(
+                "voltage {} is out of allowed range <{};{}>",
+                voltage, self.min, self.max
+            )))?
+        }
+        Ok(())
+    }
 }
 
 impl std::fmt::Display for Voltage {
@@ -165,7 +305,7 @@ impl std::cmp::PartialOrd for Voltage {
 /// S9 devices have a single I2C master that manages the voltage controllers on all hashboards.
 /// Therefore, this will be a single communication instance.
 pub struct I2cBackend {
-    inner: AsyncI2cDev,
+    inner: Box<dyn AsyncI2c>,
 }
 
 impl I2cBackend {
@@ -182,12 +322,52 @@ impl I2cBackend {
     /// Instantiates a new I2C backend
     /// * `i2c_interface_num` - index of the I2C interface in Linux dev filesystem
     pub fn new(i2c_interface_num: usize) -> Self {
+        Self::from_i2c(
+            AsyncI2cDev::open(
+                format!("/dev/i2c-{}", i2c_interface_num),
+                Default::default(),
+            )
+            .expect("I2C instantiation failed"),
+        )
+    }
+
+    /// Instantiates a new I2C backend with fallback I2C interface that is used when the primary
+    /// one gets wedged
+    /// * `i2c_interface_num` - index of the primary I2C interface in Linux dev filesystem
+    /// * `fallback_i2c_interface_num` - index of the I2C interface with the same devices
+    pub fn new_with_fallback(i2c_interface_num: usize, fallback_i2c_interface_num: usize) -> Self {
+        Self::from_i2c(
+            AsyncI2cDev::open_with_fallback(
+                format!("/dev/i2c-{}", i2c_interface_num),
+                format!("/dev/i2c-{}", fallback_i2c_interface_num),
+                Default::default(),
+            )
+            .expect("I2C instantiation failed"),
+        )
+    }
+
+    /// Instantiates a new I2C backend on top of arbitrary I2C controller implementation
+    pub fn from_i2c<T: AsyncI2c + 'static>(i2c: T) -> Self {
         Self {
-            inner: AsyncI2cDev::open(format!("/dev/i2c-{}", i2c_interface_num))
-                .expect("I2C instantiation failed"),
+            inner: Box::new(i2c),
         }
     }
 
+    /// Reset the underlying I2C controller
+    pub async fn reset_i2c_controller(&self) -> error::Result<()> {
+        self.inner.reset_i2c_controller().await
+    }
+
+    /// Close the underlying I2C controller (it can be opened again by reset)
+    pub async fn close(&self) -> error::Result<()> {
+        self.inner.close().await
+    }
+
+    /// Return I2C backend which is currently used for communication
+    pub fn active_backend(&self) -> ActiveBackend {
+        self.inner.active_backend()
+    }
+
     /// Attempt to write a byte to power controller on I2C.
     /// If write fails then retry (at most `I2C_NUM_RETRIES`).
     async fn write_retry(&self, hashboard_idx: usize, data: u8) -> error::Result<()> {
@@ -423,6 +603,8 @@ pub struct Control {
     /// Information from PIC flash
     badcore_flash: Mutex<Option<FlashBadcore>>,
     freq_flash: Mutex<Option<FlashFreq>>,
+    /// Heart beat task terminates when this is set
+    heart_beat_stopped: AtomicBool,
 }
 
 impl Control {
@@ -607,6 +789,12 @@ impl Control {
     }
 
     pub async fn set_voltage(&self, voltage: Voltage) -> error::Result<()> {
+        if voltage > *HARDWARE_MAX_VOLTAGE {
+            Err(ErrorKind::Power(format!(
+                "refusing to set voltage {} above hardware limit {}",
+                voltage, *HARDWARE_MAX_VOLTAGE
+            )))?
+        }
         let mut current_voltage = self.current_voltage.lock().await;
         if *current_voltage != Some(voltage) {
             info!(
@@ -621,6 +809,31 @@ impl Control {
         Ok(())
     }
 
+    /// Walk from current voltage to `target` in steps not larger than `step` volts with `delay`
+    /// between them. When current voltage is unknown, `target` is set immediately.
+    ///
+    /// No lock is held while waiting between steps so that the heart beat task can get through.
+    pub async fn set_voltage_ramped(
+        &self,
+        target: Voltage,
+        step: f32,
+        delay: Duration,
+    ) -> error::Result<()> {
+        let max_step = Voltage::volts_to_pic_step(step);
+        loop {
+            let next = match self.get_current_voltage().await {
+                Some(current) => current.step_towards(target, max_step),
+                None => target,
+            };
+            self.set_voltage(next).await?;
+            if next == target {
+                break;
+            }
+            delay_for(delay).await;
+        }
+        Ok(())
+    }
+
     pub async fn get_voltage(&self) -> error::Result<u8> {
         Ok(self.read(GET_VOLTAGE, 1).await?[0])
     }
@@ -669,6 +882,7 @@ impl Control {
             current_voltage: Mutex::new(None),
             badcore_flash: Mutex::new(None),
             freq_flash: Mutex::new(None),
+            heart_beat_stopped: AtomicBool::new(false),
         }
     }
 
@@ -687,16 +901,23 @@ impl Control {
     }
 
     /// Initialize voltage controller
+    ///
+    /// * `heart_beat_period` - override default heart beat period (it cannot exceed what the
+    ///   firmware watchdog requires)
     /// TODO: decouple this code from `halt_receiver`
-    pub async fn init(self: Arc<Self>, halt_receiver: halt::Receiver) -> error::Result<()> {
-        let version = self.reset_and_start_app().await?;
+    pub async fn init(
+        self: Arc<Self>,
+        halt_receiver: halt::Receiver,
+        heart_beat_period: Option<Duration>,
+    ) -> error::Result<()> {
+        let mut version = self.reset_and_start_app().await?;
         // TODO accept multiple
         if version != EXPECTED_VOLTAGE_CTRL_VERSION {
             info!("Bad firmware version! Reloading firmware...");
             let program = firmware::PicProgram::read(PIC_PROGRAM_PATH)?;
             self.program_pic(&program).await?;
 
-            let version = self.reset_and_start_app().await?;
+            version = self.reset_and_start_app().await?;
             if version != EXPECTED_VOLTAGE_CTRL_VERSION {
                 info!("Firmware reloading failed, still bad firmware version...");
                 Err(ErrorKind::UnexpectedVersion(
@@ -711,29 +932,60 @@ impl Control {
 
         // Voltage controller successfully initialized at this point, we should start sending
         // heart beats to it. Otherwise, it would shut down in about 10 seconds.
-        self.start_heart_beat_task(halt_receiver).await;
+        let period = resolve_heart_beat_period(heart_beat_period, version);
+        match watchdog_timeout(version) {
+            Some(timeout) => info!(
+                "Voltage controller heart beat period {:?} (watchdog timeout {:?})",
+                period, timeout
+            ),
+            None => info!(
+                "Voltage controller heart beat period {:?} (watchdog timeout unknown)",
+                period
+            ),
+        }
+        if heart_beat_period.map_or(false, |requested| requested != period) {
+            warn!(
+                "Requested heart beat period {:?} is too long for the watchdog, using {:?}",
+                heart_beat_period.expect("BUG: missing period"),
+                period
+            );
+        }
+        self.start_heart_beat_task(halt_receiver, period).await;
 
         Ok(())
     }
 
+    /// Stop sending heart beats to the voltage controller
+    ///
+    /// The heart beat task finishes on its next iteration. Voltage controller (if it's not
+    /// turned off already) cuts off power supply on its own when the heart beat is missing.
+    pub fn stop_heart_beat(&self) {
+        self.heart_beat_stopped.store(true, Ordering::Relaxed);
+    }
+
     /// Helper method that sends heartbeat to the voltage controller at regular intervals
     ///
     /// The reason is to notify the voltage controller that we are alive so that it wouldn't
     /// cut-off power supply to the hashing chips on the board.
-    async fn start_heart_beat_task(self: Arc<Self>, halt_receiver: halt::Receiver) {
+    async fn start_heart_beat_task(
+        self: Arc<Self>,
+        halt_receiver: halt::Receiver,
+        period: Duration,
+    ) {
         // Start heartbeat thread in termination context
         let voltage_ctrl = self.clone();
         halt_receiver
             .register_client("power heartbeat".into())
             .await
             .spawn(async move {
-                loop {
+                while !voltage_ctrl.heart_beat_stopped.load(Ordering::Relaxed) {
                     voltage_ctrl
                         .send_heart_beat()
                         .await
                         .expect("send_heart_beat failed");
-                    delay_for(VOLTAGE_CTRL_HEART_BEAT_PERIOD).await;
+                    delay_for(period).await;
                 }
+                info!("Voltage controller heart beat stopped");
             });
 
         // Make a termination handler that disables voltage when stopped
@@ -754,6 +1006,84 @@ impl Control {
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::async_i2c;
+
+    #[test]
+    fn test_resolve_heart_beat_period() {
+        assert_eq!(
+            resolve_heart_beat_period(None, EXPECTED_VOLTAGE_CTRL_VERSION),
+            VOLTAGE_CTRL_HEART_BEAT_PERIOD
+        );
+        assert_eq!(
+            resolve_heart_beat_period(Some(Duration::from_secs(3)), EXPECTED_VOLTAGE_CTRL_VERSION),
+            Duration::from_secs(3)
+        );
+        // too slow for 10s watchdog
+        assert_eq!(
+            resolve_heart_beat_period(Some(Duration::from_secs(8)), EXPECTED_VOLTAGE_CTRL_VERSION),
+            Duration::from_secs(5)
+        );
+        // unknown firmware, no limit
+        assert_eq!(
+            resolve_heart_beat_period(Some(Duration::from_secs(8)), 0x42),
+            Duration::from_secs(8)
+        );
+    }
+
+    #[test]
+    fn test_voltage_step_towards() {
+        let high = *OPEN_CORE_VOLTAGE;
+        let low = Voltage::from_volts(8.8).expect("BUG: invalid voltage");
+        let max_step = Voltage::volts_to_pic_step(0.1);
+        assert_eq!(max_step, 17);
+        // at least one PIC step
+        assert_eq!(Voltage::volts_to_pic_step(0.0), 1);
+
+        // walk both directions without overshooting
+        for (from, to) in [(high, low), (low, high)].iter() {
+            let mut voltage = *from;
+            let mut steps = 0;
+            while voltage != *to {
+                let next = voltage.step_towards(*to, max_step);
+                assert!((next.as_pic_value() as i32 - voltage.as_pic_value() as i32).abs() <= 17);
+                voltage = next;
+                steps += 1;
+            }
+            assert_eq!(steps, 7);
+        }
+    }
+
+    #[test]
+    fn test_voltage_bounds() {
+        let volts = |v| Voltage::from_volts(v).expect("BUG: invalid voltage");
+        let bounds = VoltageBounds::new(volts(8.0), volts(9.0)).expect("BUG: invalid bounds");
+
+        // boundaries are inclusive
+        assert!(bounds.check(volts(8.0)).is_ok());
+        assert!(bounds.check(volts(9.0)).is_ok());
+        assert!(bounds.check(volts(8.8)).is_ok());
+        // out of range in both directions
+        assert!(bounds.check(volts(7.95)).is_err());
+        assert!(bounds.check(volts(9.1)).is_err());
+        // open-core voltage is above working range but within hardware limit
+        assert!(bounds.check(*OPEN_CORE_VOLTAGE).is_err());
+        assert!(*OPEN_CORE_VOLTAGE <= *HARDWARE_MAX_VOLTAGE);
+
+        // inverted bounds and bounds above hardware limit are rejected
+        assert!(VoltageBounds::new(volts(9.0), volts(8.0)).is_err());
+        assert!(VoltageBounds::new(volts(8.0), Voltage::MAX_VOLTAGE).is_err());
+        assert!(VoltageBounds::new(volts(8.0), *HARDWARE_MAX_VOLTAGE).is_ok());
+    }
+
+    #[test]
+    fn test_power_reading_watts() {
+        assert_eq!(PowerReading::Unsupported.watts(), None);
+        let reading = PowerReading::Measured {
+            voltage: 12.0,
+            current: 50.0,
+        };
+        assert_eq!(reading.watts(), Some(600.0));
+    }
 
     #[test]
     fn test_pic_address_words() {
@@ -765,6 +1095,80 @@ mod test {
         assert_eq!(PicWords::from_bytes(0x1fffe), PicWords(0xffff));
     }
 
+    #[tokio::test]
+    async fn test_i2c_backend_fake_i2c() {
+        let i2c = async_i2c::test_utils::FakeI2c::new(&[0x57]);
+        let backend = I2cBackend::from_i2c(i2c.clone());
+        i2c.push_read_bytes(0x57, &[0x03]);
+
+        assert_eq!(backend.read(8, 0x11, 1).await.unwrap(), vec![0x03]);
+        backend.write(8, 0x12, &[0xab]).await.unwrap();
+        assert_eq!(
+            i2c.written_bytes(0x57),
+            vec![
+                PIC_COMMAND_1,
+                PIC_COMMAND_2,
+                0x11,
+                PIC_COMMAND_1,
+                PIC_COMMAND_2,
+                0x12,
+                0xab
+            ]
+        );
+    }
+
+    /// Test voltage controller commands on top of fake I2C
+    #[tokio::test]
+    async fn test_control_fake_i2c() {
+        let i2c = async_i2c::test_utils::FakeI2c::new(&[0x57]);
+        let control = Control::new(Arc::new(I2cBackend::from_i2c(i2c.clone())), 8);
+        let command =
+            |command: u8, data: &[u8]| [&[PIC_COMMAND_1, PIC_COMMAND_2, command], data].concat();
+
+        i2c.push_read_bytes(0x57, &[EXPECTED_VOLTAGE_CTRL_VERSION]);
+        assert_eq!(
+            control.get_version().await.unwrap(),
+            EXPECTED_VOLTAGE_CTRL_VERSION
+        );
+        let mut expected = command(GET_PIC_SOFTWARE_VERSION, &[]);
+
+        // voltage is written only when it changes
+        let voltage = Voltage::from_volts(9.0).expect("BUG: invalid voltage");
+        control.set_voltage(voltage).await.unwrap();
+        control.set_voltage(voltage).await.unwrap();
+        assert_eq!(control.get_current_voltage().await, Some(voltage));
+        expected.extend(command(SET_VOLTAGE, &[voltage.as_pic_value()]));
+        // nothing is sent when the voltage is above hardware limit
+        let too_high = Voltage::from_pic_value(0).expect("BUG: invalid voltage");
+        assert!(control.set_voltage(too_high).await.is_err());
+        assert_eq!(control.get_current_voltage().await, Some(voltage));
+
+        control.enable_voltage().await.unwrap();
+        control.send_heart_beat().await.unwrap();
+        control.disable_voltage().await.unwrap();
+        expected.extend(command(ENABLE_VOLTAGE, &[1]));
+        expected.extend(command(SEND_HEART_BEAT, &[]));
+        expected.extend(command(ENABLE_VOLTAGE, &[0]));
+
+        // flash pointer that doesn't end up where requested is reported
+        i2c.push_read_bytes(0x57, &[0x03, 0x00]);
+        control
+            .set_flash_pointer_check(PicAddress(0x300))
+            .await
+            .unwrap();
+        i2c.push_read_bytes(0x57, &[0x03, 0x10]);
+        assert!(control
+            .set_flash_pointer_check(PicAddress(0x300))
+            .await
+            .is_err());
+        for _ in 0..2 {
+            expected.extend(command(SET_PIC_FLASH_POINTER, &[0x03, 0x00]));
+            expected.extend(command(GET_PIC_FLASH_POINTER, &[]));
+        }
+
+        assert_eq!(i2c.written_bytes(0x57), expected);
+    }
+
     #[test]
     fn test_get_address() {
         let addr = I2cBackend::get_i2c_address(8);
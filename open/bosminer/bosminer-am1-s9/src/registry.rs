@@ -34,6 +34,8 @@ pub struct WorkRegistryItem {
     solutions: std::vec::Vec<Solution>,
     /// Flag that work is only for initialization of the mining chips and any results coming from it should be ignored
     pub initial_work: bool,
+    /// ASIC target that was in effect when the work was sent out
+    pub asic_target: ii_bitcoin::Target,
 }
 
 impl WorkRegistryItem {
@@ -101,6 +103,8 @@ pub struct WorkRegistry {
     next_work_id: usize,
     /// Current pending work list Each work item has a list of associated work solutions
     pending_work_list: std::vec::Vec<Option<WorkRegistryItem>>,
+    /// ASIC target assigned to newly stored work
+    asic_target: ii_bitcoin::Target,
 }
 
 impl WorkRegistry {
@@ -110,6 +114,7 @@ impl WorkRegistry {
             registry_size,
             next_work_id: 0,
             pending_work_list: vec![None; registry_size],
+            asic_target: Default::default(),
         }
     }
 
@@ -140,12 +145,18 @@ impl WorkRegistry {
             work,
             solutions: std::vec::Vec::new(),
             initial_work,
+            asic_target: self.asic_target,
         });
 
         // return assigned work id
         work_id
     }
 
+    /// Set ASIC target for work stored from now on. Work that is already stored keeps its target.
+    pub fn set_asic_target(&mut self, asic_target: ii_bitcoin::Target) {
+        self.asic_target = asic_target;
+    }
+
     /// Look-up work id
     pub fn find_work(&mut self, work_id: usize) -> &mut Option<WorkRegistryItem> {
         assert!(work_id < self.registry_size);
@@ -238,4 +249,27 @@ mod test {
             false
         );
     }
+
+    /// Test that ASIC target change affects only newly stored work
+    #[test]
+    fn test_asic_target_change() {
+        let mut registry = WorkRegistry::new(4);
+        let old_target = ii_bitcoin::Target::from_pool_difficulty(64);
+        let new_target = ii_bitcoin::Target::from_pool_difficulty(256);
+
+        registry.set_asic_target(old_target);
+        assert_eq!(registry.store_work(null_work::prepare(0), false), 0);
+        registry.set_asic_target(new_target);
+        assert_eq!(registry.store_work(null_work::prepare(1), false), 1);
+
+        let asic_target = |registry: &mut WorkRegistry, work_id| {
+            registry
+                .find_work(work_id)
+                .as_ref()
+                .expect("work not found")
+                .asic_target
+        };
+        assert!(asic_target(&mut registry, 0) == old_target);
+        assert!(asic_target(&mut registry, 1) == new_target);
+    }
 }
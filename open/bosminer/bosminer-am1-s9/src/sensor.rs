@@ -55,6 +55,9 @@ pub trait Sensor: Sync + Send {
 
     /// Read temperature from sensor
     async fn read_temperature(&mut self) -> error::Result<Temperature>;
+
+    /// Sensor model and address it has been found on (for logs and bug reports)
+    fn name(&self) -> String;
 }
 
 /// Result of measuring temperature with remote sensor
@@ -92,6 +95,34 @@ pub struct Temperature {
     pub remote: Measurement,
 }
 
+impl Temperature {
+    /// Hashboard has no temperature sensor at all
+    pub fn is_sensor_absent(&self) -> bool {
+        self.local == Measurement::NotPresent && self.remote == Measurement::NotPresent
+    }
+
+    /// Summarize readings of multiple sensors: hottest valid local and remote temperature is
+    /// taken (measurement of the first sensor is kept when there's no valid one).
+    /// Returns `None` if there are no readings.
+    pub fn summary(readings: &[Temperature]) -> Option<Temperature> {
+        let first = readings.first()?;
+        let hottest = |measurements: Vec<&Measurement>, first: &Measurement| {
+            measurements
+                .into_iter()
+                .filter_map(|m| Option::<f32>::from(m.clone()))
+                .fold(None, |max: Option<f32>, t| {
+                    Some(max.map_or(t, |max| max.max(t)))
+                })
+                .map(Measurement::Ok)
+                .unwrap_or_else(|| first.clone())
+        };
+        Some(Temperature {
+            local: hottest(readings.iter().map(|r| &r.local).collect(), &first.local),
+            remote: hottest(readings.iter().map(|r| &r.remote).collect(), &first.remote),
+        })
+    }
+}
+
 lazy_static! {
     /// List of all known I2C address where sensors are present
     static ref SENSOR_I2C_ADDRESS: [i2c::Address; 3] = [
@@ -164,6 +195,81 @@ pub async fn probe_i2c_sensors<T: 'static + i2c::AsyncBus + Clone>(
     Ok(None)
 }
 
+/// Probe all known addresses and return every supported sensor found
+///
+/// Address that fails to respond is skipped, error is returned only if no sensor was found.
+pub async fn probe_all_i2c_sensors<T: 'static + i2c::AsyncBus + Clone>(
+    i2c_bus: T,
+) -> error::Result<Vec<Box<dyn Sensor>>> {
+    let mut sensors = Vec::new();
+    let mut last_error = None;
+    for address in SENSOR_I2C_ADDRESS.iter() {
+        let i2c_device = Box::new(i2c::Device::new(i2c_bus.clone(), *address));
+        match probe_i2c_device(i2c_device).await {
+            Ok(Some(sensor)) => sensors.push(sensor),
+            Ok(None) => (),
+            Err(e) => last_error = Some(e),
+        }
+    }
+    match last_error {
+        Some(e) if sensors.is_empty() => Err(e),
+        _ => Ok(sensors),
+    }
+}
+
+/// Sensor that is disabled after `max_read_errors` consecutive failed reads so that a dead
+/// sensor doesn't keep the I2C bus busy (other sensors on the same hashboard are not affected)
+pub struct CheckedSensor {
+    sensor: Box<dyn Sensor>,
+    /// Number of consecutive failed reads
+    errors: usize,
+    max_read_errors: usize,
+}
+
+impl CheckedSensor {
+    pub fn new(sensor: Box<dyn Sensor>, max_read_errors: usize) -> Self {
+        Self {
+            sensor,
+            errors: 0,
+            max_read_errors,
+        }
+    }
+
+    pub fn name(&self) -> String {
+        self.sensor.name()
+    }
+
+    pub fn is_disabled(&self) -> bool {
+        self.errors >= self.max_read_errors
+    }
+
+    /// Read temperature, failed read (or read from disabled sensor) results in
+    /// `INVALID_TEMPERATURE_READING`
+    pub async fn read(&mut self) -> Temperature {
+        if self.is_disabled() {
+            return INVALID_TEMPERATURE_READING;
+        }
+        match self.sensor.read_temperature().await {
+            Ok(reading) => {
+                self.errors = 0;
+                reading
+            }
+            Err(e) => {
+                error!("Sensor {}: temperature read failed: {}", self.name(), e);
+                self.errors += 1;
+                if self.is_disabled() {
+                    error!(
+                        "Sensor {} failed {} times in a row, disabling it",
+                        self.name(),
+                        self.max_read_errors
+                    );
+                }
+                INVALID_TEMPERATURE_READING
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -194,4 +300,87 @@ mod test {
         assert_eq!(test_probe_address(0x9c, 0x37, 0x21).await, false);
         assert_eq!(test_probe_address(0x84, 0x55, 0x21).await, false);
     }
+
+    /// Sensor returning prepared results of reads
+    struct FakeSensor {
+        reads: Vec<error::Result<Temperature>>,
+    }
+
+    #[async_trait]
+    impl Sensor for FakeSensor {
+        async fn init(&mut self) -> error::Result<()> {
+            Ok(())
+        }
+
+        async fn read_temperature(&mut self) -> error::Result<Temperature> {
+            assert!(!self.reads.is_empty(), "BUG: unexpected sensor read");
+            self.reads.remove(0)
+        }
+
+        fn name(&self) -> String {
+            "fake".to_string()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_checked_sensor_disable() {
+        let reading = Temperature {
+            local: Measurement::Ok(40.0),
+            remote: Measurement::Ok(60.0),
+        };
+        let failure = || Err(crate::error::ErrorKind::Sensors("read failed".into()).into());
+        let mut sensor = CheckedSensor::new(
+            Box::new(FakeSensor {
+                reads: vec![failure(), Ok(reading.clone()), failure(), failure()],
+            }),
+            2,
+        );
+
+        // successful read resets error count
+        assert_eq!(sensor.read().await, INVALID_TEMPERATURE_READING);
+        assert_eq!(sensor.read().await, reading);
+        assert!(!sensor.is_disabled());
+        assert_eq!(sensor.read().await, INVALID_TEMPERATURE_READING);
+        assert!(!sensor.is_disabled());
+        assert_eq!(sensor.read().await, INVALID_TEMPERATURE_READING);
+        assert!(sensor.is_disabled());
+        // disabled sensor is not read anymore (fake sensor would panic)
+        assert_eq!(sensor.read().await, INVALID_TEMPERATURE_READING);
+    }
+
+    #[test]
+    fn test_temperature_summary() {
+        assert_eq!(Temperature::summary(&[]), None);
+        let summary = Temperature::summary(&[
+            Temperature {
+                local: Measurement::Ok(40.0),
+                remote: Measurement::OpenCircuit,
+            },
+            Temperature {
+                local: Measurement::Ok(45.0),
+                remote: Measurement::NotPresent,
+            },
+        ]);
+        assert_eq!(
+            summary,
+            Some(Temperature {
+                local: Measurement::Ok(45.0),
+                remote: Measurement::OpenCircuit,
+            })
+        );
+        let summary = Temperature::summary(&[
+            INVALID_TEMPERATURE_READING,
+            Temperature {
+                local: Measurement::Ok(30.0),
+                remote: Measurement::Ok(60.0),
+            },
+        ]);
+        assert_eq!(
+            summary,
+            Some(Temperature {
+                local: Measurement::Ok(30.0),
+                remote: Measurement::Ok(60.0),
+            })
+        );
+    }
 }
@@ -79,7 +79,6 @@ pub struct TMP42x {
     i2c_device: Box<dyn i2c::AsyncDevice>,
     /// We intend to support chips with multiple remote temperature sensors in the future.
     /// This registers defines how many remote sensors we are connected to.
-    #[allow(dead_code)]
     num_remote_sensors: usize,
     /// If `Some`: if local temperature high-byte reg is this value, then discard the reading.
     /// This is used to discard the first reading after changing register format to extended mode.
@@ -195,6 +194,15 @@ impl sensor::Sensor for TMP42x {
 
         Ok(Temperature { local, remote })
     }
+
+    /// Model number is derived from number of remote sensors (TMP421 has one)
+    fn name(&self) -> String {
+        format!(
+            "TMP42{} at {}",
+            self.num_remote_sensors,
+            self.i2c_device.get_address()
+        )
+    }
 }
 
 #[cfg(test)]
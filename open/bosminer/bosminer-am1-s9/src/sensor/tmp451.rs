@@ -123,6 +123,10 @@ impl sensor::Sensor for TMP451 {
     async fn read_temperature(&mut self) -> error::Result<Temperature> {
         read_temperature(&mut self.i2c_dev, true).await
     }
+
+    fn name(&self) -> String {
+        format!("TMP451 at {}", self.i2c_dev.get_address())
+    }
 }
 
 /// ADT7461 driver (almost the same as TMP451)
@@ -145,6 +149,10 @@ impl sensor::Sensor for ADT7461 {
     async fn read_temperature(&mut self) -> error::Result<Temperature> {
         read_temperature(&mut self.i2c_dev, false).await
     }
+
+    fn name(&self) -> String {
+        format!("ADT7461 at {}", self.i2c_dev.get_address())
+    }
 }
 
 /// NCT218 driver (only local temperature)
@@ -167,6 +175,10 @@ impl sensor::Sensor for NCT218 {
     async fn read_temperature(&mut self) -> error::Result<Temperature> {
         read_temperature_local(&mut self.i2c_dev).await
     }
+
+    fn name(&self) -> String {
+        format!("NCT218 at {}", self.i2c_dev.get_address())
+    }
 }
 
 #[cfg(test)]
@@ -221,6 +233,7 @@ mod test {
         let mut sensor = TMP451::new(Box::new(dev.clone()));
         sensor.init().await.unwrap();
         check_config_ok(&mut dev).await;
+        assert_eq!(sensor.name(), "TMP451 at 0x16");
         assert_eq!(
             sensor.read_temperature().await.unwrap(),
             Temperature {
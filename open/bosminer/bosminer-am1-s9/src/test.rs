@@ -40,6 +40,8 @@ async fn test_hchain_ctl_instance() {
         hashboard_idx,
         MidstateCount::new(1),
         config::DEFAULT_ASIC_DIFFICULTY,
+        bm1387::CORE_ADDRESS_LAYOUT,
+        config::DEFAULT_SENSOR_CHIP_ADDRESS,
         monitor_sender,
     );
     match hash_chain {
@@ -98,12 +100,1686 @@ fn test_calc_baud_div_over_baud_rate_bm1387() {
     );
 }
 
+/// Test detection of baud rate that is accepted, but off the requested one
+#[test]
+fn test_baud_rate_off_target() {
+    assert!(!baud_rate_off_target(1_562_500, 1_562_500));
+    assert!(!baud_rate_off_target(115_740, 115_741));
+    assert!(baud_rate_off_target(1_000_000, 1_041_666));
+    assert!(baud_rate_off_target(1_000_000, 960_000));
+}
+
+/// Test that events are summarized in log at most once per interval
+#[test]
+fn test_log_rate_limiter() {
+    let mut log = LogRateLimiter::new(Duration::from_secs(3600));
+    // the first event is logged right away
+    assert_eq!(log.add(), Some(1));
+    assert_eq!(log.add(), None);
+    assert_eq!(log.add(), None);
+
+    // events are counted until the interval expires
+    log.interval = Duration::from_secs(0);
+    assert_eq!(log.add(), Some(3));
+    assert_eq!(log.add(), Some(1));
+
+    // flush returns pending events regardless of interval
+    log.interval = Duration::from_secs(3600);
+    assert_eq!(log.add(), None);
+    assert_eq!(log.add(), None);
+    assert_eq!(log.flush(), Some(2));
+    assert_eq!(log.flush(), None);
+}
+
+/// Test that orphaned solutions not logged yet are flushed when the log is dropped
+#[test]
+fn test_orphaned_solution_log() {
+    let mut log = OrphanedSolutionLog::new(6);
+    log.add();
+    log.add();
+    log.add();
+    // the first one has been logged right away
+    assert_eq!(log.limiter.unlogged, 2);
+    assert_eq!(log.limiter.flush(), Some(2));
+    log.add();
+    drop(log);
+}
+
+/// Test that FIFO starving/backing up is reported only when it's consistent
+#[test]
+fn test_work_tx_occupancy() {
+    let short_wait = Duration::from_millis(3);
+    let long_wait = Duration::from_secs(1);
+
+    let mut occupancy = WorkTxOccupancy::default();
+    occupancy.add_sample(io::WorkTxLevel::Empty, Duration::from_secs(0));
+    // not enough samples yet
+    assert_eq!(occupancy.tuning_hint(), None);
+
+    // samples as they are seen by work TX task when work is sent at the right pace: it waits
+    // for room about one work time and the FIFO is not drained
+    let mut occupancy = WorkTxOccupancy::default();
+    for _ in 0..1000 {
+        occupancy.add_sample(io::WorkTxLevel::HasRoom, short_wait);
+    }
+    assert_eq!(occupancy.tuning_hint(), None);
+    // occasional empty FIFO or long wait is fine
+    occupancy.add_sample(io::WorkTxLevel::Empty, Duration::from_secs(0));
+    occupancy.add_sample(io::WorkTxLevel::HasRoom, long_wait);
+    assert_eq!(occupancy.tuning_hint(), None);
+
+    // FIFO is drained by the time there's room for new work
+    for _ in 0..1000 {
+        occupancy.add_sample(io::WorkTxLevel::Empty, Duration::from_secs(0));
+    }
+    assert!(occupancy.tuning_hint().unwrap().contains("starving"));
+
+    // chips don't take work, so there's no room for a long time
+    for _ in 0..1000 {
+        occupancy.add_sample(io::WorkTxLevel::HasRoom, long_wait);
+    }
+    assert!(occupancy.tuning_hint().unwrap().contains("backed up"));
+
+    for _ in 0..1000 {
+        occupancy.add_sample(io::WorkTxLevel::Full, short_wait);
+    }
+    assert!(occupancy.tuning_hint().unwrap().contains("backed up"));
+}
+
 /// Test work_time computation
 #[test]
 fn test_work_time_computation() {
     // you need to recalc this if you change asic diff or fpga freq
     assert_eq!(
-        secs_to_fpga_ticks(calculate_work_delay_for_pll(1, 650_000_000)),
+        utils::compute_work_time(1, 650_000_000, io::F_CLK_SPEED_HZ, utils::WORK_DELAY_FUDGE),
         36296
     );
+    assert_eq!(
+        utils::compute_work_time(4, 650_000_000, io::F_CLK_SPEED_HZ, utils::WORK_DELAY_FUDGE),
+        145187
+    );
+    // without fudge factor work is sent exactly as fast as the chips compute it
+    assert_eq!(
+        utils::compute_work_time(1, 650_000_000, io::F_CLK_SPEED_HZ, 1.0),
+        40329
+    );
+    // fudge factor has to be in sane range
+    assert!(utils::validate_work_delay_fudge(utils::WORK_DELAY_FUDGE).is_ok());
+    assert!(utils::validate_work_delay_fudge(0.5).is_ok());
+    assert!(utils::validate_work_delay_fudge(1.0).is_ok());
+    assert!(utils::validate_work_delay_fudge(0.4).is_err());
+    assert!(utils::validate_work_delay_fudge(1.1).is_err());
+    assert_eq!(
+        utils::secs_to_fpga_ticks(1.0, io::F_CLK_SPEED_HZ),
+        50_000_000
+    );
+}
+
+/// Test that whole chain is examined before chip revision policy is applied
+#[test]
+fn test_check_chip_map() {
+    let bad_rev = bm1387::ChipRevision::CatchAll(0x1385);
+    let chip_map = vec![
+        ChipEnumStatus::Ok,
+        ChipEnumStatus::UnexpectedRevision(bad_rev.clone()),
+        ChipEnumStatus::Ok,
+        ChipEnumStatus::UnexpectedRevision(bad_rev),
+    ];
+
+    assert!(check_chip_map(&chip_map, config::ChipRevisionPolicy::Strict).is_err());
+    assert_eq!(
+        check_chip_map(&chip_map, config::ChipRevisionPolicy::Warn).expect("check failed"),
+        Vec::<usize>::new()
+    );
+    assert_eq!(
+        check_chip_map(&chip_map, config::ChipRevisionPolicy::Exclude).expect("check failed"),
+        vec![1, 3]
+    );
+    assert_eq!(
+        check_chip_map(&chip_map[..1], config::ChipRevisionPolicy::Strict).expect("check failed"),
+        Vec::<usize>::new()
+    );
+}
+
+/// Test that raw solution history keeps only the most recent solutions
+#[test]
+fn test_record_raw_solution() {
+    let solution = |nonce| io::Solution {
+        nonce,
+        midstate_idx: 0,
+        solution_idx: 0,
+        hardware_id: 0,
+    };
+    let nonces = |raw_solutions: &VecDeque<io::Solution>| {
+        raw_solutions
+            .iter()
+            .map(|solution| solution.nonce)
+            .collect::<Vec<_>>()
+    };
+
+    let mut raw_solutions = VecDeque::new();
+    for nonce in 0..5 {
+        record_raw_solution(&mut raw_solutions, 3, &solution(nonce));
+    }
+    assert_eq!(nonces(&raw_solutions), vec![2, 3, 4]);
+
+    // history is disabled
+    let mut raw_solutions = VecDeque::new();
+    record_raw_solution(&mut raw_solutions, 0, &solution(0));
+    assert!(raw_solutions.is_empty());
+}
+
+/// Test that missing chips are found in partially enumerated chain
+#[test]
+fn test_find_enumeration_gaps() {
+    let bad_rev = bm1387::ChipRevision::CatchAll(0x1385);
+    let mut chip_map = vec![ChipEnumStatus::Ok; 4];
+    assert_eq!(find_enumeration_gaps(&chip_map, 4), Vec::<usize>::new());
+    assert_eq!(find_enumeration_gaps(&chip_map, 6), vec![4, 5]);
+    chip_map[1] = ChipEnumStatus::UnexpectedRevision(bad_rev);
+    assert_eq!(find_enumeration_gaps(&chip_map, 6), vec![1, 4, 5]);
+    assert_eq!(find_enumeration_gaps(&[], 2), vec![0, 1]);
+}
+
+/// Test that halt interrupts waiting between hashchain start attempts
+#[tokio::test]
+async fn test_delay_unless_halted() {
+    let (halted_sender, halted_receiver) = watch::channel(false);
+
+    // nobody halts us: the delay expires
+    assert!(!delay_unless_halted(Duration::from_millis(10), halted_receiver.clone()).await);
+
+    // halt comes during the delay
+    let started = Instant::now();
+    tokio::spawn(async move {
+        delay_for(Duration::from_millis(10)).await;
+        halted_sender.broadcast(true).expect("broadcast failed");
+    });
+    assert!(delay_unless_halted(Duration::from_secs(60), halted_receiver).await);
+    assert!(started.elapsed() < Duration::from_secs(5));
+}
+
+/// Test that solution pause can be resumed explicitly and that it expires by itself
+#[tokio::test]
+async fn test_solution_pause() {
+    let solution_pause = SolutionPause::new();
+    assert!(!solution_pause.is_paused());
+    assert_eq!(solution_pause.remaining(), None);
+
+    solution_pause.pause(Duration::from_secs(60));
+    assert!(solution_pause.is_paused());
+    let remaining = solution_pause.remaining().expect("BUG: not paused");
+    assert!(remaining <= Duration::from_secs(60));
+    assert!(remaining > Duration::from_secs(50));
+
+    solution_pause.resume();
+    assert!(!solution_pause.is_paused());
+    assert_eq!(solution_pause.remaining(), None);
+
+    // pause expires after timeout
+    solution_pause.pause(Duration::from_millis(10));
+    assert!(solution_pause.is_paused());
+    delay_for(Duration::from_millis(20)).await;
+    assert_eq!(solution_pause.remaining(), None);
+    assert!(!solution_pause.is_paused());
+}
+
+/// Test that solutions are only counted as dropped while submission is paused
+#[tokio::test]
+async fn test_submit_solution_paused() {
+    let (solution_sender, mut solution_receiver) = mpsc::unbounded();
+    let work_hub = work::SolverBuilder::new(
+        Arc::new(bosminer::Frontend::new()),
+        Arc::new(bosminer::backend::IgnoreHierarchy),
+        bosminer::test_utils::create_test_work_receiver(),
+        solution_sender,
+    );
+    let mut solution_sender = None;
+    work_hub
+        .create_work_solver(|_, sender| {
+            solution_sender.replace(sender);
+            bosminer::test_utils::TestWorkSolver::new()
+        })
+        .await;
+    let solution_sender = solution_sender.expect("BUG: missing solution sender");
+    let counter = Mutex::new(counters::HashChain::new(1, 1, bm1387::CORE_ADDRESS_LAYOUT));
+    let solution = || work::Solution::from(&bosminer::test_utils::TEST_BLOCKS[0]);
+    let solution_pause = SolutionPause::new();
+
+    solution_pause.pause(Duration::from_secs(60));
+    submit_solution(&solution_pause, &solution_sender, &counter, solution()).await;
+    submit_solution(&solution_pause, &solution_sender, &counter, solution()).await;
+    assert_eq!(counter.lock().await.dropped, 2);
+    assert!(solution_receiver.try_next().is_err());
+
+    solution_pause.resume();
+    submit_solution(&solution_pause, &solution_sender, &counter, solution()).await;
+    assert_eq!(counter.lock().await.dropped, 2);
+    assert!(solution_receiver
+        .try_next()
+        .expect("BUG: solution not sent")
+        .is_some());
+}
+
+/// Build hashchain manager that doesn't touch any hardware as long as the hashchain is not
+/// started
+async fn hardware_free_manager(
+    hashboard_idx: usize,
+) -> (Arc<Manager>, mpsc::UnboundedReceiver<monitor::Message>) {
+    let (_engine_sender, engine_receiver) = work::engine_channel(work::IgnoreEvents);
+    let (solution_sender, _solution_receiver) = mpsc::unbounded();
+    let work_hub = work::SolverBuilder::new(
+        Arc::new(bosminer::Frontend::new()),
+        Arc::new(bosminer::backend::IgnoreHierarchy),
+        engine_receiver,
+        solution_sender,
+    );
+    let (halt_sender, halt_receiver) = halt::make_pair(HALT_TIMEOUT);
+    let monitor_config = monitor::Config::new(
+        None,
+        None,
+        monitor::TempSensorConfig {
+            preference: monitor::TempSensorPreference::Remote,
+            disagreement_threshold: 20.0,
+        },
+        Default::default(),
+    );
+    let monitor =
+        monitor::Monitor::new_and_start(monitor_config, None, halt_sender, halt_receiver).await;
+    let (chain_halt_sender, _chain_halt_receiver) = halt::make_pair(HALT_TIMEOUT);
+    let (monitor_tx, monitor_rx) = mpsc::unbounded();
+    let (halted_sender, halted_receiver) = watch::channel(false);
+    let chain_config = config::Backend::default()
+        .resolve_chain_config(hashboard_idx)
+        .expect("BUG: default configuration is invalid");
+
+    let manager = work_hub
+        .create_work_solver(|work_generator, solution_sender| Manager {
+            work_solver_stats: Default::default(),
+            hashboard_idx,
+            work_generator,
+            solution_sender,
+            solution_pause: Arc::new(SolutionPause::new()),
+            plug_pin: PlugPin {
+                pin: gpio::PinIn::unexported(0),
+            },
+            reset_pin: ResetPin {
+                pin: gpio::PinOut::unexported(0),
+            },
+            voltage_ctrl_backend: Arc::new(power::I2cBackend::from_i2c(
+                async_i2c::test_utils::FakeI2c::new(&[]),
+            )),
+            midstate_count: chain_config.midstate_count,
+            monitor_tx,
+            status_stream: monitor.subscribe(),
+            ownership: ownership::Lock::new(),
+            enumeration_failures: AtomicUsize::new(0),
+            quarantined: AtomicBool::new(false),
+            halted_sender,
+            halted_receiver,
+            chain_halt_sender,
+            inner: Mutex::new(ManagerInner {
+                hash_chain: None,
+                start_count: 0,
+                running_since: None,
+                enabled: true,
+            }),
+            chain_config,
+        })
+        .await;
+    (manager, monitor_rx)
+}
+
+/// Test ownership of hashchain manager through `StoppedChain` transitions (getting
+/// `RunningChain` requires real hashchain)
+#[tokio::test]
+async fn test_manager_ownership() {
+    let (manager, _monitor_rx) = hardware_free_manager(config::S9_HASHBOARD_INDEX).await;
+    let initial_frequency = manager.chain_config.frequency.clone();
+    let initial_voltage = manager.chain_config.voltage;
+    assert_eq!(manager.owned_by(), None);
+
+    let chain = manager
+        .clone()
+        .acquire("main")
+        .await
+        .expect("BUG: acquire failed")
+        .expect_stopped();
+    assert_eq!(manager.owned_by(), Some("main"));
+    // acquire while owned (even by the same owner) fails and reports the owner
+    assert_eq!(manager.clone().acquire("main").await.err(), Some("main"));
+    assert_eq!(manager.clone().acquire("tuner").await.err(), Some("main"));
+
+    // failed start hands the chain back without releasing the ownership
+    manager.disable().await;
+    let (chain, _) = chain
+        .start(
+            &initial_frequency,
+            initial_voltage,
+            config::DEFAULT_ASIC_DIFFICULTY,
+        )
+        .await
+        .err()
+        .expect("BUG: disabled hashchain started");
+    assert_eq!(manager.owned_by(), Some("main"));
+
+    // dropped chain releases the ownership
+    drop(chain);
+    assert_eq!(manager.owned_by(), None);
+
+    // halted manager refuses to start as well
+    manager.enable().await;
+    let chain = manager
+        .clone()
+        .acquire("tuner")
+        .await
+        .expect("BUG: acquire failed")
+        .expect_stopped();
+    assert_eq!(manager.owned_by(), Some("tuner"));
+    Manager::termination_handler(manager.clone()).await;
+    assert!(manager.is_halted());
+    let (chain, _) = chain
+        .start(
+            &initial_frequency,
+            initial_voltage,
+            config::DEFAULT_ASIC_DIFFICULTY,
+        )
+        .await
+        .err()
+        .expect("BUG: halted hashchain started");
+    assert_eq!(manager.owned_by(), Some("tuner"));
+    drop(chain);
+    assert_eq!(manager.owned_by(), None);
+    assert_eq!(manager.inner.lock().await.start_count, 0);
+}
+
+/// Test that disabling running hashchain stops it and that it's not started again (e.g. by
+/// restart after monitor shutdown) until it's enabled. Running hashchain requires FPGA IP core
+/// (same as `test_hchain_ctl_instance`), voltage controller is faked.
+#[tokio::test]
+async fn test_manager_disable_running_chain() {
+    let hashboard_idx = config::S9_HASHBOARD_INDEX;
+    let (manager, mut monitor_rx) = hardware_free_manager(hashboard_idx).await;
+    let initial_frequency = manager.chain_config.frequency.clone();
+    let initial_voltage = manager.chain_config.voltage;
+
+    let gpio_mgr = gpio::ControlPinManager::new();
+    let (monitor_sender, _monitor_receiver) = mpsc::unbounded();
+    let hash_chain = HashChain::new(
+        ResetPin::open(&gpio_mgr, hashboard_idx).expect("failed to make pin"),
+        PlugPin::open(&gpio_mgr, hashboard_idx).expect("failed to make pin"),
+        Arc::new(power::I2cBackend::from_i2c(
+            async_i2c::test_utils::FakeI2c::new(&[0x57]),
+        )),
+        hashboard_idx,
+        MidstateCount::new(1),
+        config::DEFAULT_ASIC_DIFFICULTY,
+        bm1387::CORE_ADDRESS_LAYOUT,
+        config::DEFAULT_SENSOR_CHIP_ADDRESS,
+        monitor_sender,
+    )
+    .expect("BUG: failed to instantiate hash chain");
+
+    // pretend the hashchain is running
+    {
+        let mut inner = manager.inner.lock().await;
+        inner.hash_chain.replace(Arc::new(hash_chain));
+        inner.running_since.replace(Instant::now());
+    }
+
+    manager.disable().await;
+    assert!(!manager.is_enabled().await);
+    {
+        let inner = manager.inner.lock().await;
+        assert!(inner.hash_chain.is_none());
+        assert_eq!(inner.uptime(), None);
+    }
+    // monitor has been told that the hashchain is off
+    match monitor_rx.try_next() {
+        Ok(Some(monitor::Message::Off)) => (),
+        message => panic!("BUG: unexpected monitor message {:?}", message),
+    }
+
+    // restart is refused while the hashchain is disabled
+    for _ in 0..2 {
+        let chain = manager
+            .clone()
+            .acquire("main")
+            .await
+            .expect("BUG: acquire failed")
+            .expect_stopped();
+        let (chain, _) = chain
+            .start(
+                &initial_frequency,
+                initial_voltage,
+                config::DEFAULT_ASIC_DIFFICULTY,
+            )
+            .await
+            .err()
+            .expect("BUG: disabled hashchain started");
+        drop(chain);
+        assert!(!manager.is_enabled().await);
+    }
+    assert_eq!(manager.inner.lock().await.start_count, 0);
+
+    manager.enable().await;
+    assert!(manager.is_enabled().await);
+}
+
+/// Test that voltage controller I2C is closed only after hashchains are halted
+#[tokio::test]
+async fn test_miner_termination_closes_i2c() {
+    let i2c = async_i2c::test_utils::FakeI2c::new(&[0x57]);
+    let voltage_ctrl_backend = Arc::new(power::I2cBackend::from_i2c(i2c.clone()));
+    let (app_halt_sender, _app_halt_receiver) = halt::make_pair(HALT_TIMEOUT);
+    let (halt_sender, halt_receiver) = halt::make_pair(HALT_TIMEOUT);
+
+    // hashchain still uses the I2C while it's being halted
+    let chain_backend = voltage_ctrl_backend.clone();
+    halt_receiver
+        .register_client("hashchain".into())
+        .await
+        .spawn_halt_handler(async move {
+            chain_backend
+                .write(8, 0x15, &[0])
+                .await
+                .expect("BUG: I2C closed before hashchain halted");
+        });
+
+    app_halt_sender
+        .clone()
+        .send_halt_with_reason("test".to_string())
+        .await;
+    Backend::miner_termination_handler(halt_sender.clone(), app_halt_sender, voltage_ctrl_backend)
+        .await;
+    assert_eq!(
+        halt_sender.last_halt_reason().await,
+        Some("test".to_string())
+    );
+    assert!(!i2c.written_bytes(0x57).is_empty());
+    assert!(i2c.write(0x57, vec![0]).await.is_err());
+}
+
+/// Test hashchain teardown order: work TX is stopped first, then voltage is disabled and only
+/// then the remaining hashchain tasks are halted
+#[tokio::test]
+async fn test_teardown_sequence() {
+    // PIC command that disables voltage
+    const DISABLE_VOLTAGE: [u8; 4] = [0x55, 0xaa, 0x15, 0];
+
+    let i2c = async_i2c::test_utils::FakeI2c::new(&[0x57]);
+    let voltage_ctrl = Arc::new(power::Control::new(
+        Arc::new(power::I2cBackend::from_i2c(i2c.clone())),
+        8,
+    ));
+    let (halt_sender, halt_receiver) = halt::make_pair(HALT_TIMEOUT);
+    let teardown = Teardown::new(8, voltage_ctrl, halt_sender);
+    // each event is recorded along with the voltage controller traffic seen at that moment
+    let events = Arc::new(StdMutex::new(Vec::new()));
+
+    // work TX task stops when it's told to
+    let (mut stop_work_receiver, work_tx_stopped_sender) = teardown.work_tx_channels().await;
+    let (work_tx_events, work_tx_i2c) = (events.clone(), i2c.clone());
+    halt_receiver
+        .register_client("work-tx".into())
+        .await
+        .spawn(async move {
+            while let Some(stop) = stop_work_receiver.recv().await {
+                if stop {
+                    break;
+                }
+            }
+            work_tx_events
+                .lock()
+                .expect("BUG: failed to lock mutex")
+                .push(("work stopped", work_tx_i2c.written_bytes(0x57)));
+            work_tx_stopped_sender
+                .send(())
+                .expect("BUG: teardown doesn't wait for work TX");
+        });
+
+    // rest of the hashchain tasks
+    let (halt_events, halt_i2c) = (events.clone(), i2c.clone());
+    halt_receiver
+        .register_client("hashchain".into())
+        .await
+        .spawn_halt_handler(async move {
+            halt_events
+                .lock()
+                .expect("BUG: failed to lock mutex")
+                .push(("halted", halt_i2c.written_bytes(0x57)));
+        });
+
+    teardown.stop().await;
+    assert_eq!(
+        *events.lock().expect("BUG: failed to lock mutex"),
+        vec![
+            ("work stopped", vec![]),
+            ("halted", DISABLE_VOLTAGE.to_vec()),
+        ]
+    );
+
+    // voltage is disabled only once
+    teardown.shutdown().await;
+    assert_eq!(i2c.written_bytes(0x57), DISABLE_VOLTAGE.to_vec());
+}
+
+/// Test short chain policy decisions and that the default matches the former half-tries rule
+#[test]
+fn test_short_chain_policy() {
+    use config::ShortChainPolicy;
+
+    assert!(!ShortChainPolicy::Never.accept(usize::MAX));
+    assert!(ShortChainPolicy::Always.accept(0));
+    assert!(!ShortChainPolicy::AfterTries(3).accept(2));
+    assert!(ShortChainPolicy::AfterTries(3).accept(3));
+
+    let retry_count = config::DEFAULT_ENUM_RETRY_COUNT;
+    for tries_left in 0..=retry_count {
+        assert_eq!(
+            ShortChainPolicy::default().accept(retry_count - tries_left),
+            tries_left <= retry_count / 2
+        );
+    }
+}
+
+/// Test median and standard deviation of chip frequencies
+#[test]
+fn test_frequency_settings_median_std_dev() {
+    let uniform = FrequencySettings::from_frequency(650_000_000, 63);
+    assert_eq!(uniform.median(), 650_000_000);
+    assert_eq!(uniform.std_dev(), 0.0);
+
+    // one outlier chip moves the average but not the median
+    let mut outlier = FrequencySettings {
+        chip: vec![
+            600_000_000,
+            600_000_000,
+            600_000_000,
+            600_000_000,
+            100_000_000,
+        ],
+    };
+    assert_eq!(outlier.median(), 600_000_000);
+    assert_eq!(outlier.avg(), 500_000_000);
+    assert_eq!(outlier.std_dev(), 200_000_000.0);
+
+    // even number of chips: mean of the two middle frequencies
+    outlier.chip = vec![2, 4, 4, 4, 5, 5, 7, 9];
+    assert_eq!(outlier.median(), 4);
+    assert_eq!(outlier.std_dev(), 2.0);
+}
+
+#[test]
+#[should_panic(expected = "no chips on chain")]
+fn test_frequency_settings_median_empty() {
+    FrequencySettings { chip: vec![] }.median();
+}
+
+#[test]
+#[should_panic(expected = "no chips on chain")]
+fn test_frequency_settings_std_dev_empty() {
+    FrequencySettings { chip: vec![] }.std_dev();
+}
+
+/// Test validation of chip frequencies against PLL table
+#[test]
+fn test_frequency_settings_validate() {
+    let table = &bm1387::PRECOMPUTED_PLL;
+    let tolerance = bm1387::PllFrequency::MAX_FREQ_ERROR_HZ;
+    let mut frequency = FrequencySettings::from_frequency(650_000_000, 63);
+    frequency
+        .validate(table, tolerance)
+        .expect("BUG: valid frequency rejected");
+
+    // frequency out of PLL range
+    frequency.chip[5] = 50_000_000;
+    assert!(frequency.validate(table, tolerance).is_err());
+    // empty table resolves no frequency
+    frequency.chip[5] = 650_000_000;
+    assert!(frequency.validate(&[], tolerance).is_err());
+}
+
+/// Test that frequencies too far from PLL settings are refused (as `HashChain::set_pll` does)
+#[test]
+fn test_frequency_settings_pll_error() {
+    let table = &bm1387::PRECOMPUTED_PLL;
+    let mut frequency = FrequencySettings::from_frequency(650_000_000, 63);
+    let exact_error = frequency
+        .pll_error(table, 63, 0)
+        .expect("BUG: PLL setting not found");
+    assert_eq!(exact_error, 0);
+
+    // half way between two PLL settings
+    let pll = bm1387::PllFrequency::lookup_freq(650_000_000).expect("BUG: no PLL setting");
+    frequency.chip[10] = pll.frequency + 300_000;
+    let error = frequency
+        .pll_error(table, 63, bm1387::PllFrequency::MAX_FREQ_ERROR_HZ)
+        .expect("BUG: default tolerance refused");
+    assert!(error > 0);
+    let message = frequency
+        .pll_error(table, 63, error - 1)
+        .expect_err("BUG: frequency over tolerance accepted");
+    assert!(message.starts_with("chip 10:"));
+    assert!(frequency.validate(table, error - 1).is_err());
+    // only chips present on the chain are checked
+    frequency
+        .pll_error(table, 10, 0)
+        .expect("BUG: missing chip checked");
+}
+
+/// Test selection of chips that `HashChain::set_pll` loads one-by-one when chips have different
+/// frequencies
+#[test]
+fn test_frequency_settings_changed_chips() {
+    let requested = FrequencySettings {
+        chip: vec![650_000_000, 600_000_000, 650_000_000, 700_000_000],
+    };
+
+    // nothing has been set on the first init, every chip gets requested frequency
+    let mut current = FrequencySettings::from_frequency(0, 0);
+    current.set_chip_count(4);
+    assert_eq!(
+        requested.changed_chips(&current, 4),
+        vec![
+            (0, 650_000_000),
+            (1, 600_000_000),
+            (2, 650_000_000),
+            (3, 700_000_000)
+        ]
+    );
+
+    // only chips that run at different frequency are loaded
+    let current = FrequencySettings::from_frequency(650_000_000, 4);
+    assert_eq!(
+        requested.changed_chips(&current, 4),
+        vec![(1, 600_000_000), (3, 700_000_000)]
+    );
+    assert!(requested.changed_chips(&requested, 4).is_empty());
+    // chips past chip count are not touched
+    assert_eq!(requested.changed_chips(&current, 3), vec![(1, 600_000_000)]);
+}
+
+/// Test chip count changes done by `HashChain::reenumerate`
+#[test]
+fn test_frequency_settings_reenumerate() {
+    let mut initial = FrequencySettings::from_frequency(650_000_000, 4);
+    initial.chip[2] = 600_000_000;
+    let mut current = initial.clone();
+
+    // chain shrunk: frequencies of remaining chips are kept, nothing has to be loaded
+    current.set_chip_count(2);
+    assert_eq!(current.chip, vec![650_000_000, 650_000_000]);
+    assert!(initial.added_chips(4, 2).is_empty());
+    assert!(initial.added_chips(2, 2).is_empty());
+
+    // chips came back: they are loaded with initial frequency
+    current.set_chip_count(4);
+    let added = initial.added_chips(2, 4);
+    assert_eq!(added, vec![(2, 600_000_000), (3, 650_000_000)]);
+    for (i, freq) in added {
+        current.chip[i] = freq;
+    }
+    assert_eq!(current.chip, initial.chip);
+
+    // more chips than initially configured get the maximum frequency
+    assert_eq!(
+        initial.added_chips(4, 6),
+        vec![(4, 650_000_000), (5, 650_000_000)]
+    );
+}
+
+/// Build monitor status of miner in PID mode with given input temperature
+fn pid_monitor_status(
+    input_temperature: monitor::ChainTemperature,
+    no_sensors: bool,
+) -> monitor::Status {
+    let temp_config = monitor::TempControlConfig {
+        dangerous_temp: 100.0,
+        hot_temp: 90.0,
+        hot_temp_hysteresis: 5.0,
+        cold_temp: 20.0,
+    };
+    let fan_config = monitor::FanControlConfig {
+        mode: monitor::FanControlMode::TargetTemperature {
+            target_temp: 75.0,
+            temp_config,
+        },
+        min_fans: 1,
+        cold_fan_speed: fan::Speed::new(30),
+        max_speed_step_per_tick: None,
+        min_fans_ticks: 2,
+    };
+    monitor::Status {
+        config: monitor::Config::new(
+            None,
+            Some(fan_config),
+            monitor::TempSensorConfig {
+                preference: monitor::TempSensorPreference::Remote,
+                disagreement_threshold: 20.0,
+            },
+            Default::default(),
+        ),
+        fan_feedback: fan::Feedback { rpm: vec![] },
+        fan_speed: None,
+        input_temperature,
+        temperature_band: monitor::TemperatureBand::Normal,
+        temperature_accumulator: monitor::TemperatureAccumulator {
+            chain_temperatures: vec![input_temperature],
+        },
+        decision_explained: monitor::ControlDecisionExplained {
+            decision: monitor::ControlDecision::Nothing,
+            reason: "test",
+        },
+        isolated_chains: vec![],
+        last_shutdown_action: None,
+        chain_power: vec![],
+        no_sensors,
+    }
+}
+
+#[test]
+fn test_preheat_without_sensors() {
+    let epsilon = config::DEFAULT_PREHEAT_TEMP_EPSILON_C;
+    let unknown = monitor::ChainTemperature::Unknown;
+
+    // hashboards without sensors in PID mode cannot be preheated
+    assert!(RunningChain::preheat_ok(
+        pid_monitor_status(unknown, true),
+        epsilon
+    ));
+    // sensor is present, but it hasn't reported temperature yet
+    assert!(!RunningChain::preheat_ok(
+        pid_monitor_status(unknown, false),
+        epsilon
+    ));
+    // sensor present and still cold
+    assert!(!RunningChain::preheat_ok(
+        pid_monitor_status(monitor::ChainTemperature::Ok(50.0), false),
+        epsilon
+    ));
+    assert!(RunningChain::preheat_ok(
+        pid_monitor_status(monitor::ChainTemperature::Ok(74.0), false),
+        epsilon
+    ));
+}
+
+#[test]
+fn test_counters_core_error_rates() {
+    let asic_difficulty = 64;
+    let mut counter = counters::HashChain::new(2, asic_difficulty, bm1387::CORE_ADDRESS_LAYOUT);
+    let core = |chip, core| bm1387::CoreAddress { chip, core };
+
+    // 3 valid solutions and 1 error
+    for _ in 0..3 {
+        counter.add_valid(core(0, 1));
+    }
+    counter.add_error(core(0, 1));
+    // only errors
+    counter.add_error(core(1, 5));
+
+    // cores without any sample are omitted
+    assert_eq!(
+        counter.core_error_rates(),
+        vec![(core(0, 1), 0.25), (core(1, 5), 1.0)]
+    );
+}
+
+#[test]
+fn test_counters_estimated_hashrate() {
+    let mut counter = counters::HashChain::new(1, 256, bm1387::CORE_ADDRESS_LAYOUT);
+    assert_eq!(counter.estimated_hashrate(Duration::from_secs(0)), 0.0);
+    for _ in 0..10 {
+        counter.add_valid(bm1387::CoreAddress { chip: 0, core: 0 });
+    }
+    // 10 solutions at difficulty 256 in 2 seconds
+    assert_eq!(
+        counter.estimated_hashrate(Duration::from_secs(2)),
+        10.0 * 256.0 * 4_294_967_296.0 / 2.0
+    );
+}
+
+#[test]
+fn test_counters_solution_errors() {
+    let mut counter = counters::HashChain::new(1, 1, bm1387::CORE_ADDRESS_LAYOUT);
+    let addr = bm1387::CoreAddress { chip: 0, core: 3 };
+    counter.add_duplicate(addr);
+    counter.add_mismatched_nonce(addr);
+    counter.add_mismatched_nonce(addr);
+    counter.add_orphaned();
+
+    // error types are counted separately, orphaned solutions aren't attributed to any core
+    assert_eq!(counter.duplicate, 1);
+    assert_eq!(counter.mismatched_nonce, 2);
+    assert_eq!(counter.orphaned, 1);
+    assert_eq!(counter.errors, 3);
+    assert_eq!(counter.chip[0].core[3].errors, 3);
+
+    counter.reset();
+    assert_eq!(counter.duplicate, 0);
+    assert_eq!(counter.mismatched_nonce, 0);
+    assert_eq!(counter.orphaned, 0);
+}
+
+#[test]
+fn test_counters_silent_cores() {
+    let mut counter = counters::HashChain::new(2, 1, bm1387::CORE_ADDRESS_LAYOUT);
+    let core = |chip, core| bm1387::CoreAddress { chip, core };
+    counter.add_valid(core(0, 1));
+    counter.add_valid(core(1, 2));
+    let base = counter.chip[0].core[1]
+        .last_valid
+        .expect("BUG: valid solution not recorded");
+    counter.chip[1].core[2].last_valid = Some(base + Duration::from_secs(5));
+    // errors don't count as a sign of life
+    counter.add_error(core(1, 3));
+
+    // live counters use current time, nothing has gone silent yet
+    assert!(counter.silent_cores(Duration::from_secs(3600)).is_empty());
+
+    // snapshot is evaluated at the time it was taken
+    let mut snapshot = counter.snapshot();
+    snapshot.stopped = Some(base + Duration::from_secs(8));
+    assert_eq!(
+        snapshot.silent_cores(Duration::from_secs(4)),
+        vec![core(0, 1)]
+    );
+    assert_eq!(
+        snapshot.silent_cores(Duration::from_secs(2)),
+        vec![core(0, 1), core(1, 2)]
+    );
+    assert!(snapshot.silent_cores(Duration::from_secs(8)).is_empty());
+
+    // time of last valid solution survives reset
+    snapshot.reset();
+    assert_eq!(
+        snapshot.silent_cores(Duration::from_secs(4)),
+        vec![core(0, 1)]
+    );
+}
+
+#[test]
+fn test_counters_diff() {
+    let mut counter = counters::HashChain::new(2, 1, bm1387::CORE_ADDRESS_LAYOUT);
+    let core = |chip, core| bm1387::CoreAddress { chip, core };
+    counter.add_valid(core(0, 1));
+    counter.add_error(core(1, 2));
+    counter.add_orphaned();
+    let earlier = counter.snapshot();
+
+    counter.add_valid(core(0, 1));
+    counter.add_valid(core(0, 1));
+    counter.add_valid(core(1, 3));
+    counter.add_duplicate(core(1, 2));
+    counter.set_chip_count(3);
+    counter.add_valid(core(2, 0));
+    let diff = counter.snapshot().diff(&earlier);
+
+    assert_eq!(diff.valid, 4);
+    assert_eq!(diff.errors, 1);
+    assert_eq!(diff.duplicate, 1);
+    assert_eq!(diff.orphaned, 0);
+    assert_eq!(diff.started, earlier.stopped.unwrap());
+    // per-chip and per-core counters are subtracted too
+    assert_eq!(diff.chip[0].valid, 2);
+    assert_eq!(diff.chip[0].core[1].valid, 2);
+    assert_eq!(diff.chip[1].valid, 1);
+    assert_eq!(diff.chip[1].errors, 1);
+    assert_eq!(diff.chip[1].core[2].errors, 1);
+    assert_eq!(diff.chip[1].core[3].valid, 1);
+    // chip missing in earlier snapshot is kept as is
+    assert_eq!(diff.chip[2].core[0].valid, 1);
+    // original counters are left intact
+    assert_eq!(counter.valid, 5);
+}
+
+/// Build configuration with given global hash chain settings
+fn config_with_hash_chain(hash_chain: config::HashChain) -> config::Backend {
+    config::Backend {
+        hash_chain_global: Some(config::HashChainGlobal {
+            overridable: Some(hash_chain),
+        }),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_validate_config_chip_frequency() {
+    let chip_frequency = |chips: &[(&str, f64)]| {
+        Some(
+            chips
+                .iter()
+                .map(|(chip_idx, frequency)| (chip_idx.to_string(), *frequency))
+                .collect(),
+        )
+    };
+
+    let config = config_with_hash_chain(config::HashChain {
+        frequency: Some(650.0),
+        chip_frequency: chip_frequency(&[("0", 600.0), ("62", 700.0)]),
+        ..Default::default()
+    });
+    config::validate_config(&config).expect("BUG: valid chip frequency rejected");
+
+    // chip index is checked against expected chip count
+    let mut config = config_with_hash_chain(config::HashChain {
+        chip_frequency: chip_frequency(&[("40", 600.0)]),
+        ..Default::default()
+    });
+    config::validate_config(&config).expect("BUG: valid chip frequency rejected");
+    config.expected_chip_count = Some(40);
+    let errors = config::validate_config(&config).expect_err("BUG: chip index out of range");
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].section, "hash_chain_global");
+
+    // chip frequency out of range
+    let config = config_with_hash_chain(config::HashChain {
+        chip_frequency: chip_frequency(&[("1", 50.0)]),
+        ..Default::default()
+    });
+    assert!(config::validate_config(&config).is_err());
+
+    // chip index has to be a number
+    let config = config_with_hash_chain(config::HashChain {
+        chip_frequency: chip_frequency(&[("first", 600.0)]),
+        ..Default::default()
+    });
+    let errors = config::validate_config(&config).expect_err("BUG: invalid chip index accepted");
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].section, "hash_chain_global");
+}
+
+/// Test that per-chip frequencies are parsed from configuration file and overlaid onto the scalar
+/// frequency of the chain
+#[test]
+fn test_resolve_chip_frequency() {
+    let config: config::Backend = toml::from_str(
+        "[hash_chain_global]\nfrequency = 600.0\n\
+         [hash_chain_global.chip_frequency]\n0 = 650.0\n62 = 550.0\n\
+         [hash_chain.6]\nfrequency = 625.0\n\
+         [hash_chain.6.chip_frequency]\n5 = 700.0\n\
+         [hash_chain.7]\nfrequency = 625.0\n",
+    )
+    .expect("BUG: cannot parse");
+    let resolve_frequency = |hash_chain_idx| {
+        config
+            .resolve_chain_config(hash_chain_idx)
+            .expect("BUG: cannot resolve chain config")
+            .frequency
+    };
+
+    // global chip frequencies overlay global frequency
+    let frequency = resolve_frequency(8);
+    assert_eq!(
+        frequency.chip.len(),
+        config::DEFAULT_EXPECTED_CHIPS_ON_CHAIN
+    );
+    assert_eq!(frequency.chip[0], 650_000_000);
+    assert_eq!(frequency.chip[1], 600_000_000);
+    assert_eq!(frequency.chip[62], 550_000_000);
+
+    // per-chain chip frequencies replace the global ones
+    let frequency = resolve_frequency(6);
+    assert_eq!(frequency.chip[0], 625_000_000);
+    assert_eq!(frequency.chip[5], 700_000_000);
+    assert_eq!(frequency.chip[62], 625_000_000);
+
+    // global chip frequencies overlay per-chain frequency
+    let frequency = resolve_frequency(7);
+    assert_eq!(frequency.chip[0], 650_000_000);
+    assert_eq!(frequency.chip[1], 625_000_000);
+    assert_eq!(frequency.chip[62], 550_000_000);
+
+    // chip index out of bounds is rejected during resolution as well
+    let config: config::Backend = toml::from_str(
+        "[hash_chain_global]\nfrequency = 600.0\n\
+         [hash_chain_global.chip_frequency]\n63 = 650.0\n",
+    )
+    .expect("BUG: cannot parse");
+    assert!(config::validate_config(&config).is_err());
+    assert!(config.resolve_chain_config(6).is_err());
+}
+
+/// Test that AsicBoost can be overridden per hash chain and that work is generated for the hash
+/// chain with the most midstates
+#[test]
+fn test_resolve_midstate_count() {
+    let midstate_count = |config: &config::Backend, hash_chain_idx| {
+        config
+            .resolve_chain_config(hash_chain_idx)
+            .expect("BUG: cannot resolve chain config")
+            .midstate_count
+            .to_count()
+    };
+
+    let config: config::Backend = toml::from_str(
+        "[hash_chain_global]\nasic_boost = false\n\
+         [hash_chain.6]\nasic_boost = true\n",
+    )
+    .expect("BUG: cannot parse");
+    assert_eq!(
+        midstate_count(&config, 6),
+        config::ASIC_BOOST_MIDSTATE_COUNT
+    );
+    assert_eq!(midstate_count(&config, 7), 1);
+    assert_eq!(config.midstate_count(), config::ASIC_BOOST_MIDSTATE_COUNT);
+
+    let mut config: config::Backend = toml::from_str(
+        "[hash_chain_global]\nasic_boost = true\n\
+         [hash_chain.6]\nasic_boost = false\n",
+    )
+    .expect("BUG: cannot parse");
+    assert_eq!(midstate_count(&config, 6), 1);
+    assert_eq!(
+        midstate_count(&config, 7),
+        config::ASIC_BOOST_MIDSTATE_COUNT
+    );
+    assert_eq!(config.midstate_count(), config::ASIC_BOOST_MIDSTATE_COUNT);
+
+    // command line switch wins over per-chain override
+    config.disable_asic_boost = Some(true);
+    assert_eq!(midstate_count(&config, 6), 1);
+    assert_eq!(midstate_count(&config, 7), 1);
+    assert_eq!(config.midstate_count(), 1);
+
+    // AsicBoost is enabled by default
+    let config = config::Backend::default();
+    assert_eq!(
+        midstate_count(&config, 6),
+        config::ASIC_BOOST_MIDSTATE_COUNT
+    );
+    assert_eq!(config.midstate_count(), config::ASIC_BOOST_MIDSTATE_COUNT);
+}
+
+#[test]
+fn test_validate_config_pll_freq_error_tolerance() {
+    let pll = bm1387::PllFrequency::lookup_freq(650_000_000).expect("BUG: no PLL setting");
+    let mut config = config_with_hash_chain(config::HashChain {
+        frequency: Some((pll.frequency + 300_000) as f64 / 1_000_000.0),
+        ..Default::default()
+    });
+    config::validate_config(&config).expect("BUG: frequency within default tolerance rejected");
+
+    // configured tolerance is used
+    let tolerance: config::Backend =
+        toml::from_str("pll_freq_error_tolerance = 100000\n").expect("BUG: cannot parse");
+    config.pll_freq_error_tolerance = tolerance.pll_freq_error_tolerance;
+    let errors = config::validate_config(&config).expect_err("BUG: frequency over tolerance");
+    assert_eq!(errors[0].section, "hash_chain_global");
+
+    config.pll_freq_error_tolerance = Some(100_000_000);
+    let errors = config::validate_config(&config).expect_err("BUG: bad tolerance accepted");
+    assert_eq!(errors[0].section, "pll_freq_error_tolerance");
+}
+
+/// Test that switches are read from configuration file and applied to resolved configuration
+#[test]
+fn test_parse_config_switches() {
+    let config: config::Backend = toml::from_str("preheat = false\nisolate_failed_chains = true\n")
+        .expect("BUG: cannot parse");
+    let chain_config = config
+        .resolve_chain_config(8)
+        .expect("BUG: cannot resolve chain config");
+    assert!(!chain_config.preheat);
+    assert!(config.resolve_monitor_config().isolate_failed_chains);
+}
+
+#[test]
+fn test_validate_config_temp_sensor() {
+    let mut config: config::Backend =
+        toml::from_str("temp_sensor_preference = \"local\"\ntemp_sensor_disagreement = 15.0\n")
+            .expect("BUG: cannot parse");
+    config::validate_config(&config).expect("BUG: valid sensor settings rejected");
+    let sensor_config = config.resolve_monitor_config().sensor_config;
+    assert_eq!(
+        sensor_config.preference,
+        monitor::TempSensorPreference::Local
+    );
+    assert_eq!(sensor_config.disagreement_threshold, 15.0);
+
+    config.temp_sensor_disagreement = Some(0.0);
+    let errors = config::validate_config(&config).expect_err("BUG: zero disagreement accepted");
+    assert_eq!(errors[0].section, "temp_sensor_disagreement");
+
+    assert!(toml::from_str::<config::Backend>("temp_sensor_preference = \"chip\"\n").is_err());
+}
+
+#[test]
+fn test_validate_config_monitor_timeouts() {
+    // missing timeouts are taken from defaults
+    let mut config: config::Backend =
+        toml::from_str("[monitor_timeouts]\nstart = 600\nrun_update = 0.5\n")
+            .expect("BUG: cannot parse");
+    let timeouts = config.resolve_monitor_timeouts();
+    assert_eq!(timeouts.start, Duration::from_secs(600));
+    assert_eq!(timeouts.run_update, Duration::from_millis(500));
+    assert_eq!(
+        timeouts.tick_length,
+        monitor::Timeouts::default().tick_length
+    );
+    // temperature updates are not sent often enough
+    let errors = config::validate_config(&config).expect_err("BUG: short run update accepted");
+    assert_eq!(errors[0].section, "monitor");
+
+    config.monitor_timeouts = Some(monitor::Timeouts {
+        run_update: Duration::from_secs(3600),
+        ..Default::default()
+    });
+    config::validate_config(&config).expect("BUG: valid timeouts rejected");
+    config.monitor_timeouts = Some(monitor::Timeouts {
+        tick_length: Duration::from_secs(0),
+        ..Default::default()
+    });
+    assert!(config::validate_config(&config).is_err());
+}
+
+#[test]
+fn test_validate_config_shutdown_fan_mode() {
+    let mut config: config::Backend =
+        toml::from_str("[shutdown_fan_mode]\nmode = \"cooldown\"\nspeed = 50\nduration = 10\n")
+            .expect("BUG: cannot parse");
+    config::validate_config(&config).expect("BUG: valid cooldown rejected");
+    assert_eq!(
+        config.resolve_monitor_config().shutdown_fan_mode,
+        monitor::ShutdownFanMode::Cooldown {
+            speed: fan::Speed::new(50),
+            duration: Duration::from_secs(10),
+        }
+    );
+
+    config.shutdown_fan_mode = Some(monitor::ShutdownFanMode::Cooldown {
+        speed: fan::Speed::new(50),
+        duration: monitor::MAX_SHUTDOWN_COOLDOWN * 2,
+    });
+    let errors = config::validate_config(&config).expect_err("BUG: long cooldown accepted");
+    assert_eq!(errors[0].section, "shutdown_fan_mode");
+
+    let config: config::Backend =
+        toml::from_str("[shutdown_fan_mode]\nmode = \"keep_last_speed\"\n")
+            .expect("BUG: cannot parse");
+    assert_eq!(
+        config.shutdown_fan_mode,
+        Some(monitor::ShutdownFanMode::KeepLastSpeed)
+    );
+    // fan speed is in percent
+    assert!(toml::from_str::<config::Backend>(
+        "[shutdown_fan_mode]\nmode = \"cooldown\"\nspeed = 150\nduration = 10\n"
+    )
+    .is_err());
+}
+
+#[test]
+fn test_validate_config_allow_insecure() {
+    let config_with_pool = |url: &str, allow_insecure: &str| {
+        toml::from_str::<config::Backend>(&format!(
+            "[[group]]\nname = \"test\"\n\
+             [[group.pool]]\nurl = \"{}\"\nuser = \"user\"\n{}",
+            url, allow_insecure
+        ))
+        .expect("BUG: cannot parse")
+    };
+
+    // insecure protocol has to be explicitly allowed
+    let config = config_with_pool("stratum2+tcp+insecure://pool.example.com", "");
+    let errors = config::validate_config(&config).expect_err("BUG: insecure pool accepted");
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].section, "config");
+    let config = config_with_pool(
+        "stratum2+tcp+insecure://pool.example.com",
+        "allow_insecure = false\n",
+    );
+    assert!(config::validate_config(&config).is_err());
+    let config = config_with_pool(
+        "stratum2+tcp+insecure://pool.example.com",
+        "allow_insecure = true\n",
+    );
+    config::validate_config(&config).expect("BUG: allowed insecure pool rejected");
+
+    // other protocols don't need the opt-in
+    let config = config_with_pool("stratum+tcp://pool.example.com", "");
+    config::validate_config(&config).expect("BUG: valid pool rejected");
+}
+
+#[test]
+fn test_validate_config_fan_speed_step() {
+    let mut config: config::Backend =
+        toml::from_str("fan_max_speed_step_per_tick = 10\n").expect("BUG: cannot parse");
+    config::validate_config(&config).expect("BUG: valid fan speed step rejected");
+    assert_eq!(
+        config
+            .resolve_monitor_config()
+            .fan_config()
+            .and_then(|v| v.max_speed_step_per_tick),
+        Some(10)
+    );
+
+    // fans would never change their speed
+    config.fan_max_speed_step_per_tick = Some(0);
+    let errors = config::validate_config(&config).expect_err("BUG: zero step accepted");
+    assert_eq!(errors[0].section, "fan_max_speed_step_per_tick");
+}
+
+#[test]
+fn test_validate_config_sensor_config() {
+    let mut config: config::Backend =
+        toml::from_str("[sensor_config]\nmax_read_errors = 30\n").expect("BUG: cannot parse");
+    config::validate_config(&config).expect("BUG: valid sensor config rejected");
+    assert_eq!(
+        config
+            .resolve_chain_config(8)
+            .expect("BUG: cannot resolve chain config")
+            .sensor_config
+            .max_read_errors,
+        30
+    );
+
+    config.sensor_config = Some(config::SensorConfig { max_read_errors: 0 });
+    let errors = config::validate_config(&config).expect_err("BUG: zero read errors accepted");
+    assert_eq!(errors[0].section, "sensor_config");
+}
+
+#[test]
+fn test_validate_config_preheat() {
+    let mut config: config::Backend =
+        toml::from_str("preheat_timeout = 60\npreheat_temp_epsilon = 5.0\n")
+            .expect("BUG: cannot parse");
+    config::validate_config(&config).expect("BUG: valid preheat settings rejected");
+    let chain_config = config
+        .resolve_chain_config(8)
+        .expect("BUG: cannot resolve chain config");
+    assert_eq!(chain_config.preheat_timeout, Duration::from_secs(60));
+    assert_eq!(chain_config.preheat_temp_epsilon, 5.0);
+
+    config.preheat_timeout = Some(Duration::from_secs(3600));
+    config.preheat_temp_epsilon = Some(-1.0);
+    let errors = config::validate_config(&config).expect_err("BUG: bad preheat accepted");
+    assert_eq!(errors.len(), 2);
+    assert_eq!(errors[0].section, "preheat_timeout");
+    assert_eq!(errors[1].section, "preheat_temp_epsilon");
+}
+
+#[test]
+fn test_validate_config_fan_min_fans_ticks() {
+    let mut config: config::Backend =
+        toml::from_str("fan_min_fans_ticks = 5\n").expect("BUG: cannot parse");
+    config::validate_config(&config).expect("BUG: valid fan ticks rejected");
+    assert_eq!(
+        config
+            .resolve_monitor_config()
+            .fan_config()
+            .map(|v| v.min_fans_ticks),
+        Some(5)
+    );
+
+    config.fan_min_fans_ticks = Some(0);
+    let errors = config::validate_config(&config).expect_err("BUG: zero fan ticks accepted");
+    assert_eq!(errors[0].section, "fan_min_fans_ticks");
+}
+
+#[test]
+fn test_validate_config_enum_retry_policy() {
+    // missing values are taken from default policy
+    let mut config: config::Backend =
+        toml::from_str("[enum_retry_policy]\nbase_delay = 0.5\nretry_count = 3\n")
+            .expect("BUG: cannot parse");
+    assert_eq!(
+        config.enum_retry_policy,
+        Some(config::EnumRetryPolicy {
+            base_delay: Duration::from_millis(500),
+            retry_count: 3,
+            ..Default::default()
+        })
+    );
+    config::validate_config(&config).expect("BUG: valid retry policy rejected");
+
+    config.enum_retry_policy = Some(config::EnumRetryPolicy {
+        multiplier: 0,
+        ..Default::default()
+    });
+    let errors = config::validate_config(&config).expect_err("BUG: zero multiplier accepted");
+    assert_eq!(errors[0].section, "enum_retry_policy");
+
+    assert!(toml::from_str::<config::Backend>("[enum_retry_policy]\nmax_delay = -1\n").is_err());
+}
+
+#[test]
+fn test_validate_config_init_work_multiplier() {
+    let mut config: config::Backend =
+        toml::from_str("init_work_multiplier = 0.0\n").expect("BUG: cannot parse");
+    assert_eq!(config.init_work_multiplier, Some(0.0));
+    config::validate_config(&config).expect("BUG: disabled open-core work rejected");
+
+    for &multiplier in [-1.0, 100.0, std::f32::NAN].iter() {
+        config.init_work_multiplier = Some(multiplier);
+        let errors = config::validate_config(&config).expect_err("BUG: bad multiplier accepted");
+        assert_eq!(errors[0].section, "init_work_multiplier");
+    }
+}
+
+#[test]
+fn test_validate_config_fpga_clock_frequency() {
+    let mut config: config::Backend =
+        toml::from_str("fpga_clock_frequency = 100000000\n").expect("BUG: cannot parse");
+    config::validate_config(&config).expect("BUG: valid FPGA clock rejected");
+    assert_eq!(
+        config
+            .resolve_chain_config(8)
+            .expect("BUG: cannot resolve chain config")
+            .fpga_clock_frequency,
+        100_000_000
+    );
+
+    for &frequency in [0, 1_000_000_000].iter() {
+        config.fpga_clock_frequency = Some(frequency);
+        let errors = config::validate_config(&config).expect_err("BUG: bad FPGA clock accepted");
+        assert_eq!(errors[0].section, "fpga_clock_frequency");
+    }
+}
+
+#[test]
+fn test_validate_config_short_chain_policy() {
+    let resolve = |config: &config::Backend| {
+        config
+            .resolve_chain_config(8)
+            .expect("BUG: cannot resolve chain config")
+            .short_chain_policy
+    };
+
+    // default policy follows configured number of retries
+    let mut config: config::Backend =
+        toml::from_str("[enum_retry_policy]\nretry_count = 4\n").expect("BUG: cannot parse");
+    assert_eq!(resolve(&config), config::ShortChainPolicy::AfterTries(2));
+    assert_eq!(
+        resolve(&Default::default()),
+        config::DEFAULT_SHORT_CHAIN_POLICY
+    );
+
+    config.short_chain_policy =
+        toml::from_str::<config::Backend>("short_chain_policy = \"always\"\n")
+            .expect("BUG: cannot parse")
+            .short_chain_policy;
+    assert_eq!(resolve(&config), config::ShortChainPolicy::Always);
+
+    let config: config::Backend = toml::from_str(
+        "short_chain_policy = { after_tries = 3 }\n[enum_retry_policy]\nretry_count = 4\n",
+    )
+    .expect("BUG: cannot parse");
+    assert_eq!(resolve(&config), config::ShortChainPolicy::AfterTries(3));
+    config::validate_config(&config).expect("BUG: valid short chain policy rejected");
+
+    // short chain is never accepted
+    let config: config::Backend =
+        toml::from_str("short_chain_policy = { after_tries = 20 }\n").expect("BUG: cannot parse");
+    let errors = config::validate_config(&config).expect_err("BUG: bad short chain policy");
+    assert_eq!(errors[0].section, "short_chain_policy");
+}
+
+#[test]
+fn test_validate_config_voltage_ramp() {
+    let mut config: config::Backend =
+        toml::from_str("[voltage_ramp]\nstep = 0.1\ndelay = 0.2\n").expect("BUG: cannot parse");
+    config::validate_config(&config).expect("BUG: valid voltage ramp rejected");
+    let voltage_ramp = config
+        .resolve_chain_config(8)
+        .expect("BUG: cannot resolve chain config")
+        .voltage_ramp
+        .expect("BUG: missing voltage ramp");
+    assert_eq!(voltage_ramp.delay, Duration::from_millis(200));
+    // 1 V is changed in 10 steps
+    assert_eq!(voltage_ramp.duration(1.0), Duration::from_millis(1800));
+
+    for &(step, delay) in [(0.0, 0.2), (-0.1, 0.2), (0.01, 10.0)].iter() {
+        config.voltage_ramp = Some(power::VoltageRamp {
+            step,
+            delay: Duration::from_secs_f64(delay),
+        });
+        let errors = config::validate_config(&config).expect_err("BUG: bad voltage ramp");
+        assert_eq!(errors[0].section, "voltage_ramp");
+    }
+}
+
+#[test]
+fn test_validate_config_init_delay() {
+    let mut config: config::Backend =
+        toml::from_str("init_delay = 2.5\n").expect("BUG: cannot parse");
+    config::validate_config(&config).expect("BUG: valid init delay rejected");
+    assert_eq!(
+        config
+            .resolve_chain_config(8)
+            .expect("BUG: cannot resolve chain config")
+            .init_delay,
+        Duration::from_millis(2500)
+    );
+
+    for &init_delay in [0.1, 60.0].iter() {
+        config.init_delay = Some(Duration::from_secs_f64(init_delay));
+        let errors = config::validate_config(&config).expect_err("BUG: bad init delay accepted");
+        assert_eq!(errors[0].section, "init_delay");
+    }
+}
+
+#[test]
+fn test_validate_config_expected_chip_count() {
+    let mut config: config::Backend =
+        toml::from_str("expected_chip_count = 60\n").expect("BUG: cannot parse");
+    config::validate_config(&config).expect("BUG: valid chip count rejected");
+    assert_eq!(
+        config
+            .resolve_chain_config(8)
+            .expect("BUG: cannot resolve chain config")
+            .expected_chip_count,
+        60
+    );
+
+    // chip addresses cannot be assigned to more chips than `MAX_CHIPS_ON_CHAIN - 1`
+    for &chip_count in [0, MAX_CHIPS_ON_CHAIN, 100].iter() {
+        config.expected_chip_count = Some(chip_count);
+        let errors = config::validate_config(&config).expect_err("BUG: bad chip count accepted");
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].section, "expected_chip_count");
+    }
+}
+
+#[test]
+fn test_validate_config_work_delay_fudge() {
+    let mut config: config::Backend =
+        toml::from_str("work_delay_fudge = 0.8\n").expect("BUG: cannot parse");
+    config::validate_config(&config).expect("BUG: valid fudge factor rejected");
+    assert_eq!(
+        config
+            .resolve_chain_config(8)
+            .expect("BUG: cannot resolve chain config")
+            .work_delay_fudge,
+        0.8
+    );
+
+    config.work_delay_fudge = Some(1.5);
+    let errors = config::validate_config(&config).expect_err("BUG: bad fudge factor accepted");
+    assert_eq!(errors[0].section, "work_delay_fudge");
+    assert!(config.resolve_chain_config(8).is_err());
+}
+
+#[test]
+fn test_validate_config_sensor_chip_address() {
+    let mut config: config::Backend =
+        toml::from_str("sensor_chip_address = 62\n").expect("BUG: cannot parse");
+    assert_eq!(config.sensor_chip_address, Some(62));
+    config::validate_config(&config).expect("BUG: valid sensor chip address rejected");
+
+    // sensor chip has to be on the chain
+    config.expected_chip_count = Some(60);
+    let errors = config::validate_config(&config).expect_err("BUG: sensor chip out of chain");
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].section, "sensor_chip_address");
+}
+
+#[test]
+fn test_validate_config_max_enumeration_failures() {
+    let config: config::Backend =
+        toml::from_str("max_enumeration_failures = 3\n").expect("BUG: cannot parse");
+    assert_eq!(config.max_enumeration_failures, Some(3));
+    config::validate_config(&config).expect("BUG: valid enumeration failures rejected");
+    assert_eq!(
+        config
+            .resolve_chain_config(6)
+            .expect("BUG: cannot resolve chain config")
+            .max_enumeration_failures,
+        3
+    );
+
+    // zero disables the quarantine
+    let config: config::Backend =
+        toml::from_str("max_enumeration_failures = 0\n").expect("BUG: cannot parse");
+    config::validate_config(&config).expect("BUG: disabled quarantine rejected");
+
+    let config: config::Backend =
+        toml::from_str("max_enumeration_failures = 1001\n").expect("BUG: cannot parse");
+    let errors = config::validate_config(&config).expect_err("BUG: too many failures accepted");
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].section, "max_enumeration_failures");
+}
+
+#[test]
+fn test_config_chip_revision_policy() {
+    let config: config::Backend = toml::from_str("").expect("BUG: cannot parse");
+    assert_eq!(
+        config
+            .resolve_chain_config(6)
+            .expect("BUG: cannot resolve chain config")
+            .chip_revision_policy,
+        config::ChipRevisionPolicy::Strict
+    );
+
+    for (value, policy) in &[
+        ("strict", config::ChipRevisionPolicy::Strict),
+        ("warn", config::ChipRevisionPolicy::Warn),
+        ("exclude", config::ChipRevisionPolicy::Exclude),
+    ] {
+        let config: config::Backend =
+            toml::from_str(&format!("chip_revision_policy = \"{}\"\n", value))
+                .expect("BUG: cannot parse");
+        config::validate_config(&config).expect("BUG: valid chip revision policy rejected");
+        assert_eq!(
+            config
+                .resolve_chain_config(6)
+                .expect("BUG: cannot resolve chain config")
+                .chip_revision_policy,
+            *policy
+        );
+    }
+
+    assert!(toml::from_str::<config::Backend>("chip_revision_policy = \"ignore\"\n").is_err());
+}
+
+#[test]
+fn test_validate_config_raw_solution_history() {
+    let config: config::Backend =
+        toml::from_str("raw_solution_history = 100\n").expect("BUG: cannot parse");
+    config::validate_config(&config).expect("BUG: valid raw solution history rejected");
+    assert_eq!(
+        config
+            .resolve_chain_config(6)
+            .expect("BUG: cannot resolve chain config")
+            .raw_solution_history,
+        100
+    );
+
+    let config: config::Backend =
+        toml::from_str("raw_solution_history = 10001\n").expect("BUG: cannot parse");
+    let errors = config::validate_config(&config).expect_err("BUG: too long history accepted");
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].section, "raw_solution_history");
+}
+
+#[test]
+fn test_validate_config_heart_beat_period() {
+    let config: config::Backend =
+        toml::from_str("heart_beat_period = 500\n").expect("BUG: cannot parse");
+    assert_eq!(config.heart_beat_period, Some(Duration::from_millis(500)));
+    config::validate_config(&config).expect("BUG: valid heart beat period rejected");
+    assert_eq!(
+        config
+            .resolve_chain_config(6)
+            .expect("BUG: cannot resolve chain config")
+            .heart_beat_period,
+        Some(Duration::from_millis(500))
+    );
+
+    for period in &[0, 99, 10_001] {
+        let config: config::Backend = toml::from_str(&format!("heart_beat_period = {}\n", period))
+            .expect("BUG: cannot parse");
+        let errors =
+            config::validate_config(&config).expect_err("BUG: invalid heart beat period accepted");
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].section, "heart_beat_period");
+    }
+
+    // negative period is refused by parser
+    assert!(toml::from_str::<config::Backend>("heart_beat_period = -1\n").is_err());
+}
+
+#[test]
+fn test_validate_config_core_address_layout() {
+    let mut config: config::Backend = toml::from_str(
+        "[core_address_layout]\n\
+         chip_shift = 3\nchip_mask = 31\ncore_shift = 25\ncore_mask = 63\n\
+         core_space_size = 60\n",
+    )
+    .expect("BUG: cannot parse");
+    assert_eq!(
+        config.core_address_layout.map(|v| v.core_space_size),
+        Some(60)
+    );
+    config::validate_config(&config).expect("BUG: valid core address layout rejected");
+
+    // core space doesn't fit core mask
+    config.core_address_layout = Some(bm1387::CoreAddressLayout {
+        core_space_size: 0x41,
+        ..config.core_address_layout.unwrap()
+    });
+    let errors = config::validate_config(&config).expect_err("BUG: invalid layout accepted");
+    assert_eq!(errors[0].section, "core_address_layout");
+}
+
+#[test]
+fn test_validate_config_voltage_bounds() {
+    let mut config: config::Backend =
+        toml::from_str("min_voltage = 8.5\nmax_voltage = 9.0\n").expect("BUG: cannot parse");
+    config::validate_config(&config).expect("BUG: valid voltage bounds rejected");
+
+    // default voltage is out of bounds
+    config.min_voltage = Some(9.0);
+    let errors = config::validate_config(&config).expect_err("BUG: voltage out of bounds");
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].section, "hash_chain_global");
+
+    // per-chain voltage is out of bounds
+    let mut config: config::Backend =
+        toml::from_str("max_voltage = 9.0\n[hash_chain.6]\nvoltage = 9.2\n")
+            .expect("BUG: cannot parse");
+    let errors = config::validate_config(&config).expect_err("BUG: voltage out of bounds");
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].section, "hash_chain.6");
+
+    config.min_voltage = Some(9.2);
+    let errors = config::validate_config(&config).expect_err("BUG: inverted bounds accepted");
+    assert_eq!(errors[0].section, "min_voltage");
+    config.min_voltage = Some(5.0);
+    let errors = config::validate_config(&config).expect_err("BUG: bound out of range");
+    assert_eq!(errors[0].section, "min_voltage");
+}
+
+#[test]
+fn test_validate_config_power_limit() {
+    let mut config: config::Backend =
+        toml::from_str("[power_limit]\nmax_watts = 1400.0\n").expect("BUG: cannot parse");
+    assert_eq!(
+        config.power_limit.as_ref().map(|v| v.max_watts),
+        Some(1400.0)
+    );
+    config::validate_config(&config).expect("BUG: valid power limit rejected");
+
+    config.power_limit = Some(monitor::PowerControlConfig { max_watts: 0.0 });
+    let errors = config::validate_config(&config).expect_err("BUG: zero power limit accepted");
+    assert_eq!(errors[0].section, "power_limit");
 }
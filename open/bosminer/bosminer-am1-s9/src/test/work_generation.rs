@@ -23,7 +23,7 @@
 use ii_logging::macros::*;
 
 use super::*;
-use crate::bm1387::MidstateCount;
+use crate::bm1387::{self, MidstateCount};
 use crate::fan;
 use crate::{FrequencySettings, HashChain, Solution};
 
@@ -146,6 +146,8 @@ async fn start_hchain(monitor_tx: mpsc::UnboundedSender<monitor::Message>) -> Ha
         hashboard_idx,
         MidstateCount::new(1),
         ASIC_DIFFICULTY,
+        bm1387::CORE_ADDRESS_LAYOUT,
+        config::DEFAULT_SENSOR_CHIP_ADDRESS,
         monitor_tx,
     )
     .unwrap();
@@ -155,6 +157,7 @@ async fn start_hchain(monitor_tx: mpsc::UnboundedSender<monitor::Message>) -> Ha
         .init(
             &FrequencySettings::from_frequency(
                 (config::DEFAULT_FREQUENCY_MHZ * 1_000_000.0) as usize,
+                config::DEFAULT_EXPECTED_CHIPS_ON_CHAIN,
             ),
             *crate::power::OPEN_CORE_VOLTAGE,
             true,
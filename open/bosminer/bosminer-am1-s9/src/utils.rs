@@ -41,3 +41,89 @@ where
         u32::from_be_bytes(self.pack())
     }
 }
+
+/// Fudge factor of work delay, see `calculate_work_delay_for_pll`
+///
+/// Lower value sends work more often: chips are less likely to run out of work when sending
+/// is delayed, but more of the work is replaced before it's finished (wasting bandwidth and
+/// work generation). Higher value risks idle chips.
+pub const WORK_DELAY_FUDGE: f64 = 0.9;
+/// Sane range of work delay fudge factor (above 1.0 chips would be idle)
+pub const WORK_DELAY_FUDGE_MIN: f64 = 0.5;
+pub const WORK_DELAY_FUDGE_MAX: f64 = 1.0;
+
+/// Check that work delay `fudge` is in the sane range
+pub fn validate_work_delay_fudge(fudge: f64) -> Result<(), String> {
+    if !(WORK_DELAY_FUDGE_MIN..=WORK_DELAY_FUDGE_MAX).contains(&fudge) {
+        return Err(format!(
+            "work delay fudge factor {} is out of range '{}..{}'",
+            fudge, WORK_DELAY_FUDGE_MIN, WORK_DELAY_FUDGE_MAX
+        ));
+    }
+    Ok(())
+}
+
+/// Helper method to calculate time to finish one piece of work
+///
+/// * `n_midstates` - number of midstates
+/// * `pll_frequency` - frequency of chip in Hz
+/// * `fudge` - fudge factor of work delay (see below)
+/// Return a number of seconds.
+///
+/// The formula for work_delay is:
+///
+///   work_delay = space_size_of_one_work / computation_speed; [sec, hashes, hashes_per_sec]
+///
+/// In our case it would be
+///
+///   work_delay = n_midstates * 2^32 / (freq * num_chips * cores_per_chip)
+///
+/// Unfortunately the space is not divided evenly, some nonces get never computed.
+/// The current conjecture is that nonce space is divided by chip/core address,
+/// ie. chip number 0x1a iterates all nonces 0x1axxxxxx. That's 6 bits of chip_address
+/// and 7 bits of core_address. Putting it all together:
+///
+///   work_delay = n_midstates * num_chips * cores_per_chip * 2^(32 - 7 - 6) / (freq * num_chips * cores_per_chip)
+///
+/// Simplify:
+///
+///   work_delay = n_midstates * 2^19 / freq
+///
+/// Last but not least, we apply fudge factor (`WORK_DELAY_FUDGE` = 0.9 by default) and send
+/// work 11% faster to offset delays when sending out/generating work/chips not getting proper
+/// work...:
+///
+///   work_delay = 0.9 * n_midstates * 2^19 / freq
+pub fn calculate_work_delay_for_pll(n_midstates: usize, pll_frequency: usize, fudge: f64) -> f64 {
+    let space_size_per_core: u64 = 1 << 19;
+    fudge * (n_midstates as u64 * space_size_per_core) as f64 / pll_frequency as f64
+}
+
+/// Helper method to convert seconds to FPGA ticks suitable to be written
+/// to `WORK_TIME` FPGA register.
+///
+/// * `fpga_frequency` - frequency of FPGA IP core clock in Hz
+/// Returns number of ticks.
+pub fn secs_to_fpga_ticks(secs: f64, fpga_frequency: usize) -> u32 {
+    (secs * fpga_frequency as f64) as u32
+}
+
+/// Calculate work time (number of FPGA ticks between sending two pieces of work to
+/// the hashchain) exactly as the miner does, suitable to be written to `WORK_TIME` FPGA
+/// register.
+///
+/// * `midstate_count` - number of midstates
+/// * `pll_frequency` - frequency of the fastest chip in Hz
+/// * `fpga_frequency` - frequency of FPGA IP core clock in Hz
+/// * `fudge` - fudge factor of work delay (`WORK_DELAY_FUDGE` is used by miner)
+pub fn compute_work_time(
+    midstate_count: usize,
+    pll_frequency: usize,
+    fpga_frequency: usize,
+    fudge: f64,
+) -> u32 {
+    secs_to_fpga_ticks(
+        calculate_work_delay_for_pll(midstate_count, pll_frequency, fudge),
+        fpga_frequency,
+    )
+}
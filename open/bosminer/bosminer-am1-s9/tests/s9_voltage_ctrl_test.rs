@@ -23,6 +23,7 @@
 use ii_async_compat::tokio;
 use tokio::time::delay_for;
 
+use bosminer_am1_s9::config;
 use bosminer_am1_s9::gpio;
 use bosminer_am1_s9::power;
 use bosminer_am1_s9::{Backend, ResetPin};
@@ -71,8 +72,7 @@ async fn test_voltage_ctrl_all_hashboards() {
     let expected_tested_hashboards: usize = 1;
 
     let gpio_mgr = gpio::ControlPinManager::new();
-    for hashboard_idx in Backend::detect_hashboards(&gpio_mgr).expect("failed to detect hashboards")
-    {
+    for hashboard_idx in Backend::detect_hashboards(&gpio_mgr, &config::DEFAULT_HASHBOARD_SLOTS) {
         test_voltage_ctrl_on_1_hashboard(&gpio_mgr, hashboard_idx).await;
         tested_hashboards += 1;
     }
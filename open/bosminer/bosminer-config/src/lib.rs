@@ -25,8 +25,12 @@ mod error;
 mod group;
 
 // Reexport inner structures
+pub use client::ConnectionConfig as ClientConnectionConfig;
 pub use client::Descriptor as ClientDescriptor;
+pub use client::Endpoint as ClientEndpoint;
 pub use client::Protocol as ClientProtocol;
+pub use client::Proxy as ClientProxy;
+pub use client::ProxyAuth as ClientProxyAuth;
 pub use client::UserInfo as ClientUserInfo;
 pub use client::URL_JAVA_SCRIPT_REGEX as CLIENT_URL_JAVA_SCRIPT_REGEX;
 
@@ -48,6 +52,12 @@ pub struct PoolConfig {
     pub user: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub password: Option<String>,
+    /// Explicit opt-in for protocols without encryption and authentication
+    /// (e.g. `stratum2+tcp+insecure`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub allow_insecure: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub connection: Option<client::ConnectionConfig>,
 }
 
 // NOTE: `#[serde(deny_unknown_fields)]` cannot be used due to flatten descriptor but the error is
@@ -27,6 +27,7 @@ mod scheduler;
 
 // Sub-modules with client implementation
 pub mod drain;
+pub mod socks5;
 pub mod stratum_v2;
 pub mod stratum_v2_channels;
 
@@ -582,6 +583,12 @@ impl Manager {
                             ),
                             pool_config.enabled.unwrap_or(default_pool_enabled),
                         )
+                        .and_then(|descriptor| {
+                            descriptor
+                                .check_insecure(pool_config.allow_insecure.unwrap_or(false))?;
+                            descriptor
+                                .with_connection(pool_config.connection.clone().unwrap_or_default())
+                        })
                         .map_err(|e| e.to_string())?;
                         let client_handle = Handle::new(descriptor, backend_info.cloned(), None);
                         group.push_client(client_handle).await;
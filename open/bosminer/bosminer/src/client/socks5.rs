@@ -0,0 +1,309 @@
+// Copyright (C) 2019  Braiins Systems s.r.o.
+//
+// This file is part of Braiins Open-Source Initiative (BOSI).
+//
+// BOSI is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+//
+// Please, keep in mind that we may also license BOSI or any part thereof
+// under a proprietary license. For more information on the terms and conditions
+// of such proprietary license or if you have any other questions, please
+// contact us at opensource@braiins.com.
+
+//! Minimal SOCKS5 client (RFC 1928, RFC 1929) used to reach pools through a proxy. The result is
+//! a plain `TcpStream` tunneled to the pool endpoint, so it can be handed over to both V1 and V2
+//! (noise) connection setup.
+
+use crate::error;
+
+pub use bosminer_config::{ClientProxy as Proxy, ClientProxyAuth as ProxyAuth};
+
+use ii_async_compat::prelude::*;
+use tokio::net::TcpStream;
+
+use std::convert::TryFrom;
+
+const SOCKS_VERSION: u8 = 5;
+const AUTH_VERSION: u8 = 1;
+
+const METHOD_NO_AUTH: u8 = 0x00;
+const METHOD_USER_PASSWORD: u8 = 0x02;
+const METHOD_NOT_ACCEPTABLE: u8 = 0xff;
+
+const CMD_CONNECT: u8 = 0x01;
+
+const ATYP_IPV4: u8 = 0x01;
+const ATYP_DOMAIN_NAME: u8 = 0x03;
+const ATYP_IPV6: u8 = 0x04;
+
+const REPLY_SUCCEEDED: u8 = 0x00;
+
+/// Convert length of SOCKS5 string field to its one byte representation
+fn field_len(field: &str, name: &str) -> error::Result<u8> {
+    Ok(u8::try_from(field.len())
+        .map_err(|_| format!("SOCKS5: {} is longer than 255 bytes", name))?)
+}
+
+/// Return `TcpStream` connected to `host`:`port` through `proxy`
+/// The host name is resolved by the proxy.
+pub async fn connect(proxy: &Proxy, host: &str, port: u16) -> error::Result<TcpStream> {
+    let mut stream = TcpStream::connect(proxy.get_host_and_port()).await?;
+
+    // Negotiate authentication method
+    let method = match proxy.auth {
+        Some(_) => METHOD_USER_PASSWORD,
+        None => METHOD_NO_AUTH,
+    };
+    stream.write_all(&[SOCKS_VERSION, 1, method]).await?;
+    let mut reply = [0u8; 2];
+    stream.read_exact(&mut reply).await?;
+    if reply[0] != SOCKS_VERSION {
+        Err(format!("SOCKS5: unexpected protocol version {}", reply[0]))?
+    }
+    if reply[1] == METHOD_NOT_ACCEPTABLE || reply[1] != method {
+        Err("SOCKS5: proxy refused authentication method")?
+    }
+
+    // Authenticate with username/password
+    if let Some(auth) = proxy.auth.as_ref() {
+        let mut request = vec![AUTH_VERSION, field_len(&auth.user, "user")?];
+        request.extend(auth.user.as_bytes());
+        request.push(field_len(&auth.password, "password")?);
+        request.extend(auth.password.as_bytes());
+        stream.write_all(&request).await?;
+        stream.read_exact(&mut reply).await?;
+        if reply[0] != AUTH_VERSION {
+            Err(format!(
+                "SOCKS5: unexpected authentication version {}",
+                reply[0]
+            ))?
+        }
+        if reply[1] != REPLY_SUCCEEDED {
+            Err("SOCKS5: proxy authentication failed")?
+        }
+    }
+
+    // Ask proxy to connect to the destination
+    let mut request = vec![
+        SOCKS_VERSION,
+        CMD_CONNECT,
+        0,
+        ATYP_DOMAIN_NAME,
+        field_len(host, "host")?,
+    ];
+    request.extend(host.as_bytes());
+    request.extend(&port.to_be_bytes());
+    stream.write_all(&request).await?;
+
+    let mut reply = [0u8; 4];
+    stream.read_exact(&mut reply).await?;
+    if reply[0] != SOCKS_VERSION {
+        Err(format!("SOCKS5: unexpected protocol version {}", reply[0]))?
+    }
+    if reply[1] != REPLY_SUCCEEDED {
+        Err(format!(
+            "SOCKS5: proxy failed to connect to {}:{} (reply {:#x})",
+            host, port, reply[1]
+        ))?
+    }
+    // Skip bound address and port the proxy reports
+    let address_len = match reply[3] {
+        ATYP_IPV4 => 4,
+        ATYP_IPV6 => 16,
+        ATYP_DOMAIN_NAME => {
+            let mut len = [0u8; 1];
+            stream.read_exact(&mut len).await?;
+            len[0] as usize
+        }
+        atyp => Err(format!("SOCKS5: unexpected address type {:#x}", atyp))?,
+    };
+    let mut bound_address = vec![0u8; address_len + 2];
+    stream.read_exact(&mut bound_address).await?;
+
+    Ok(stream)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use tokio::net::TcpListener;
+
+    const HOST: &str = "pool.example.com";
+    const PORT: u16 = 3333;
+
+    /// What the fake proxy has been asked for
+    #[derive(Debug, PartialEq)]
+    struct Request {
+        auth: Option<ProxyAuth>,
+        host: String,
+        port: u16,
+    }
+
+    async fn read_field(stream: &mut TcpStream) -> String {
+        let mut len = [0u8; 1];
+        stream
+            .read_exact(&mut len)
+            .await
+            .expect("BUG: cannot read field length");
+        let mut field = vec![0u8; len[0] as usize];
+        stream
+            .read_exact(&mut field)
+            .await
+            .expect("BUG: cannot read field");
+        String::from_utf8(field).expect("BUG: invalid field")
+    }
+
+    /// In-process SOCKS5 server that handles single client. The CONNECT reply is sent with
+    /// `reply_version` and then all data going through the tunnel are echoed back.
+    async fn fake_proxy(mut listener: TcpListener, reply_version: u8) -> Request {
+        let (mut stream, _) = listener
+            .accept()
+            .await
+            .expect("BUG: fake proxy cannot accept");
+
+        let mut greeting = [0u8; 3];
+        stream
+            .read_exact(&mut greeting)
+            .await
+            .expect("BUG: cannot read greeting");
+        assert_eq!(greeting[..2], [SOCKS_VERSION, 1]);
+        let method = greeting[2];
+        stream
+            .write_all(&[SOCKS_VERSION, method])
+            .await
+            .expect("BUG: cannot send method");
+
+        let auth = if method == METHOD_USER_PASSWORD {
+            let mut version = [0u8; 1];
+            stream
+                .read_exact(&mut version)
+                .await
+                .expect("BUG: cannot read authentication version");
+            assert_eq!(version[0], AUTH_VERSION);
+            let user = read_field(&mut stream).await;
+            let password = read_field(&mut stream).await;
+            stream
+                .write_all(&[AUTH_VERSION, REPLY_SUCCEEDED])
+                .await
+                .expect("BUG: cannot send authentication reply");
+            Some(ProxyAuth { user, password })
+        } else {
+            None
+        };
+
+        let mut request = [0u8; 4];
+        stream
+            .read_exact(&mut request)
+            .await
+            .expect("BUG: cannot read request");
+        assert_eq!(request, [SOCKS_VERSION, CMD_CONNECT, 0, ATYP_DOMAIN_NAME]);
+        let host = read_field(&mut stream).await;
+        let mut port = [0u8; 2];
+        stream
+            .read_exact(&mut port)
+            .await
+            .expect("BUG: cannot read port");
+        // Report bound address 0.0.0.0:0
+        stream
+            .write_all(&[
+                reply_version,
+                REPLY_SUCCEEDED,
+                0,
+                ATYP_IPV4,
+                0,
+                0,
+                0,
+                0,
+                0,
+                0,
+            ])
+            .await
+            .expect("BUG: cannot send reply");
+
+        let mut data = [0u8; 4];
+        if stream.read_exact(&mut data).await.is_ok() {
+            stream
+                .write_all(&data)
+                .await
+                .expect("BUG: cannot echo data");
+        }
+
+        Request {
+            auth,
+            host,
+            port: u16::from_be_bytes(port),
+        }
+    }
+
+    async fn start_fake_proxy(
+        auth: Option<ProxyAuth>,
+        reply_version: u8,
+    ) -> (Proxy, tokio::task::JoinHandle<Request>) {
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("BUG: cannot bind fake proxy");
+        let addr = listener.local_addr().expect("BUG: missing local address");
+        let proxy = Proxy {
+            host: addr.ip().to_string(),
+            port: addr.port(),
+            auth,
+        };
+        (proxy, tokio::spawn(fake_proxy(listener, reply_version)))
+    }
+
+    #[tokio::test]
+    async fn test_connect() {
+        let auths = vec![
+            None,
+            Some(ProxyAuth {
+                user: "user".to_string(),
+                password: "secret".to_string(),
+            }),
+        ];
+        for auth in auths {
+            let (proxy, fake_proxy) = start_fake_proxy(auth.clone(), SOCKS_VERSION).await;
+
+            let mut stream = connect(&proxy, HOST, PORT)
+                .await
+                .expect("BUG: cannot connect through proxy");
+            stream
+                .write_all(b"ping")
+                .await
+                .expect("BUG: cannot write to tunnel");
+            let mut data = [0u8; 4];
+            stream
+                .read_exact(&mut data)
+                .await
+                .expect("BUG: cannot read from tunnel");
+            assert_eq!(&data, b"ping");
+
+            assert_eq!(
+                fake_proxy.await.expect("BUG: fake proxy failed"),
+                Request {
+                    auth,
+                    host: HOST.to_string(),
+                    port: PORT,
+                }
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_connect_unexpected_version() {
+        let (proxy, fake_proxy) = start_fake_proxy(None, SOCKS_VERSION - 1).await;
+
+        assert!(connect(&proxy, HOST, PORT).await.is_err());
+        fake_proxy.await.expect("BUG: fake proxy failed");
+    }
+}
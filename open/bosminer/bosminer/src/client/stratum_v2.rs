@@ -25,6 +25,8 @@ pub mod telemetry;
 
 use ii_logging::macros::*;
 
+use super::socks5;
+
 use crate::error;
 use crate::hal;
 use crate::job;
@@ -37,7 +39,7 @@ use failure::ResultExt;
 
 use ii_bitcoin::HashTrait;
 
-use bosminer_config::{ClientDescriptor, ClientProtocol};
+use bosminer_config::{ClientConnectionConfig, ClientDescriptor, ClientProtocol};
 use bosminer_macros::ClientNode;
 
 use async_trait::async_trait;
@@ -45,6 +47,8 @@ use futures::channel::mpsc;
 use futures::lock::Mutex;
 use ii_async_compat::prelude::*;
 use ii_async_compat::select;
+use ii_wire::Backoff;
+use tokio::time::delay_for;
 
 use std::collections::VecDeque;
 use std::fmt;
@@ -71,6 +75,52 @@ use std::collections::HashMap;
 // TODO: move it to the stratum crate
 const VERSION_MASK: u32 = 0x1fffe000;
 
+/// How to retry failed connection attempt (TCP connect and noise handshake)
+///
+/// Delays between attempts are generated by `ii_wire::DefaultBackoff` with `base_delay` as the unit
+/// of the fibonacci sequence and they are capped at `max_delay`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct RetryPolicy {
+    pub base_delay: time::Duration,
+    pub max_delay: time::Duration,
+    /// Number of retries after the first failed attempt
+    pub retry_count: usize,
+}
+
+impl RetryPolicy {
+    /// Override default policy with values present in pool connection `config`
+    pub fn from_config(config: &ClientConnectionConfig) -> Self {
+        let default = Self::default();
+        Self {
+            base_delay: config
+                .retry_delay_ms
+                .map(time::Duration::from_millis)
+                .unwrap_or(default.base_delay),
+            max_delay: config
+                .retry_max_delay_ms
+                .map(time::Duration::from_millis)
+                .unwrap_or(default.max_delay),
+            retry_count: config.retry_count.unwrap_or(default.retry_count),
+        }
+    }
+
+    /// Fresh generator of delays between successive attempts
+    pub fn backoff(&self) -> ii_wire::DefaultBackoff {
+        ii_wire::DefaultBackoff::new(self.base_delay, self.max_delay)
+    }
+}
+
+impl Default for RetryPolicy {
+    /// Attempt only once to connect (as the stratum client is being managed externally)
+    fn default() -> Self {
+        Self {
+            base_delay: time::Duration::from_secs(1),
+            max_delay: time::Duration::from_secs(10),
+            retry_count: 0,
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct ConnectionDetails {
     /// TODO temporary field that denotes the protocol, it will be replaced by a `Connector`
@@ -79,6 +129,10 @@ pub struct ConnectionDetails {
     pub user: String,
     pub host: String,
     pub port: u16,
+    /// Retry connection for flaky pool links
+    pub retry_policy: RetryPolicy,
+    /// Reach the pool through SOCKS5 proxy
+    pub proxy: Option<socks5::Proxy>,
 }
 
 impl ConnectionDetails {
@@ -88,6 +142,8 @@ impl ConnectionDetails {
             user: descriptor.user.clone(),
             host: descriptor.host.clone(),
             port: descriptor.port(),
+            retry_policy: RetryPolicy::from_config(&descriptor.connection),
+            proxy: descriptor.proxy.clone(),
         }
     }
 
@@ -541,16 +597,61 @@ impl StratumConnectionHandler {
             .unwrap_or(Err("Unexpected response for stratum open channel".into()))
     }
 
+    /// Connect to the remote server and retry according to `ConnectionDetails::retry_policy`
+    /// Each attempt is limited by `StratumClient::CONNECTION_TIMEOUT`. The error of the last
+    /// attempt is returned when all attempts fail.
     async fn connect(&self) -> error::Result<v2::Framed> {
         let connection_details = self.client.connection_details();
-        let addr = ii_wire::Address::from_str(connection_details.get_host_and_port().as_str())?;
-        let mut client = ii_wire::Client::new(addr);
-        // Attempt only once to connect (as the stratum client is being managed externally)
-        let connection = client.next().await?;
+        let retry_policy = connection_details.retry_policy;
+        let mut backoff = retry_policy.backoff();
+        let mut retry = 0;
+        loop {
+            let result = self
+                .connect_once(&connection_details)
+                .timeout(StratumClient::CONNECTION_TIMEOUT)
+                .await
+                .map_err(|_| error::ErrorKind::General("Connection timeout".to_string()).into())
+                .and_then(|result| result);
+            match result {
+                Ok(framed_connection) => return Ok(framed_connection),
+                Err(e) if retry < retry_policy.retry_count => {
+                    let delay = backoff.next();
+                    retry += 1;
+                    warn!(
+                        "Failed to connect to {} ({}), retrying in {}ms ({}/{})...",
+                        connection_details.get_host_and_port(),
+                        e,
+                        delay.as_millis(),
+                        retry,
+                        retry_policy.retry_count
+                    );
+                    delay_for(delay).await;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    /// Single attempt to connect (new TCP connection + handshake)
+    async fn connect_once(
+        &self,
+        connection_details: &ConnectionDetails,
+    ) -> error::Result<v2::Framed> {
+        let connection = match connection_details.proxy.as_ref() {
+            Some(proxy) => {
+                socks5::connect(proxy, &connection_details.host, connection_details.port).await?
+            }
+            None => {
+                let addr =
+                    ii_wire::Address::from_str(connection_details.get_host_and_port().as_str())?;
+                let mut client = ii_wire::Client::new(addr);
+                client.next().await?
+            }
+        };
 
         // TODO this will be replaced by a 'connector' that will be set when building stratum
         // client instance
-        let client_framed_stream = match connection_details.protocol {
+        let client_framed_stream = match connection_details.protocol.clone() {
             // V2 secure connector
             ClientProtocol::StratumV2(upstream_authority_public_key) => {
                 let noise_initiator =
@@ -560,6 +661,10 @@ impl StratumConnectionHandler {
             }
             // V2 insecure connector
             ClientProtocol::StratumV2Insecure => {
+                warn!(
+                    "Connection to {} is INSECURE (no encryption, no authentication of pool)!",
+                    connection_details.get_host_and_port()
+                );
                 ii_wire::Connection::<v2::Framing>::new(connection).into_inner()
             }
             // Anything else is considered a bug
@@ -909,13 +1014,8 @@ impl StratumClient {
         let host_and_port = connection_details.get_host_and_port();
         let user = connection_details.user.clone();
 
-        match connection_handler
-            .connect()
-            .timeout(Self::CONNECTION_TIMEOUT)
-            .await
-            .map_err(|_| error::ErrorKind::General("Connection timeout".to_string()).into())
-        {
-            Ok(Ok(framed_connection)) => {
+        match connection_handler.connect().await {
+            Ok(framed_connection) => {
                 let (framed_sink, mut framed_stream) = framed_connection.split();
                 let framed_sink = Arc::new(Mutex::new(framed_sink));
                 match connection_handler
@@ -943,7 +1043,7 @@ impl StratumClient {
                     }
                 }
             }
-            Ok(Err(e)) | Err(e) => {
+            Err(e) => {
                 info!(
                     "Failed to connect to {}, user={} {:?}",
                     host_and_port, user, e
@@ -1043,3 +1143,41 @@ impl fmt::Display for StratumClient {
         )
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_retry_policy() {
+        let default = RetryPolicy::default();
+        assert_eq!(
+            RetryPolicy::from_config(&ClientConnectionConfig::default()),
+            default
+        );
+
+        let retry_policy = RetryPolicy::from_config(&ClientConnectionConfig {
+            retry_count: Some(6),
+            retry_delay_ms: Some(100),
+            retry_max_delay_ms: Some(500),
+            ..Default::default()
+        });
+        assert_eq!(
+            retry_policy,
+            RetryPolicy {
+                base_delay: time::Duration::from_millis(100),
+                max_delay: time::Duration::from_millis(500),
+                retry_count: 6,
+            }
+        );
+
+        let mut backoff = retry_policy.backoff();
+        let delays: Vec<_> = (0..retry_policy.retry_count)
+            .map(|_| backoff.next().as_millis())
+            .collect();
+        assert_eq!(delays, vec![100, 100, 200, 300, 500, 500]);
+
+        // Every connection gets a fresh sequence of delays
+        assert_eq!(retry_policy.backoff().next().as_millis(), 100);
+    }
+}
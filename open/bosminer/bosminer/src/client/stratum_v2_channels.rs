@@ -24,6 +24,8 @@
 
 use ii_logging::macros::*;
 
+use super::socks5;
+
 use crate::error;
 use crate::job;
 use crate::node;
@@ -35,7 +37,7 @@ use failure::ResultExt;
 
 use ii_bitcoin::HashTrait;
 
-use bosminer_config::{ClientDescriptor, ClientProtocol};
+use bosminer_config::{ClientDescriptor, ClientEndpoint as Endpoint, ClientProtocol};
 use bosminer_macros::ClientNode;
 
 use async_trait::async_trait;
@@ -45,8 +47,10 @@ use ii_async_compat::prelude::*;
 use ii_async_compat::select;
 
 use std::collections::VecDeque;
+use std::convert::TryFrom;
 use std::fmt;
 use std::net::ToSocketAddrs;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::{Arc, Weak};
 use std::time;
 
@@ -75,6 +79,14 @@ pub struct ConnectionDetails {
     pub host: String,
     pub port: u16,
     pub fragment: Option<String>,
+    /// Reach the pool through SOCKS5 proxy
+    pub proxy: Option<socks5::Proxy>,
+    /// Capacity of channels between V2 client, V2->V1 translation and upstream V1 connection
+    pub translation_channel_size: usize,
+    /// Maximum time to wait for upstream V1 connection to receive or send a frame
+    pub event_timeout: time::Duration,
+    /// Ordered list of endpoints tried when the primary one fails
+    pub failover: Vec<Endpoint>,
 }
 
 impl ConnectionDetails {
@@ -84,15 +96,33 @@ impl ConnectionDetails {
             host: descriptor.host.clone(),
             port: descriptor.port(),
             fragment: descriptor.fragment.clone(),
+            proxy: descriptor.proxy.clone(),
+            translation_channel_size: descriptor
+                .connection
+                .translation_channel_size
+                .unwrap_or(TranslationHandler::DEFAULT_CHANNEL_SIZE),
+            event_timeout: descriptor
+                .connection
+                .event_timeout_ms
+                .map(time::Duration::from_millis)
+                .unwrap_or(StratumClient::EVENT_TIMEOUT),
+            failover: descriptor.failover.clone(),
         }
     }
 
-    fn get_host_and_port(&self) -> String {
-        format!("{}:{}", self.host, self.port)
+    /// All upstream endpoints in order of preference starting with the primary one
+    fn endpoints(&self) -> Vec<Endpoint> {
+        let primary = Endpoint {
+            host: self.host.clone(),
+            port: self.port,
+        };
+        std::iter::once(primary)
+            .chain(self.failover.iter().cloned())
+            .collect()
     }
 
-    fn try_enable_xnsub(&self) -> bool {
-        self.host.find(".nicehash.com").is_some()
+    fn try_enable_xnsub(&self, endpoint: &Endpoint) -> bool {
+        endpoint.host.find(".nicehash.com").is_some()
             || self
                 .fragment
                 .as_ref()
@@ -549,10 +579,15 @@ impl StratumConnectionHandler {
             .unwrap_or(Err("Unexpected response for stratum open channel".into()))
     }
 
-    async fn connect(self) -> error::Result<v1::Framed> {
-        let socket_addr = self
-            .client
-            .connection_details
+    async fn connect(self, endpoint: &Endpoint) -> error::Result<v1::Framed> {
+        if let Some(proxy) = self.client.connection_details.proxy.as_ref() {
+            let stream = socks5::connect(proxy, &endpoint.host, endpoint.port)
+                .await
+                .context("Cannot connect to stratum server through proxy")?;
+            return Ok(Connection::<v1::Framing>::new(stream).into_inner());
+        }
+
+        let socket_addr = endpoint
             .get_host_and_port()
             .to_socket_addrs()
             .context("Invalid server address")?
@@ -642,12 +677,18 @@ pub struct StratumClient {
     solutions: SolutionQueue,
     job_sender: Mutex<job::Sender>,
     solution_receiver: Mutex<job::SolutionReceiver>,
+    /// Statistics of V2->V1 translation for current connection
+    translation_stats: Mutex<Option<Arc<TranslationStats>>>,
+    /// Index of the endpoint (see `ConnectionDetails::endpoints`) to be tried first
+    endpoint_idx: AtomicUsize,
 }
 
 impl StratumClient {
     const CONNECTION_TIMEOUT: time::Duration = time::Duration::from_secs(5);
     const EVENT_TIMEOUT: time::Duration = time::Duration::from_secs(60);
     const SEND_TIMEOUT: time::Duration = time::Duration::from_secs(2);
+    /// How often availability of the primary endpoint is checked when connected to a failover one
+    const FAILBACK_INTERVAL: time::Duration = time::Duration::from_secs(60);
 
     pub fn new(connection_details: ConnectionDetails, solver: job::Solver) -> Self {
         let (stop_sender, stop_receiver) = mpsc::channel(1);
@@ -661,9 +702,16 @@ impl StratumClient {
             solutions: Mutex::new(VecDeque::new()),
             job_sender: Mutex::new(solver.job_sender),
             solution_receiver: Mutex::new(solver.solution_receiver),
+            translation_stats: Mutex::new(None),
+            endpoint_idx: AtomicUsize::new(0),
         }
     }
 
+    /// Statistics of V2->V1 translation for current (or last) upstream connection
+    pub async fn translation_stats(&self) -> Option<Arc<TranslationStats>> {
+        self.translation_stats.lock().await.clone()
+    }
+
     async fn update_last_job(&self, job: Arc<StratumJob>) {
         self.last_job.lock().await.replace(Arc::downgrade(&job));
     }
@@ -746,29 +794,110 @@ impl StratumClient {
         }
     }
 
+    /// Order in which `count` endpoints are tried starting with endpoint `first_idx`
+    fn endpoint_order(first_idx: usize, count: usize) -> impl Iterator<Item = usize> {
+        (0..count).map(move |i| (first_idx + i) % count)
+    }
+
+    /// Endpoint to be tried first after session with endpoint `idx` has terminated
+    /// Session with a failover endpoint fails back to the primary one as well as explicit
+    /// reconnect request does. Otherwise the primary endpoint is considered broken and the first
+    /// failover endpoint is tried.
+    fn next_endpoint_idx(idx: usize, count: usize, reconnect_requested: bool) -> usize {
+        if idx == 0 && !reconnect_requested {
+            1 % count
+        } else {
+            0
+        }
+    }
+
+    /// Try all endpoints starting with the current one and return the first successful
+    /// connection together with index of the connected endpoint
+    async fn connect(self: &Arc<Self>, endpoints: &[Endpoint]) -> Option<(usize, v1::Framed)> {
+        let first_idx = self.endpoint_idx.load(Ordering::Relaxed);
+        for idx in Self::endpoint_order(first_idx, endpoints.len()) {
+            let endpoint = &endpoints[idx];
+            match StratumConnectionHandler::new(self.clone())
+                .connect(endpoint)
+                .timeout(Self::CONNECTION_TIMEOUT)
+                .await
+            {
+                Ok(Ok(v1_framed_connection)) => {
+                    self.endpoint_idx.store(idx, Ordering::Relaxed);
+                    return Some((idx, v1_framed_connection));
+                }
+                Ok(Err(e)) => warn!("Cannot connect to {}: {}", endpoint.get_host_and_port(), e),
+                Err(_) => warn!("Connection to {} timed out", endpoint.get_host_and_port()),
+            }
+        }
+        None
+    }
+
+    /// Resolve as soon as the `primary` endpoint is available again
+    async fn wait_for_primary(self: &Arc<Self>, primary: &Endpoint) {
+        loop {
+            tokio::time::delay_for(Self::FAILBACK_INTERVAL).await;
+            if let Ok(Ok(_)) = StratumConnectionHandler::new(self.clone())
+                .connect(primary)
+                .timeout(Self::CONNECTION_TIMEOUT)
+                .await
+            {
+                return;
+            }
+        }
+    }
+
     async fn run(self: Arc<Self>) {
-        match StratumConnectionHandler::new(self.clone())
-            .connect()
-            .timeout(Self::CONNECTION_TIMEOUT)
-            .await
-        {
-            Ok(Ok(v1_framed_connection)) => {
+        let endpoints = self.connection_details.endpoints();
+        match self.connect(&endpoints).await {
+            Some((idx, v1_framed_connection)) => {
                 if self.status.initiate_running() {
                     let options = V2ToV1TranslationOptions {
-                        try_enable_xnsub: self.connection_details.try_enable_xnsub(),
+                        try_enable_xnsub: self.connection_details.try_enable_xnsub(&endpoints[idx]),
                     };
-                    let (translation_handler, v2_translation_rx, v2_translation_tx) =
-                        TranslationHandler::new(v1_framed_connection, options);
+                    let (translation_handler, v2_translation_rx, v2_translation_tx, stats) =
+                        TranslationHandler::new(
+                            v1_framed_connection,
+                            options,
+                            self.connection_details.translation_channel_size,
+                            self.connection_details.event_timeout,
+                        );
+                    self.translation_stats.lock().await.replace(stats.clone());
                     tokio::spawn(async move {
-                        let status = translation_handler.run().await;
-                        info!("V2->V1 translation terminated: {:?}", status);
+                        match translation_handler.run().await {
+                            // Client side ended the session, nothing unusual
+                            error::Connector::ClientShutdown => {
+                                info!("V2->V1 translation terminated: client shutdown")
+                            }
+                            e => warn!("V2->V1 translation terminated: {}", e),
+                        }
                     });
-                    self.clone()
-                        .run_job_solver(v2_translation_rx, v2_translation_tx)
-                        .await;
+                    let job_solver = self
+                        .clone()
+                        .run_job_solver(v2_translation_rx, v2_translation_tx);
+                    if idx == 0 {
+                        job_solver.await;
+                    } else {
+                        select! {
+                            _ = job_solver.fuse() => {}
+                            _ = self.wait_for_primary(&endpoints[0]).fuse() => {
+                                info!(
+                                    "Primary endpoint {} is available again, failing back",
+                                    endpoints[0].get_host_and_port()
+                                );
+                            }
+                        }
+                    }
+
+                    let next_idx = Self::next_endpoint_idx(
+                        idx,
+                        endpoints.len(),
+                        *stats.reconnect_messages.take_snapshot() > 0,
+                    );
+                    self.endpoint_idx.store(next_idx, Ordering::Relaxed);
                 }
             }
-            Ok(Err(_)) | Err(_) => self.status.initiate_failing(),
+            None => self.status.initiate_failing(),
         }
     }
 
@@ -801,6 +930,32 @@ impl StratumClient {
     }
 }
 
+/// Counters of V2->V1 translation shared between `TranslationHandler` and its user
+#[derive(Debug, Default)]
+pub struct TranslationStats {
+    /// V1 frames received from upstream and passed to the translation
+    pub v1_to_v2_frames: stats::CounterU64,
+    /// V2 frames received from client and passed to the translation
+    pub v2_to_v1_frames: stats::CounterU64,
+    /// Reconnect requests received from upstream V1 server
+    pub reconnect_messages: stats::CounterU64,
+    last_activity: Mutex<Option<time::Instant>>,
+}
+
+impl TranslationStats {
+    /// Time of the last frame passing through the translation in any direction
+    pub async fn last_activity(&self) -> Option<time::Instant> {
+        *self.last_activity.lock().await
+    }
+
+    async fn touch(&self) {
+        self.last_activity
+            .lock()
+            .await
+            .replace(time::Instant::now());
+    }
+}
+
 /// This object receives V1 messages and passes them to `V2ToV1Translation` component for
 /// translation. The user of this component is provided with an Rx/Tx channel pair that is
 /// intended for sending V2 messages and receiving the translated V2 messages.
@@ -814,23 +969,37 @@ struct TranslationHandler {
     v1_translation_rx: mpsc::Receiver<v1::Frame>,
     /// V2 Frames from the client that we use for feeding the translator
     v2_client_rx: mpsc::Receiver<v2::Frame>,
+    /// Translation statistics shared with the user of this handler
+    stats: Arc<TranslationStats>,
+    /// Timeout for receiving and sending frames over V1 connection
+    event_timeout: time::Duration,
 }
 
 impl TranslationHandler {
-    const MAX_TRANSLATION_CHANNEL_SIZE: usize = 10;
+    const DEFAULT_CHANNEL_SIZE: usize = 10;
 
-    /// Builds the new translation handler and provides Tx/Rx communication ends
+    /// Builds the new translation handler and provides Tx/Rx communication ends together with
+    /// shared translation statistics
+    /// `channel_size` - capacity of each of the internal channels
+    /// `event_timeout` - maximum time of upstream V1 connection inactivity or frame sending
     fn new(
         v1_conn: v1::Framed,
         options: V2ToV1TranslationOptions,
-    ) -> (Self, mpsc::Receiver<v2::Frame>, mpsc::Sender<v2::Frame>) {
-        let (v1_translation_tx, v1_translation_rx) =
-            mpsc::channel(Self::MAX_TRANSLATION_CHANNEL_SIZE);
-        let (v2_translation_tx, v2_translation_rx) =
-            mpsc::channel(Self::MAX_TRANSLATION_CHANNEL_SIZE);
-        let (v2_client_tx, v2_client_rx) = mpsc::channel(Self::MAX_TRANSLATION_CHANNEL_SIZE);
+        channel_size: usize,
+        event_timeout: time::Duration,
+    ) -> (
+        Self,
+        mpsc::Receiver<v2::Frame>,
+        mpsc::Sender<v2::Frame>,
+        Arc<TranslationStats>,
+    ) {
+        debug!("V2->V1 translation channel size: {}", channel_size);
+        let (v1_translation_tx, v1_translation_rx) = mpsc::channel(channel_size);
+        let (v2_translation_tx, v2_translation_rx) = mpsc::channel(channel_size);
+        let (v2_client_tx, v2_client_rx) = mpsc::channel(channel_size);
 
         let translation = V2ToV1Translation::new(v1_translation_tx, v2_translation_tx, options);
+        let stats = Arc::new(TranslationStats::default());
 
         (
             Self {
@@ -838,9 +1007,12 @@ impl TranslationHandler {
                 v1_conn,
                 v1_translation_rx,
                 v2_client_rx,
+                stats: stats.clone(),
+                event_timeout,
             },
             v2_translation_rx,
             v2_client_tx,
+            stats,
         )
     }
 
@@ -851,58 +1023,89 @@ impl TranslationHandler {
     /// - v1_conn_rx -> build message + accept(translation)
     /// - v2_client_rx -> build message + accept(translation)
     /// - v1_translation_rx -> send
-    /// terminate upon any error or timeout
-    async fn run(mut self) -> error::Result<()> {
+    /// terminate upon any error or timeout, the returned error tells the cause
+    async fn run(mut self) -> error::Connector {
         //while !self.status.is_shutting_down() {
         info!("Starting V2->V1 translation handler");
         loop {
-            select! {
-                // Receive V1 frame and translate it to V2 message
-                v1_frame = self.v1_conn.next().timeout(StratumClient::EVENT_TIMEOUT).fuse() => {
-                    match v1_frame {
-                        Ok(Some(v1_frame)) => {
-                            let v1_msg = v1::build_message_from_frame(v1_frame?)?;
-                            v1_msg.accept(&mut self.translation).await;
-                        }
-                        Ok(None) | Err(_) => {
-                            Err("Upstream V1 stratum connection dropped terminating translation")?;
-                        }
-                    }
-                },
-                // Receive V2 frame from our client (no timeout needed) and pass it to V1
-                // translation
-                v2_frame = self.v2_client_rx.next().fuse() => {
-                    match v2_frame {
-                        Some(v2_frame) => {
-                            let v2_msg = v2::build_message_from_frame(v2_frame)?;
-                            v2_msg.accept(&mut self.translation).await;
-                        }
-                        None => {
-                            Err("V2 client shutdown, terminating translation")?;
+            if let Err(e) = self.handle_next_event().await {
+                return e;
+            }
+        }
+    }
+
+    /// Upstream connection that fails on I/O level (e.g. it has been reset) is considered
+    /// dropped, any other error is a stratum error
+    fn v1_conn_error(e: ii_stratum::error::Error) -> error::Connector {
+        match e.kind() {
+            ii_stratum::error::ErrorKind::Io(_) => {
+                debug!("V2->V1 translation: upstream connection failed: {}", e);
+                error::Connector::UpstreamDropped
+            }
+            _ => error::Connector::Stratum(e.to_string()),
+        }
+    }
+
+    /// Wait for one event (see `run`) and handle it
+    async fn handle_next_event(&mut self) -> Result<(), error::Connector> {
+        select! {
+            // Receive V1 frame and translate it to V2 message
+            v1_frame = self.v1_conn.next().timeout(self.event_timeout).fuse() => {
+                match v1_frame {
+                    Ok(Some(v1_frame)) => {
+                        let v1_frame = v1_frame.map_err(Self::v1_conn_error)?;
+                        let rpc = v1::rpc::Rpc::try_from(v1_frame)
+                            .map_err(|e| error::Connector::Stratum(e.to_string()))?;
+                        self.stats.touch().await;
+                        if let v1::rpc::Rpc::Request(request) = &rpc {
+                            if request.payload.method == v1::rpc::Method::ClientReconnect {
+                                self.stats.reconnect_messages.inc();
+                                Err(error::Connector::ReconnectRequested)?;
+                            }
                         }
+                        let v1_msg = v1::build_message_from_rpc(rpc)
+                            .map_err(|e| error::Connector::Stratum(e.to_string()))?;
+                        v1_msg.accept(&mut self.translation).await;
+                        self.stats.v1_to_v2_frames.inc();
                     }
-                },
-                // Receive V1 frame from the translation and send it upstream
-                v1_frame = self.v1_translation_rx.next().fuse() => {
-                    match v1_frame {
-                        Some(v1_frame) => self
-                            .v1_conn
-                            .send(v1_frame)
-                            // NOTE: this timeout is important otherwise the whole task could
-                            // block indefinitely and the above timeout for v1_conn_rx wouldn't
-                            // do anything. Besides this, we don't want to wait with system time
-                            // out in case the upstream connection just hangs
-                            .timeout(StratumClient::EVENT_TIMEOUT)
-                            .await
-                            // Unwrap timeout and actual sending error
-                            .map_err(|e| "V1 send timeout")??,
-                        None => {
-                            Err("V1 translation component terminated, terminating translation")?;
-                        }
+                    Ok(None) => Err(error::Connector::UpstreamDropped)?,
+                    Err(_) => Err(error::Connector::Timeout)?,
+                }
+            },
+            // Receive V2 frame from our client (no timeout needed) and pass it to V1
+            // translation
+            v2_frame = self.v2_client_rx.next().fuse() => {
+                match v2_frame {
+                    Some(v2_frame) => {
+                        self.stats.touch().await;
+                        let v2_msg = v2::build_message_from_frame(v2_frame)
+                            .map_err(|e| error::Connector::Stratum(e.to_string()))?;
+                        v2_msg.accept(&mut self.translation).await;
+                        self.stats.v2_to_v1_frames.inc();
                     }
-                },
-            }
+                    None => Err(error::Connector::ClientShutdown)?,
+                }
+            },
+            // Receive V1 frame from the translation and send it upstream
+            v1_frame = self.v1_translation_rx.next().fuse() => {
+                match v1_frame {
+                    Some(v1_frame) => self
+                        .v1_conn
+                        .send(v1_frame)
+                        // NOTE: this timeout is important otherwise the whole task could
+                        // block indefinitely and the above timeout for v1_conn_rx wouldn't
+                        // do anything. Besides this, we don't want to wait with system time
+                        // out in case the upstream connection just hangs
+                        .timeout(self.event_timeout)
+                        .await
+                        // Unwrap timeout and actual sending error
+                        .map_err(|_| error::Connector::Timeout)?
+                        .map_err(Self::v1_conn_error)?,
+                    None => Err(error::Connector::TranslationTerminated)?,
+                }
+            },
         }
+        Ok(())
     }
 }
 
@@ -942,3 +1145,170 @@ impl fmt::Display for StratumClient {
         )
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use bosminer_config::{ClientConnectionConfig, ClientUserInfo};
+
+    use tokio::net::{TcpListener, TcpStream};
+
+    fn create_descriptor(connection: ClientConnectionConfig) -> ClientDescriptor {
+        ClientDescriptor::create(
+            "stratum+tcp://pool.example.com",
+            &ClientUserInfo::new("user", None),
+            true,
+        )
+        .and_then(|descriptor| descriptor.with_connection(connection))
+        .expect("BUG: cannot create descriptor")
+    }
+
+    #[test]
+    fn test_connection_details_from_descriptor() {
+        let connection_details =
+            ConnectionDetails::from_descriptor(&create_descriptor(Default::default()));
+        assert_eq!(
+            connection_details.translation_channel_size,
+            TranslationHandler::DEFAULT_CHANNEL_SIZE
+        );
+        assert_eq!(
+            connection_details.event_timeout,
+            StratumClient::EVENT_TIMEOUT
+        );
+
+        let connection_details =
+            ConnectionDetails::from_descriptor(&create_descriptor(ClientConnectionConfig {
+                translation_channel_size: Some(2),
+                event_timeout_ms: Some(1500),
+                ..Default::default()
+            }));
+        assert_eq!(connection_details.translation_channel_size, 2);
+        assert_eq!(
+            connection_details.event_timeout,
+            time::Duration::from_millis(1500)
+        );
+    }
+
+    #[test]
+    fn test_connection_details_failover() {
+        let connection_details =
+            ConnectionDetails::from_descriptor(&create_descriptor(ClientConnectionConfig {
+                failover: Some(vec![
+                    "stratum+tcp://backup1.example.com:3334".to_string(),
+                    "stratum+tcp://backup2.example.com".to_string(),
+                ]),
+                ..Default::default()
+            }));
+        let hosts: Vec<_> = connection_details
+            .endpoints()
+            .iter()
+            .map(Endpoint::get_host_and_port)
+            .collect();
+        assert_eq!(
+            hosts,
+            vec![
+                "pool.example.com:3333",
+                "backup1.example.com:3334",
+                "backup2.example.com:3333"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_endpoint_rotation() {
+        let order: Vec<_> = StratumClient::endpoint_order(1, 3).collect();
+        assert_eq!(order, vec![1, 2, 0]);
+        let order: Vec<_> = StratumClient::endpoint_order(0, 1).collect();
+        assert_eq!(order, vec![0]);
+
+        // Broken primary endpoint is followed by the first failover
+        assert_eq!(StratumClient::next_endpoint_idx(0, 3, false), 1);
+        // Any failover session fails back to the primary endpoint
+        assert_eq!(StratumClient::next_endpoint_idx(1, 3, false), 0);
+        assert_eq!(StratumClient::next_endpoint_idx(2, 3, false), 0);
+        // Explicit reconnect resets to the primary endpoint
+        assert_eq!(StratumClient::next_endpoint_idx(0, 3, true), 0);
+        assert_eq!(StratumClient::next_endpoint_idx(2, 3, true), 0);
+        // Without failover there is nothing else to try
+        assert_eq!(StratumClient::next_endpoint_idx(0, 1, false), 0);
+    }
+
+    /// Verify that silent upstream V1 endpoint terminates the translation only after configured
+    /// event timeout elapses. The clock is paused and advanced manually so the test does not depend
+    /// on scheduling of the test machine.
+    #[tokio::test]
+    async fn test_translation_event_timeout() {
+        const EVENT_TIMEOUT: time::Duration = time::Duration::from_millis(300);
+        const MARGIN: time::Duration = time::Duration::from_millis(100);
+
+        // Mock V1 endpoint that accepts the connection and never responds
+        let mut listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("BUG: cannot bind mock V1 endpoint");
+        let addr = listener.local_addr().expect("BUG: missing local address");
+        let stream = TcpStream::connect(addr)
+            .await
+            .expect("BUG: cannot connect to mock V1 endpoint");
+        let (_v1_endpoint, _) = listener
+            .accept()
+            .await
+            .expect("BUG: mock V1 endpoint cannot accept");
+
+        tokio::time::pause();
+        let v1_conn = Connection::<v1::Framing>::new(stream).into_inner();
+        // Keep both V2 channel ends alive so that only upstream can terminate the translation
+        let (translation_handler, _v2_translation_rx, _v2_translation_tx, _stats) =
+            TranslationHandler::new(
+                v1_conn,
+                Default::default(),
+                TranslationHandler::DEFAULT_CHANNEL_SIZE,
+                EVENT_TIMEOUT,
+            );
+        let mut translation = Box::pin(translation_handler.run());
+        // Start waiting for upstream
+        assert!(futures::poll!(translation.as_mut()).is_pending());
+
+        // Upstream silent just under the limit keeps translation running
+        tokio::time::advance(EVENT_TIMEOUT - MARGIN).await;
+        assert!(futures::poll!(translation.as_mut()).is_pending());
+
+        // Upstream silent over the limit terminates translation with a timeout error
+        tokio::time::advance(2 * MARGIN).await;
+        assert_eq!(translation.await, error::Connector::Timeout);
+    }
+
+    /// Verify that upstream V1 connection reset terminates the translation as dropped upstream
+    #[tokio::test]
+    async fn test_translation_upstream_reset() {
+        let mut listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("BUG: cannot bind mock V1 endpoint");
+        let addr = listener.local_addr().expect("BUG: missing local address");
+        let stream = TcpStream::connect(addr)
+            .await
+            .expect("BUG: cannot connect to mock V1 endpoint");
+        let (v1_endpoint, _) = listener
+            .accept()
+            .await
+            .expect("BUG: mock V1 endpoint cannot accept");
+        // Closing socket with zero linger time resets the connection
+        v1_endpoint
+            .set_linger(Some(time::Duration::from_secs(0)))
+            .expect("BUG: cannot set linger");
+        drop(v1_endpoint);
+
+        let v1_conn = Connection::<v1::Framing>::new(stream).into_inner();
+        let (translation_handler, _v2_translation_rx, _v2_translation_tx, _stats) =
+            TranslationHandler::new(
+                v1_conn,
+                Default::default(),
+                TranslationHandler::DEFAULT_CHANNEL_SIZE,
+                time::Duration::from_secs(60),
+            );
+        assert_eq!(
+            translation_handler.run().await,
+            error::Connector::UpstreamDropped
+        );
+    }
+}
@@ -23,8 +23,10 @@
 //! The bosminer errors
 
 mod client;
+mod connector;
 
 pub use client::ErrorKind as Client;
+pub use connector::ErrorKind as Connector;
 
 use ii_async_compat::prelude::*;
 
@@ -58,6 +60,10 @@ pub enum ErrorKind {
     /// Error related to clients
     #[fail(display = "Client error: {}", _0)]
     Client(Client),
+
+    /// Error that terminated stratum connector
+    #[fail(display = "Connector error: {}", _0)]
+    Connector(Connector),
 }
 
 /// Implement Fail trait instead of use Derive to get more control over custom type.
@@ -124,6 +130,12 @@ impl From<Client> for Error {
     }
 }
 
+impl From<Connector> for Error {
+    fn from(connector: Connector) -> Self {
+        ErrorKind::Connector(connector).into()
+    }
+}
+
 impl From<Context<ErrorKind>> for Error {
     fn from(inner: Context<ErrorKind>) -> Self {
         Self { inner }
@@ -0,0 +1,40 @@
+// Copyright (C) 2019  Braiins Systems s.r.o.
+//
+// This file is part of Braiins Open-Source Initiative (BOSI).
+//
+// BOSI is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+//
+// Please, keep in mind that we may also license BOSI or any part thereof
+// under a proprietary license. For more information on the terms and conditions
+// of such proprietary license or if you have any other questions, please
+// contact us at opensource@braiins.com.
+
+use failure::Fail;
+
+/// Reasons why the connector (V2->V1 translation) terminated
+#[derive(Clone, Eq, PartialEq, Debug, Fail)]
+pub enum ErrorKind {
+    #[fail(display = "upstream stratum connection dropped")]
+    UpstreamDropped,
+    #[fail(display = "upstream server requested reconnect")]
+    ReconnectRequested,
+    #[fail(display = "client shutdown")]
+    ClientShutdown,
+    #[fail(display = "upstream connection timeout")]
+    Timeout,
+    #[fail(display = "translation component terminated")]
+    TranslationTerminated,
+    #[fail(display = "stratum error: {}", _0)]
+    Stratum(String),
+}
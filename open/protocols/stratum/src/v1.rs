@@ -100,7 +100,11 @@ pub fn build_message_from_frame(frame: framing::Frame) -> Result<Message<Protoco
     // TODO extend try_from to implement downcast from SerializablePayload/AnyPayload to Rpc (that
     //  implements SerializablePayload, too).
     let rpc = Rpc::try_from(frame)?;
+    build_message_from_rpc(rpc)
+}
 
+/// Variant of `build_message_from_frame` for callers that need to inspect the RPC first
+pub fn build_message_from_rpc(rpc: Rpc) -> Result<Message<Protocol>> {
     // The rest of the deserialization needs to be take care of also in case the Rpc was present
     // in the frame in deserialized form.
     trace!("V1: Deserialized V1 message payload: {:?}", rpc);
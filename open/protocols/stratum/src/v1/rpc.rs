@@ -59,6 +59,9 @@ pub enum Method {
     Notify,
     #[serde(rename = "mining.set_version_mask")]
     SetVersionMask,
+    /// Server request to reconnect (the only recognized method without the 'mining.' prefix)
+    #[serde(rename = "client.reconnect")]
+    ClientReconnect,
     /// Catch all variant
     #[serde(other)]
     Unknown,
@@ -134,7 +134,7 @@ pub trait Backoff: Send + fmt::Debug {
 
 /// Default `Backoff` implementation, based on the fibonacci sequence.
 #[derive(Debug)]
-struct DefaultBackoff {
+pub struct DefaultBackoff {
     current: u32,
     prev: u32,
     unit: Duration,